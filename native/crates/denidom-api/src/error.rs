@@ -0,0 +1,96 @@
+//! API error type and its JSON representation
+
+use axum::{
+    extract::rejection::JsonRejection,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use denidom_core::DeniDomError;
+use denidom_db::DbError;
+use serde_json::json;
+use tokio::task::JoinError;
+
+/// Error returned by API handlers
+#[derive(Debug)]
+pub enum ApiError {
+    /// Requested resource does not exist
+    NotFound(String),
+    /// Request is malformed or invalid
+    BadRequest(String),
+    /// Database failure
+    Database(DbError),
+    /// Calculation failure
+    Calculation(DeniDomError),
+    /// Blocking task panicked or was cancelled
+    Task(JoinError),
+}
+
+impl From<DbError> for ApiError {
+    fn from(err: DbError) -> Self {
+        ApiError::Database(err)
+    }
+}
+
+impl From<DeniDomError> for ApiError {
+    fn from(err: DeniDomError) -> Self {
+        ApiError::Calculation(err)
+    }
+}
+
+impl From<JoinError> for ApiError {
+    fn from(err: JoinError) -> Self {
+        ApiError::Task(err)
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::BadRequest(rejection.body_text())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::Database(err) => {
+                tracing::error!("database error: {}", err);
+                (StatusCode::INTERNAL_SERVER_ERROR, "database error".to_string())
+            }
+            ApiError::Calculation(
+                err @ (DeniDomError::Validation(_)
+                | DeniDomError::InvalidCoefficient(_)
+                | DeniDomError::Parse(_)),
+            ) => (StatusCode::UNPROCESSABLE_ENTITY, err.to_string()),
+            ApiError::Calculation(err) => {
+                tracing::error!("calculation error: {}", err);
+                (StatusCode::INTERNAL_SERVER_ERROR, "calculation error".to_string())
+            }
+            ApiError::Task(err) => {
+                tracing::error!("blocking task failed: {}", err);
+                (StatusCode::INTERNAL_SERVER_ERROR, "internal error".to_string())
+            }
+        };
+
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
+
+/// Result type alias for API handlers
+pub type ApiResult<T> = Result<T, ApiError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_input_is_unprocessable() {
+        let invalid = ApiError::from(DeniDomError::Validation("negative quantity".to_string()));
+        assert_eq!(invalid.into_response().status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let failed = ApiError::from(DeniDomError::Calculation("inconsistent totals".to_string()));
+        assert_eq!(failed.into_response().status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
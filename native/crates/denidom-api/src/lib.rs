@@ -6,24 +6,53 @@ use axum::{
     routing::{get, post},
     Router,
 };
+use denidom_db::DbPool;
 use tower_http::cors::CorsLayer;
 use std::net::SocketAddr;
 
 pub mod routes;
 pub mod handlers;
+pub mod error;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+/// Shared application state
+#[derive(Clone)]
+pub struct AppState {
+    /// Pooled database connections
+    pub db: DbPool,
+}
+
+impl AppState {
+    /// Create state over a database pool
+    pub fn new(db: DbPool) -> Self {
+        Self { db }
+    }
+}
 
 /// Create the API router
-pub fn create_router() -> Router {
-    Router::new()
+pub fn create_router(state: AppState) -> Router {
+    let router = Router::new()
         .route("/health", get(routes::health))
         .route("/api/calculate", post(routes::calculate))
-        .route("/api/normatives/search", get(routes::search_normatives))
+        .route("/api/calculate/explain", post(routes::calculate::explain))
+        .route("/api/normatives/search", get(routes::normatives::search))
+        .route("/api/normatives/:code/preview", get(routes::normatives::preview))
+        .route("/api/estimates/validate", post(routes::estimates::validate_estimate))
+        .route("/api/estimates/:id/calculate", post(routes::estimates::calculate_estimate))
+        .route("/api/estimates/:a/diff/:b", get(routes::estimates::diff_estimates));
+
+    #[cfg(feature = "metrics")]
+    let router = router.route("/metrics", get(metrics::metrics));
+
+    router
         .layer(CorsLayer::permissive())
+        .with_state(state)
 }
 
 /// Start the API server
-pub async fn start_server(addr: SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
-    let app = create_router();
+pub async fn start_server(addr: SocketAddr, state: AppState) -> Result<(), Box<dyn std::error::Error>> {
+    let app = create_router(state);
     
     tracing::info!("🚀 DeniDom API starting on {}", addr);
     
@@ -1,5 +1,7 @@
 //! DeniDom API Server Entry Point
 
+use denidom_api::AppState;
+use denidom_db::{DbPool, DEFAULT_POOL_SIZE};
 use std::net::SocketAddr;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -10,7 +12,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    let db_path = std::env::var("DENIDOM_DB_PATH").unwrap_or_else(|_| "denidom.db".to_string());
+    let db = DbPool::open(&db_path, DEFAULT_POOL_SIZE)?;
+    db.migrate()?;
+
     let addr = SocketAddr::from(([0, 0, 0, 0], 3001));
     
-    denidom_api::start_server(addr).await
+    denidom_api::start_server(addr, AppState::new(db)).await
 }
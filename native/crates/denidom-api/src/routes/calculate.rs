@@ -0,0 +1,311 @@
+//! Calculation routes
+
+use axum::{extract::rejection::JsonRejection, Json};
+use denidom_core::{
+    calculate_totals, explain_totals, CalculationSettings, CalculationStep, EstimateItem,
+    EstimateTotals,
+};
+use denidom_simd::{calculate_estimate_totals, CalculationTotals, ItemData};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+use crate::error::{ApiError, ApiResult};
+
+/// Items and settings for the fast calculation
+#[derive(Debug, Clone, Deserialize)]
+pub struct CalculateRequest {
+    /// Позиции сметы
+    pub items: Vec<ItemData>,
+    /// Настройки расчёта
+    #[serde(default)]
+    pub settings: denidom_simd::CalculationSettings,
+}
+
+impl CalculateRequest {
+    /// Reject items with a negative or non-finite quantity or unit cost
+    fn validate(&self) -> ApiResult<()> {
+        for (position, item) in self.items.iter().enumerate() {
+            let costs = &item.unit_costs;
+            let values = [
+                ("quantity", item.quantity),
+                ("unit cost direct", costs.direct),
+                ("unit cost labor", costs.labor),
+                ("unit cost machine_operator", costs.machine_operator),
+                ("unit cost materials", costs.materials),
+                ("unit cost machines", costs.machines),
+            ];
+            for (name, value) in values {
+                if !value.is_finite() || value < 0.0 {
+                    return Err(ApiError::BadRequest(format!(
+                        "item {}: {} = {} is negative or not a number",
+                        position + 1,
+                        name,
+                        value
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Items and settings for a step-by-step explanation
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExplainRequest {
+    /// Позиции сметы
+    pub items: Vec<EstimateItem>,
+    /// Настройки расчёта
+    #[serde(default)]
+    pub settings: CalculationSettings,
+}
+
+impl ExplainRequest {
+    /// Reject items with a negative quantity or unit cost
+    fn validate(&self) -> ApiResult<()> {
+        for item in &self.items {
+            if item.quantity < Decimal::ZERO {
+                return Err(ApiError::BadRequest(format!(
+                    "item {}: quantity {} is negative",
+                    item.code, item.quantity
+                )));
+            }
+            let costs = &item.unit_costs;
+            let components = [
+                ("direct", costs.direct),
+                ("labor", costs.labor),
+                ("machine_operator", costs.machine_operator),
+                ("materials", costs.materials),
+                ("machines", costs.machines),
+            ];
+            for (name, value) in components {
+                if value < Decimal::ZERO {
+                    return Err(ApiError::BadRequest(format!(
+                        "item {}: unit cost {} = {} is negative",
+                        item.code, name, value
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/// Totals with the formula behind each step
+#[derive(Debug, Clone, Serialize)]
+pub struct CalculationExplanation {
+    /// Итоги
+    pub totals: EstimateTotals,
+    /// Шаги расчёта в порядке выполнения
+    pub steps: Vec<CalculationStep>,
+}
+
+/// Run a calculation over `items` items, recording the item count and
+/// elapsed time in a tracing span and the duration metric
+pub(crate) fn timed<T>(items: usize, calculate: impl FnOnce() -> T) -> T {
+    let span = tracing::info_span!(
+        "calculate",
+        items,
+        elapsed_seconds = tracing::field::Empty
+    );
+    let _guard = span.enter();
+
+    let start = Instant::now();
+    let result = calculate();
+    let elapsed = start.elapsed();
+
+    span.record("elapsed_seconds", elapsed.as_secs_f64());
+    tracing::info!(
+        items,
+        elapsed_seconds = elapsed.as_secs_f64(),
+        "calculation finished"
+    );
+    #[cfg(feature = "metrics")]
+    crate::metrics::CALC_DURATION.observe(elapsed);
+
+    result
+}
+
+/// Calculate totals for the given items with the SIMD calculator
+///
+/// Malformed bodies and negative quantities or costs are rejected with 400.
+pub async fn calculate(
+    payload: Result<Json<CalculateRequest>, JsonRejection>,
+) -> ApiResult<Json<CalculationTotals>> {
+    let Json(request) = payload?;
+    request.validate()?;
+    Ok(Json(timed(request.items.len(), || {
+        calculate_estimate_totals(&request.items, &request.settings)
+    })))
+}
+
+/// Calculate totals and return the step-by-step formula breakdown
+pub async fn explain(
+    payload: Result<Json<ExplainRequest>, JsonRejection>,
+) -> ApiResult<Json<CalculationExplanation>> {
+    let Json(request) = payload?;
+    request.validate()?;
+    let items: Vec<&EstimateItem> = request.items.iter().collect();
+
+    Ok(Json(timed(items.len(), || CalculationExplanation {
+        totals: calculate_totals(&items, &request.settings),
+        steps: explain_totals(&items, &request.settings),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{create_router, AppState};
+    use axum::{
+        body::{to_bytes, Body},
+        http::{header, Request, StatusCode},
+    };
+    use denidom_core::{EstimateItem, MeasureUnit, UnitCosts};
+    use denidom_db::DbPool;
+    use denidom_simd::{calculate_totals_scalar, CalculationSettings, ItemData, UnitCostsData};
+    use rust_decimal::Decimal;
+    use tower::ServiceExt;
+
+    fn priced_item() -> EstimateItem {
+        let mut item = EstimateItem::new(
+            "ФЕР01-01-001-01".to_string(),
+            "Разработка грунта".to_string(),
+            MeasureUnit::CubicMeter,
+            Decimal::new(10, 0),
+        );
+        item.unit_costs = UnitCosts::new(
+            Decimal::new(500, 0),
+            Decimal::new(200, 0),
+            Decimal::ZERO,
+            Decimal::new(300, 0),
+            Decimal::ZERO,
+        );
+        item
+    }
+
+    async fn post_calculate(body: String) -> (StatusCode, serde_json::Value) {
+        let db = DbPool::open_in_memory().unwrap();
+        let request = Request::post("/api/calculate")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = create_router(AppState::new(db)).oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    fn item_data(quantity: f64) -> ItemData {
+        ItemData {
+            quantity,
+            unit_costs: UnitCostsData {
+                direct: 500.25,
+                labor: 200.1,
+                materials: 300.15,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_calculate_matches_scalar_totals() {
+        let items: Vec<ItemData> = (1..=37).map(|i| item_data(i as f64 * 1.3)).collect();
+        let settings = CalculationSettings {
+            index: 8.5,
+            ..Default::default()
+        };
+        let body = serde_json::json!({ "items": items, "settings": settings });
+
+        let (status, json) = post_calculate(body.to_string()).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let expected = calculate_totals_scalar(&items, &settings);
+        let expected = serde_json::to_value(expected).unwrap();
+        for field in ["direct_costs", "labor_costs", "overhead", "profit", "vat", "total"] {
+            let got = json[field].as_f64().unwrap();
+            let want = expected[field].as_f64().unwrap();
+            assert!((got - want).abs() <= want.abs() * 1e-12, "{}: {} != {}", field, got, want);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_calculate_rejects_invalid_body() {
+        let body = serde_json::json!({ "items": [item_data(-1.0)] }).to_string();
+        let (status, json) = post_calculate(body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(json["error"].as_str().unwrap().contains("item 1: quantity = -1"));
+
+        let (status, json) = post_calculate(r#"{"items": [{"quantity": "много"}]}"#.to_string()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(!json["error"].as_str().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_explain_includes_overhead_formula() {
+        let body = serde_json::json!({ "items": [priced_item()] }).to_string();
+
+        let db = DbPool::open_in_memory().unwrap();
+        let request = Request::post("/api/calculate/explain")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = create_router(AppState::new(db))
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let overhead = json["steps"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|step| step["step"] == "overhead")
+            .unwrap();
+
+        assert!(overhead["expression"]
+            .as_str()
+            .unwrap()
+            .contains("overhead = ФОТ × rate"));
+        // ФОТ 2000 × 12%
+        assert_eq!(
+            overhead["value"]
+                .as_str()
+                .unwrap()
+                .parse::<Decimal>()
+                .unwrap(),
+            Decimal::new(240, 0)
+        );
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_calculate_records_duration_metric() {
+        let router = create_router(AppState::new(DbPool::open_in_memory().unwrap()));
+
+        let body = serde_json::json!({ "items": [item_data(10.0)] }).to_string();
+        let request = Request::post("/api/calculate")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::get("/metrics").body(Body::empty()).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(text.contains("# TYPE calc_duration_seconds histogram"));
+        let count: u64 = text
+            .lines()
+            .find_map(|line| line.strip_prefix("calc_duration_seconds_count "))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(count >= 1);
+    }
+}
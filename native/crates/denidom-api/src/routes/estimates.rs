@@ -0,0 +1,271 @@
+//! Estimate routes
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    Json,
+};
+use denidom_core::{
+    calculate_estimate_report, try_calculate_estimate_totals, CalculationSettings, CalculationWarning,
+    Estimate, EstimateDiff, EstimateTotals, SettingsOverride,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::error::{ApiError, ApiResult};
+use crate::AppState;
+
+/// Totals of a recalculated estimate with its pricing gaps
+#[derive(Debug, Clone, Serialize)]
+pub struct EstimateCalculation {
+    /// Итоги сметы
+    #[serde(flatten)]
+    pub totals: EstimateTotals,
+    /// Позиции без цены
+    pub unpriced_items: Vec<Uuid>,
+}
+
+/// Result of a dry-run estimate validation
+#[derive(Debug, Clone, Serialize)]
+pub struct EstimateValidation {
+    /// Смета пригодна для сохранения
+    pub valid: bool,
+    /// Ошибки, препятствующие расчёту
+    pub problems: Vec<String>,
+    /// Некритичные предупреждения
+    pub warnings: Vec<CalculationWarning>,
+}
+
+fn load(state: &AppState, id: Uuid) -> ApiResult<Estimate> {
+    state
+        .db
+        .load_estimate(id)?
+        .ok_or_else(|| ApiError::NotFound(format!("estimate {}", id)))
+}
+
+/// Recompute a stored estimate with optional settings overrides.
+/// The stored estimate is not modified.
+///
+/// An empty body means no overrides; a body that is not a valid
+/// [`SettingsOverride`] is rejected with 400.
+pub async fn calculate_estimate(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    body: Bytes,
+) -> ApiResult<Json<EstimateCalculation>> {
+    let overrides: SettingsOverride = if body.iter().all(u8::is_ascii_whitespace) {
+        SettingsOverride::default()
+    } else {
+        serde_json::from_slice(&body)
+            .map_err(|e| ApiError::BadRequest(format!("invalid settings override: {}", e)))?
+    };
+
+    // rusqlite and the calculation block, keep them off the async workers
+    tokio::task::spawn_blocking(move || {
+        let estimate = load(&state, id)?;
+        let settings = CalculationSettings::from_estimate_with(&estimate, overrides);
+
+        let totals = crate::routes::calculate::timed(estimate.all_items().len(), || {
+            try_calculate_estimate_totals(&estimate, &settings)
+        })?;
+
+        Ok(Json(EstimateCalculation {
+            totals,
+            unpriced_items: estimate.unpriced_items().iter().map(|item| item.id).collect(),
+        }))
+    })
+    .await?
+}
+
+/// Validate an uploaded estimate and its coefficients without saving it
+pub async fn validate_estimate(Json(estimate): Json<Estimate>) -> Json<EstimateValidation> {
+    let mut problems: Vec<String> = Vec::new();
+    if let Err(errors) = estimate.validate() {
+        problems.extend(errors.iter().map(|e| e.to_string()));
+    }
+    if let Err(errors) = estimate.coefficients.validate() {
+        problems.extend(errors.iter().map(|e| e.to_string()));
+    }
+
+    Json(EstimateValidation {
+        valid: problems.is_empty(),
+        problems,
+        warnings: calculate_estimate_report(&estimate).warnings,
+    })
+}
+
+/// Compare two stored estimates, `a` as the old version and `b` as the new one
+pub async fn diff_estimates(
+    State(state): State<AppState>,
+    Path((a, b)): Path<(Uuid, Uuid)>,
+) -> ApiResult<Json<EstimateDiff>> {
+    tokio::task::spawn_blocking(move || {
+        let old = load(&state, a)?;
+        let new = load(&state, b)?;
+        Ok(Json(EstimateDiff::between(&old, &new)))
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_router;
+    use axum::{
+        body::{to_bytes, Body},
+        http::{header, Request, StatusCode},
+    };
+    use denidom_core::{EstimateItem, EstimateSection, MeasureUnit, UnitCosts};
+    use denidom_db::DbPool;
+    use rust_decimal::Decimal;
+    use tower::ServiceExt;
+
+    fn test_state() -> (AppState, Estimate) {
+        let db = DbPool::open_in_memory().unwrap();
+        db.migrate().unwrap();
+
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Земляные работы".to_string());
+        let mut item = EstimateItem::new(
+            "ФЕР01-01-001-01".to_string(),
+            "Разработка грунта".to_string(),
+            MeasureUnit::CubicMeter,
+            Decimal::new(10, 0),
+        );
+        item.unit_costs = UnitCosts::new(
+            Decimal::new(500, 0),
+            Decimal::new(200, 0),
+            Decimal::ZERO,
+            Decimal::new(300, 0),
+            Decimal::ZERO,
+        );
+        section.add_item(item);
+        estimate.sections.push(section);
+        db.save_estimate(&estimate, "test").unwrap();
+
+        (AppState::new(db), estimate)
+    }
+
+    async fn post_calculate(state: AppState, id: Uuid, body: &str) -> (StatusCode, serde_json::Value) {
+        let request = Request::post(format!("/api/estimates/{}/calculate", id))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = create_router(state).oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    fn total_of(body: &serde_json::Value) -> Decimal {
+        body["total"].as_str().unwrap().parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_calculate_with_index_override() {
+        let (state, estimate) = test_state();
+
+        let (status, base) = post_calculate(state.clone(), estimate.id, "{}").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(base["unpriced_items"], serde_json::json!([]));
+
+        let (status, scaled) = post_calculate(state.clone(), estimate.id, r#"{"index": "2"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(total_of(&scaled), total_of(&base) * Decimal::TWO);
+
+        let stored = state.db.load_estimate(estimate.id).unwrap().unwrap();
+        assert_eq!(stored.coefficients.index, Decimal::ONE);
+    }
+
+    #[tokio::test]
+    async fn test_calculate_override_body() {
+        let (state, estimate) = test_state();
+
+        let (status, base) = post_calculate(state.clone(), estimate.id, "{}").await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, empty) = post_calculate(state.clone(), estimate.id, "").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(total_of(&empty), total_of(&base));
+
+        for malformed in [r#"{"index": "два"}"#, r#"{"index": 2"#, "[]"] {
+            let (status, body) = post_calculate(state.clone(), estimate.id, malformed).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", malformed);
+            assert!(body["error"].as_str().unwrap().contains("invalid settings override"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_calculate_matches_estimate_totals() {
+        let (state, mut estimate) = test_state();
+        estimate.sections[0].coefficient = Some(Decimal::new(115, 2));
+        estimate.coefficients.apply_winter(4, "Земляные работы").unwrap();
+        state.db.save_estimate(&estimate, "test").unwrap();
+
+        let (status, body) = post_calculate(state, estimate.id, "").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(total_of(&body), denidom_core::calculate_estimate_totals(&estimate).total);
+    }
+
+    #[tokio::test]
+    async fn test_calculate_unknown_estimate() {
+        let (state, _) = test_state();
+        let (status, _) = post_calculate(state, Uuid::new_v4(), "{}").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    async fn get_diff(state: AppState, a: Uuid, b: Uuid) -> (StatusCode, Vec<u8>) {
+        let request = Request::get(format!("/api/estimates/{}/diff/{}", a, b))
+            .body(Body::empty())
+            .unwrap();
+        let response = create_router(state).oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, bytes.to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_validate_invalid_estimate() {
+        let (state, mut estimate) = test_state();
+        estimate.sections[0].items[0].quantity = Decimal::new(-5, 0);
+        estimate.coefficients.index = Decimal::new(500, 0);
+
+        let request = Request::post("/api/estimates/validate")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(&estimate).unwrap()))
+            .unwrap();
+        let response = create_router(state.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+        assert_eq!(body["valid"], false);
+        let problems = body["problems"].as_array().unwrap();
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].as_str().unwrap().contains("quantity -5 is negative"));
+        assert!(problems[1].as_str().unwrap().contains("coefficient index"));
+
+        let stored = state.db.load_estimate(estimate.id).unwrap().unwrap();
+        assert_eq!(stored.sections[0].items[0].quantity, Decimal::new(10, 0));
+    }
+
+    #[tokio::test]
+    async fn test_diff_estimates() {
+        let (state, estimate) = test_state();
+        let mut revised = estimate.clone();
+        revised.id = Uuid::new_v4();
+        revised.sections[0].items[0].quantity = Decimal::new(15, 0);
+        state.db.save_estimate(&revised, "test").unwrap();
+
+        let (status, body) = get_diff(state.clone(), estimate.id, revised.id).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let diff: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(diff["changed"].as_array().unwrap().len(), 1);
+        assert_eq!(diff["changed"][0]["changes"][0]["field"], "quantity");
+        assert!(diff["added"].as_array().unwrap().is_empty());
+        assert!(diff["removed"].as_array().unwrap().is_empty());
+
+        let (status, _) = get_diff(state, estimate.id, Uuid::new_v4()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -1,5 +1,11 @@
 //! API Routes
 
+pub mod calculate;
+pub mod estimates;
+pub mod normatives;
+
+pub use calculate::calculate;
+
 use axum::{Json, response::IntoResponse};
 use serde_json::json;
 
@@ -11,17 +17,3 @@ pub async fn health() -> impl IntoResponse {
         "version": "0.1.0"
     }))
 }
-
-/// Calculate estimate
-pub async fn calculate() -> impl IntoResponse {
-    Json(json!({
-        "message": "Calculate endpoint - coming soon"
-    }))
-}
-
-/// Search normatives
-pub async fn search_normatives() -> impl IntoResponse {
-    Json(json!({
-        "message": "Search normatives - coming soon"
-    }))
-}
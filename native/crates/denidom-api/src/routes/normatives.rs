@@ -0,0 +1,153 @@
+//! Normative routes
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use denidom_core::{CalculationSettings, EstimateTotals, NormativeBase, NormativeItem};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::error::{ApiError, ApiResult};
+use crate::AppState;
+
+/// Default number of search results
+pub const DEFAULT_SEARCH_LIMIT: usize = 50;
+/// Upper bound on the requested number of search results
+pub const MAX_SEARCH_LIMIT: usize = 500;
+
+/// Text, base and limit of a normative search
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SearchQuery {
+    /// Фрагмент шифра или наименования
+    #[serde(default)]
+    pub q: String,
+    /// Нормативная база (FER, GESN, TER)
+    pub base: Option<NormativeBase>,
+    /// Максимальное число результатов
+    pub limit: Option<usize>,
+}
+
+/// Search normatives by code or name fragment
+///
+/// `limit` defaults to [`DEFAULT_SEARCH_LIMIT`] and is capped at
+/// [`MAX_SEARCH_LIMIT`]; no matches yield an empty array.
+pub async fn search(
+    State(state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+) -> ApiResult<Json<Vec<NormativeItem>>> {
+    let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).min(MAX_SEARCH_LIMIT);
+    // r2d2 and rusqlite block, keep them off the async workers
+    tokio::task::spawn_blocking(move || {
+        Ok(Json(state.db.search_normatives(&query.q, query.base, limit)?))
+    })
+    .await?
+}
+
+/// Quantity and optional index for a normative preview
+#[derive(Debug, Clone, Deserialize)]
+pub struct PreviewQuery {
+    /// Количество в единицах измерения расценки
+    pub quantity: Decimal,
+    /// Индекс пересчёта
+    pub index: Option<Decimal>,
+}
+
+/// Full totals of a single normative at the given quantity
+pub async fn preview(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+    Query(query): Query<PreviewQuery>,
+) -> ApiResult<Json<EstimateTotals>> {
+    let normative = tokio::task::spawn_blocking(move || {
+        state
+            .db
+            .get_normative(&code)?
+            .ok_or_else(|| ApiError::NotFound(format!("normative {}", code)))
+    })
+    .await??;
+
+    let mut settings = CalculationSettings::default();
+    if let Some(index) = query.index {
+        settings.index = index;
+    }
+
+    Ok(Json(normative.preview(query.quantity, &settings)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{create_router, AppState};
+    use axum::{
+        body::{to_bytes, Body},
+        http::{Request, StatusCode},
+    };
+    use denidom_core::{NormativeBase, NormativeItem, UnitCosts};
+    use denidom_db::DbPool;
+    use rust_decimal::Decimal;
+    use tower::ServiceExt;
+
+    fn seeded_state() -> AppState {
+        let db = DbPool::open_in_memory().unwrap();
+        db.migrate().unwrap();
+
+        let mut item = NormativeItem::new(
+            "ФЕР01-01-001-01".to_string(),
+            "Разработка грунта".to_string(),
+            NormativeBase::FER,
+        );
+        item.costs = UnitCosts::new(
+            Decimal::new(5000, 0),
+            Decimal::new(1200, 0),
+            Decimal::new(300, 0),
+            Decimal::ZERO,
+            Decimal::new(3500, 0),
+        );
+        db.insert_normatives_bulk(&[item]).unwrap();
+
+        AppState::new(db)
+    }
+
+    async fn get(state: AppState, uri: &str) -> (StatusCode, serde_json::Value) {
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        let response = create_router(state).oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_search_normatives() {
+        let state = seeded_state();
+
+        let (status, body) = get(state.clone(), "/api/normatives/search?q=%D0%B3%D1%80%D1%83%D0%BD%D1%82&base=FER").await;
+        assert_eq!(status, StatusCode::OK);
+        let rows = body.as_array().unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["code"], "ФЕР01-01-001-01");
+
+        let (status, body) = get(state.clone(), "/api/normatives/search?q=01-01&base=GESN").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, serde_json::json!([]));
+
+        let (status, body) = get(state, "/api/normatives/search?q=nothing&limit=10000").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_preview_normative() {
+        let state = seeded_state();
+        let uri = "/api/normatives/%D0%A4%D0%95%D0%A001-01-001-01/preview?quantity=100&index=8.5";
+
+        let (status, body) = get(state.clone(), uri).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let direct: Decimal = body["direct_costs"].as_str().unwrap().parse().unwrap();
+        // 100 × 5000 × 8.5
+        assert_eq!(direct, Decimal::new(4_250_000, 0));
+
+        let (status, _) = get(state, "/api/normatives/UNKNOWN-01/preview?quantity=1").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
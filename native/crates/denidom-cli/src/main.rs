@@ -3,9 +3,15 @@
 //! High-performance construction estimate calculations from the command line.
 
 use clap::{Parser, Subcommand};
+use denidom_core::{DeniDomError, Estimate, NormativeBase};
+use denidom_db::Database;
 use denidom_simd::{
-    calculate_estimate_totals, CalculationSettings, ItemData, UnitCostsData,
+    calculate_estimate_totals, compiled_backends, CalculationSettings, ItemData, UnitCostsData,
 };
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 #[derive(Parser)]
@@ -51,8 +57,44 @@ enum Commands {
         verbose: bool,
     },
 
+    /// Импортировать нормативы ФЕР из CSV файла
+    Import {
+        /// Путь к CSV файлу с расценками
+        #[arg(short, long)]
+        input: String,
+    },
+
+    /// Найти нормативы в локальной базе
+    Search {
+        /// Путь к базе данных SQLite
+        #[arg(long)]
+        db: PathBuf,
+
+        /// Фрагмент шифра или наименования
+        query: String,
+
+        /// Нормативная база (ФЕР, ГЭСН, ТЕР, ...)
+        #[arg(short, long)]
+        base: Option<String>,
+
+        /// Максимальное количество результатов
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Проверить смету из JSON файла перед утверждением
+    Validate {
+        /// Путь к JSON файлу со сметой
+        #[arg(short, long)]
+        input: String,
+    },
+
     /// Показать информацию о системе
-    Info,
+    Info {
+        /// Вывести в формате JSON
+        #[arg(long)]
+        json: bool,
+    },
 
     /// Демонстрация расчёта
     Demo {
@@ -80,8 +122,26 @@ fn main() -> anyhow::Result<()> {
         } => {
             run_calculation(&input, output.as_deref(), verbose)?;
         }
-        Commands::Info => {
-            show_info();
+        Commands::Import { input } => {
+            run_import(&input)?;
+        }
+        Commands::Search {
+            db,
+            query,
+            base,
+            limit,
+        } => {
+            run_search(&mut std::io::stdout(), &db, &query, base.as_deref(), limit)?;
+        }
+        Commands::Validate { input } => {
+            run_validate(&input)?;
+        }
+        Commands::Info { json } => {
+            if json {
+                show_info_json()?;
+            } else {
+                show_info();
+            }
         }
         Commands::Demo { items } => {
             run_demo(items)?;
@@ -209,6 +269,171 @@ fn run_calculation(input: &str, output: Option<&str>, verbose: bool) -> anyhow::
     Ok(())
 }
 
+fn run_import(input: &str) -> anyhow::Result<()> {
+    println!("📂 Импорт нормативов из: {}", input);
+
+    let start = Instant::now();
+    let mut report = |rows: usize, _total: Option<usize>| {
+        print!("\r⏳ Обработано строк: {}", rows);
+        let _ = std::io::stdout().flush();
+    };
+    let result = std::fs::File::open(input)
+        .map_err(DeniDomError::from)
+        .and_then(|file| {
+            denidom_parser::parse_fer_csv_with_options(
+                file,
+                denidom_parser::FerImportOptions::new().progress(&mut report),
+            )
+        });
+    println!();
+
+    match result.map(|import| import.items) {
+        Ok(items) => {
+            println!("✅ Загружено расценок: {}", items.len());
+            println!("⚡ Время импорта: {:?}", start.elapsed());
+            Ok(())
+        }
+        Err(DeniDomError::ParseAt { message, context }) => {
+            println!("❌ Ошибка разбора: {}", message);
+            if let Some(line) = context.line {
+                match context.column {
+                    Some(column) => println!("   Строка {}, столбец {}", line, column),
+                    None => println!("   Строка {}", line),
+                }
+            }
+            if let Some(snippet) = context.snippet {
+                println!("   > {}", snippet);
+            }
+            anyhow::bail!("импорт прерван")
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn run_search(
+    out: &mut impl Write,
+    db: &Path,
+    query: &str,
+    base: Option<&str>,
+    limit: usize,
+) -> anyhow::Result<()> {
+    let base = match base {
+        Some(name) => Some(
+            NormativeBase::from_code_prefix(name)
+                .ok_or_else(|| anyhow::anyhow!("неизвестная нормативная база: {}", name))?,
+        ),
+        None => None,
+    };
+
+    let db = Database::open(&db.to_string_lossy())?;
+    let items = db.search_normatives(query, base, limit)?;
+
+    if items.is_empty() {
+        writeln!(out, "🔍 Ничего не найдено по запросу «{}»", query)?;
+        return Ok(());
+    }
+
+    writeln!(out, "{:<20} {:<50} {:<10} {:>14}", "Шифр", "Наименование", "Ед. изм.", "ПЗ, ₽")?;
+    writeln!(out, "{}", "─".repeat(97))?;
+    for item in &items {
+        let name: String = item.name.chars().take(50).collect();
+        writeln!(
+            out,
+            "{:<20} {:<50} {:<10} {:>14.2}",
+            item.code,
+            name,
+            item.unit.abbreviation(),
+            item.costs.direct
+        )?;
+    }
+    writeln!(out, "Найдено: {}", items.len())?;
+
+    Ok(())
+}
+
+fn run_validate(input: &str) -> anyhow::Result<()> {
+    println!("📂 Проверка сметы: {}", input);
+
+    let estimate: Estimate = serde_json::from_str(&std::fs::read_to_string(input)?)?;
+    let warnings = validation_warnings(&estimate);
+
+    if warnings.is_empty() {
+        println!("✅ Замечаний нет");
+    } else {
+        for warning in &warnings {
+            println!("⚠️  {}", warning);
+        }
+    }
+
+    Ok(())
+}
+
+/// Human-readable warnings for an estimate
+fn validation_warnings(estimate: &Estimate) -> Vec<String> {
+    let mut warnings: Vec<String> = estimate
+        .unpriced_items()
+        .into_iter()
+        .map(|item| format!("Позиция {} ({}) не имеет цены", item.position, item.code))
+        .collect();
+
+    let bases = estimate.base_types_used();
+    if bases.len() > 1 {
+        let mut names: Vec<&str> = bases.iter().map(|base| base.display_name()).collect();
+        names.sort_unstable();
+        warnings.push(format!("Смешаны нормативные базы: {}", names.join(", ")));
+    }
+
+    if let Err(errors) = estimate.coefficients.validate() {
+        warnings.extend(errors.iter().map(|err| format!("Недопустимый коэффициент: {}", err)));
+    }
+
+    warnings
+}
+
+/// Machine-readable system information for `info --json`
+#[derive(Serialize)]
+struct SystemInfo {
+    version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    cpu_features: BTreeMap<&'static str, bool>,
+    backends: Vec<&'static str>,
+}
+
+/// Runtime-detected CPU features relevant to the SIMD backends
+fn cpu_features() -> Vec<(&'static str, bool)> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        vec![
+            ("SSE4.1", is_x86_feature_detected!("sse4.1")),
+            ("AVX", is_x86_feature_detected!("avx")),
+            ("AVX2", is_x86_feature_detected!("avx2")),
+            ("AVX-512", is_x86_feature_detected!("avx512f")),
+            ("FMA", is_x86_feature_detected!("fma")),
+        ]
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        Vec::new()
+    }
+}
+
+fn system_info() -> SystemInfo {
+    SystemInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        cpu_features: cpu_features().into_iter().collect(),
+        backends: compiled_backends(),
+    }
+}
+
+fn show_info_json() -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(&system_info())?);
+    Ok(())
+}
+
 fn show_info() {
     println!("╔═══════════════════════════════════════╗");
     println!("║     🏠 DeniDom Native v0.1.0          ║");
@@ -221,50 +446,21 @@ fn show_info() {
     println!("   ОС:          {}", std::env::consts::OS);
     println!("   Архитектура: {}", std::env::consts::ARCH);
 
-    #[cfg(target_arch = "x86_64")]
-    {
+    let features = cpu_features();
+    if !features.is_empty() {
         println!();
         println!("🔧 CPU возможности:");
-        println!(
-            "   SSE4.1:  {}",
-            if is_x86_feature_detected!("sse4.1") {
-                "✅ Поддерживается"
-            } else {
-                "❌ Не поддерживается"
-            }
-        );
-        println!(
-            "   AVX:     {}",
-            if is_x86_feature_detected!("avx") {
-                "✅ Поддерживается"
-            } else {
-                "❌ Не поддерживается"
-            }
-        );
-        println!(
-            "   AVX2:    {}",
-            if is_x86_feature_detected!("avx2") {
-                "✅ Поддерживается"
-            } else {
-                "❌ Не поддерживается"
-            }
-        );
-        println!(
-            "   AVX-512: {}",
-            if is_x86_feature_detected!("avx512f") {
-                "✅ Поддерживается"
-            } else {
-                "❌ Не поддерживается"
-            }
-        );
-        println!(
-            "   FMA:     {}",
-            if is_x86_feature_detected!("fma") {
-                "✅ Поддерживается"
-            } else {
-                "❌ Не поддерживается"
-            }
-        );
+        for (name, supported) in features {
+            println!(
+                "   {:<8} {}",
+                format!("{}:", name),
+                if supported {
+                    "✅ Поддерживается"
+                } else {
+                    "❌ Не поддерживается"
+                }
+            );
+        }
     }
 
     println!();
@@ -303,6 +499,7 @@ fn run_demo(items: usize) -> anyhow::Result<()> {
         profit_rate: 0.08,
         vat_rate: 0.20,
         index: 8.5, // Typical index for 2024
+        deterministic: false,
     };
 
     println!("📋 Параметры расчёта:");
@@ -339,3 +536,73 @@ fn run_demo(items: usize) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use denidom_core::{EstimateItem, EstimateSection, MeasureUnit};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_search_prints_matches() {
+        use denidom_core::NormativeItem;
+
+        let path = std::env::temp_dir().join(format!("denidom-cli-search-{}.db", std::process::id()));
+        let db = Database::open(&path.to_string_lossy()).unwrap();
+        db.migrate().unwrap();
+        let mut item = NormativeItem::new(
+            "ФЕР01-01-001-01".to_string(),
+            "Разработка грунта".to_string(),
+            NormativeBase::FER,
+        );
+        item.costs.direct = Decimal::new(500050, 2);
+        db.insert_normatives_bulk(&[item]).unwrap();
+        drop(db);
+
+        let mut out = Vec::new();
+        run_search(&mut out, &path, "грунт", Some("ФЕР"), 10).unwrap();
+        let output = String::from_utf8(out).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(output.contains("ФЕР01-01-001-01"));
+        assert!(output.contains("5000.50"));
+        assert!(output.contains("Найдено: 1"));
+    }
+
+    #[test]
+    fn test_validate_warns_on_unpriced_items() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        let mut item = EstimateItem::new(
+            "ФЕР15-01-002-01".to_string(),
+            "Штукатурка".to_string(),
+            MeasureUnit::SquareMeter100,
+            Decimal::ONE,
+        );
+        item.position = 3;
+        section.add_item(item);
+        estimate.sections.push(section);
+
+        let warnings = validation_warnings(&estimate);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("ФЕР15-01-002-01"));
+
+        estimate.coefficients.index = Decimal::new(850, 0);
+        let warnings = validation_warnings(&estimate);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[1].contains("index = 850"));
+    }
+
+    #[test]
+    fn test_info_json() {
+        let json = serde_json::to_string(&system_info()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["arch"], std::env::consts::ARCH);
+        assert_eq!(value["os"], std::env::consts::OS);
+        assert!(value["backends"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("scalar")));
+    }
+}
@@ -3,29 +3,48 @@
 //! Provides high-performance calculation functions for estimates.
 
 use rust_decimal::Decimal;
-use crate::estimate::{CalculationSettings, Estimate, EstimateItem, EstimateTotals};
+use serde::{Deserialize, Serialize};
+use crate::error::{DeniDomError, Result};
+use crate::estimate::{
+    CalculationSettings, Estimate, EstimateItem, EstimateTotals, OverheadMethod, ProfitMethod,
+    TaxationRegime,
+};
 
-/// Calculate totals for an estimate
+/// Calculate totals for an estimate, with section coefficients applied
 pub fn calculate_estimate_totals(estimate: &Estimate) -> EstimateTotals {
-    let settings = estimate.calculation_settings();
-    let items: Vec<&EstimateItem> = estimate.all_items();
-    
-    calculate_totals(&items, &settings)
+    estimate.totals_of(estimate.lines(), &estimate.calculation_settings())
 }
 
 /// Calculate totals from items and settings
 pub fn calculate_totals(items: &[&EstimateItem], settings: &CalculationSettings) -> EstimateTotals {
+    let mut totals = sum_item_costs(items.iter().map(|item| (*item, Decimal::ONE)));
+    apply_index_and_markups(&mut totals, settings);
+    totals
+}
+
+/// Sum base-price cost components of items, each scaled by a line factor
+/// (the coefficient of its section); index and markups are not applied
+pub(crate) fn sum_item_costs<'a>(lines: impl IntoIterator<Item = (&'a EstimateItem, Decimal)>) -> EstimateTotals {
     let mut totals = EstimateTotals::default();
-    
-    // Sum up all costs
-    for item in items {
-        totals.direct_costs += item.total_direct_cost();
-        totals.labor_costs += item.total_labor_cost();
-        totals.machine_operator_costs += item.quantity * item.unit_costs.machine_operator;
-        totals.material_costs += item.total_material_cost();
-        totals.machine_costs += item.total_machine_cost();
+    for (item, factor) in lines {
+        totals.direct_costs += item.total_direct_cost() * factor;
+        totals.labor_costs += item.total_labor_cost() * factor;
+        totals.machine_operator_costs += item.total_machine_operator_cost() * factor;
+        totals.material_costs += item.total_material_cost() * factor;
+        totals.machine_costs += item.total_machine_cost() * factor;
     }
-    
+    totals
+}
+
+/// Turn summed base-price cost components into final totals: apply the
+/// index and УСН VAT compensation, then overhead, profit, contingency and VAT
+pub fn apply_index_and_markups(totals: &mut EstimateTotals, settings: &CalculationSettings) {
+    apply_index(totals, settings);
+    apply_markups(totals, settings);
+}
+
+/// Apply the index and УСН VAT compensation to the cost components
+pub(crate) fn apply_index(totals: &mut EstimateTotals, settings: &CalculationSettings) {
     // Apply index
     totals.direct_costs *= settings.index;
     totals.labor_costs *= settings.index;
@@ -33,23 +52,214 @@ pub fn calculate_totals(items: &[&EstimateItem], settings: &CalculationSettings)
     totals.material_costs *= settings.index;
     totals.machine_costs *= settings.index;
     
-    // Calculate overhead from labor costs (ФОТ = ОЗП + ЗПМ)
+    // Компенсация НДС при УСН raises the material and machine base
+    if settings.taxation == TaxationRegime::Usn {
+        if let Some(compensation) = settings.usn_vat_compensation {
+            let base = totals.material_costs + totals.machine_costs;
+            totals.material_costs *= compensation;
+            totals.machine_costs *= compensation;
+            totals.direct_costs += base * (compensation - Decimal::ONE);
+        }
+    }
+}
+
+/// Add overhead, profit, contingency and VAT on top of the cost components
+pub(crate) fn apply_markups(totals: &mut EstimateTotals, settings: &CalculationSettings) {
+    apply_markups_shared(totals, settings, Decimal::ONE);
+}
+
+/// [`apply_markups`] for a part of an estimate that carries `fixed_share`
+/// of the fixed-sum overhead and profit
+pub(crate) fn apply_markups_shared(
+    totals: &mut EstimateTotals,
+    settings: &CalculationSettings,
+    fixed_share: Decimal,
+) {
+    // Overhead and profit from ФОТ (ОЗП + ЗПМ), from direct costs or as a fixed sum
     let labor_total = totals.labor_costs + totals.machine_operator_costs;
-    totals.overhead = labor_total * settings.overhead_rate;
+    totals.overhead = match settings.overhead_method {
+        OverheadMethod::FromLaborCost => labor_total * settings.overhead_rate,
+        OverheadMethod::FromDirectCost => totals.direct_costs * settings.overhead_rate,
+        OverheadMethod::Fixed => settings.overhead_rate * fixed_share,
+    };
+    totals.profit = match settings.profit_method {
+        ProfitMethod::FromLaborCost => labor_total * settings.profit_rate,
+        ProfitMethod::FromDirectCost => totals.direct_costs * settings.profit_rate,
+        ProfitMethod::Fixed => settings.profit_rate * fixed_share,
+    };
     
-    // Calculate profit from labor costs
-    totals.profit = labor_total * settings.profit_rate;
+    // Subtotal with the contingency reserve on top
+    let base = totals.direct_costs + totals.overhead + totals.profit;
+    totals.contingency = base * settings.contingency_rate;
+    totals.subtotal = base + totals.contingency;
     
-    // Subtotal
-    totals.subtotal = totals.direct_costs + totals.overhead + totals.profit;
-    
-    // VAT
-    totals.vat = totals.subtotal * settings.vat_rate;
+    // VAT (not charged under УСН)
+    totals.vat = match settings.taxation {
+        TaxationRegime::Osn => totals.subtotal * settings.vat_rate,
+        TaxationRegime::Usn => Decimal::ZERO,
+    };
     
     // Total
     totals.total = totals.subtotal + totals.vat;
-    
-    totals
+}
+
+/// Calculate totals and verify they add up within [`RECONCILE_TOLERANCE`]
+///
+/// Guards document generation against arithmetic bugs in the calculation.
+pub fn try_calculate_totals(
+    items: &[&EstimateItem],
+    settings: &CalculationSettings,
+) -> Result<EstimateTotals> {
+    reconciled(calculate_totals(items, settings))
+}
+
+/// Calculate estimate totals under `settings` in place of the estimate's own
+/// (e.g. from [`CalculationSettings::from_estimate_with`]) and verify they
+/// add up within [`RECONCILE_TOLERANCE`]. Section coefficients and the
+/// differentiated winter coefficient apply as in [`calculate_estimate_totals`].
+pub fn try_calculate_estimate_totals(
+    estimate: &Estimate,
+    settings: &CalculationSettings,
+) -> Result<EstimateTotals> {
+    reconciled(estimate.totals_of(estimate.lines(), settings))
+}
+
+fn reconciled(totals: EstimateTotals) -> Result<EstimateTotals> {
+    if !totals.is_consistent(RECONCILE_TOLERANCE) {
+        return Err(DeniDomError::Calculation(format!(
+            "inconsistent totals: subtotal {}, total {}",
+            totals.subtotal, totals.total
+        )));
+    }
+    Ok(totals)
+}
+
+/// Шаг расчёта с формулой
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalculationStep {
+    /// Идентификатор шага
+    pub step: String,
+    /// Формула с подставленными значениями
+    pub expression: String,
+    /// Результат шага
+    pub value: Decimal,
+}
+
+impl CalculationStep {
+    fn new(step: &str, expression: String, value: Decimal) -> Self {
+        Self {
+            step: step.to_string(),
+            expression,
+            value,
+        }
+    }
+}
+
+/// Step-by-step breakdown of [`calculate_totals`], in calculation order
+pub fn explain_totals(items: &[&EstimateItem], settings: &CalculationSettings) -> Vec<CalculationStep> {
+    let totals = calculate_totals(items, settings);
+    let mut steps = Vec::new();
+
+    let base = |unit_cost: fn(&EstimateItem) -> Decimal| -> Decimal {
+        items.iter().map(|item| item.effective_quantity() * unit_cost(item)).sum()
+    };
+    let components: [(&str, &str, Decimal); 5] = [
+        ("direct_costs", "ПЗ", base(|item| item.unit_costs.direct)),
+        ("labor_costs", "ОЗП", base(|item| item.unit_costs.labor)),
+        ("machine_operator_costs", "ЗПМ", base(|item| item.unit_costs.machine_operator)),
+        ("material_costs", "М", base(|item| item.unit_costs.materials)),
+        ("machine_costs", "ЭМ", base(|item| item.unit_costs.machines)),
+    ];
+    for (name, label, value) in components {
+        steps.push(CalculationStep::new(
+            &format!("{}_base", name),
+            format!("{} = Σ количество × {} ед. ({} позиций)", label, label, items.len()),
+            value,
+        ));
+    }
+
+    steps.push(CalculationStep::new(
+        "index",
+        format!("ПЗ × index = {} × {}", components[0].2, settings.index),
+        components[0].2 * settings.index,
+    ));
+
+    if settings.taxation == TaxationRegime::Usn {
+        if let Some(compensation) = settings.usn_vat_compensation {
+            steps.push(CalculationStep::new(
+                "usn_vat_compensation",
+                format!("ПЗ + (М + ЭМ) × (compensation − 1), compensation = {}", compensation),
+                totals.direct_costs,
+            ));
+        }
+    }
+
+    let labor_total = totals.labor_costs + totals.machine_operator_costs;
+    steps.push(CalculationStep::new(
+        "labor_fund",
+        format!("ФОТ = ОЗП + ЗПМ = {} + {}", totals.labor_costs, totals.machine_operator_costs),
+        labor_total,
+    ));
+    let overhead_expression = match settings.overhead_method {
+        OverheadMethod::FromLaborCost => {
+            format!("overhead = ФОТ × rate = {} × {}", labor_total, settings.overhead_rate)
+        }
+        OverheadMethod::FromDirectCost => {
+            format!("overhead = ПЗ × rate = {} × {}", totals.direct_costs, settings.overhead_rate)
+        }
+        OverheadMethod::Fixed => format!("overhead = fixed = {}", settings.overhead_rate),
+    };
+    steps.push(CalculationStep::new("overhead", overhead_expression, totals.overhead));
+    let profit_expression = match settings.profit_method {
+        ProfitMethod::FromLaborCost => {
+            format!("profit = ФОТ × rate = {} × {}", labor_total, settings.profit_rate)
+        }
+        ProfitMethod::FromDirectCost => {
+            format!("profit = ПЗ × rate = {} × {}", totals.direct_costs, settings.profit_rate)
+        }
+        ProfitMethod::Fixed => format!("profit = fixed = {}", settings.profit_rate),
+    };
+    steps.push(CalculationStep::new("profit", profit_expression, totals.profit));
+    if settings.contingency_rate.is_zero() {
+        steps.push(CalculationStep::new(
+            "subtotal",
+            format!(
+                "subtotal = ПЗ + overhead + profit = {} + {} + {}",
+                totals.direct_costs, totals.overhead, totals.profit
+            ),
+            totals.subtotal,
+        ));
+    } else {
+        let base = totals.direct_costs + totals.overhead + totals.profit;
+        steps.push(CalculationStep::new(
+            "contingency",
+            format!(
+                "contingency = (ПЗ + overhead + profit) × rate = {} × {}",
+                base, settings.contingency_rate
+            ),
+            totals.contingency,
+        ));
+        steps.push(CalculationStep::new(
+            "subtotal",
+            format!(
+                "subtotal = ПЗ + overhead + profit + contingency = {} + {} + {} + {}",
+                totals.direct_costs, totals.overhead, totals.profit, totals.contingency
+            ),
+            totals.subtotal,
+        ));
+    }
+    let vat_expression = match settings.taxation {
+        TaxationRegime::Osn => format!("vat = subtotal × rate = {} × {}", totals.subtotal, settings.vat_rate),
+        TaxationRegime::Usn => "vat = 0 (УСН)".to_string(),
+    };
+    steps.push(CalculationStep::new("vat", vat_expression, totals.vat));
+    steps.push(CalculationStep::new(
+        "total",
+        format!("total = subtotal + vat = {} + {}", totals.subtotal, totals.vat),
+        totals.total,
+    ));
+
+    steps
 }
 
 /// Fast calculation using f64 for SIMD compatibility
@@ -77,7 +287,7 @@ impl FastCalculator {
         };
         
         for item in items {
-            calc.quantities.push(decimal_to_f64(item.quantity));
+            calc.quantities.push(decimal_to_f64(item.effective_quantity()));
             calc.direct_costs.push(decimal_to_f64(item.unit_costs.direct));
             calc.labor_costs.push(decimal_to_f64(item.unit_costs.labor));
             calc.machine_op_costs.push(decimal_to_f64(item.unit_costs.machine_operator));
@@ -182,12 +392,19 @@ impl Default for FastCalculationSettings {
     }
 }
 
+/// УСН maps to a zero VAT rate; the УСН VAT compensation, the contingency
+/// reserve and overhead/profit methods other than from ФОТ are only applied
+/// by the Decimal path ([`calculate_totals`])
 impl From<&CalculationSettings> for FastCalculationSettings {
     fn from(settings: &CalculationSettings) -> Self {
+        let vat_rate = match settings.taxation {
+            TaxationRegime::Osn => decimal_to_f64(settings.vat_rate),
+            TaxationRegime::Usn => 0.0,
+        };
         Self {
             overhead_rate: decimal_to_f64(settings.overhead_rate),
             profit_rate: decimal_to_f64(settings.profit_rate),
-            vat_rate: decimal_to_f64(settings.vat_rate),
+            vat_rate,
             index: decimal_to_f64(settings.index),
         }
     }
@@ -219,6 +436,8 @@ impl FastTotals {
             machine_costs: f64_to_decimal(self.machine_costs),
             overhead: f64_to_decimal(self.overhead),
             profit: f64_to_decimal(self.profit),
+            contingency: Decimal::ZERO,
+            limited_costs: Decimal::ZERO,
             subtotal: f64_to_decimal(self.subtotal),
             vat: f64_to_decimal(self.vat),
             total: f64_to_decimal(self.total),
@@ -226,6 +445,48 @@ impl FastTotals {
     }
 }
 
+/// Maximum accepted difference between the fast and Decimal grand totals (one kopeck)
+pub const RECONCILE_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+
+/// Reconcile fast f64 totals with a Decimal recomputation
+///
+/// Uses the default tolerance of one kopeck, see [`reconcile_with_tolerance`].
+pub fn reconcile(
+    fast: &FastTotals,
+    items: &[&EstimateItem],
+    settings: &CalculationSettings,
+) -> EstimateTotals {
+    reconcile_with_tolerance(fast, items, settings, RECONCILE_TOLERANCE)
+}
+
+/// Reconcile fast f64 totals with a Decimal recomputation
+///
+/// The grand total is recomputed in Decimal. If it differs from the fast
+/// result by more than `tolerance`, the discrepancy is logged and the Decimal
+/// totals are returned; otherwise the fast totals are accepted.
+pub fn reconcile_with_tolerance(
+    fast: &FastTotals,
+    items: &[&EstimateItem],
+    settings: &CalculationSettings,
+    tolerance: Decimal,
+) -> EstimateTotals {
+    let precise = calculate_totals(items, settings);
+    let fast_totals = fast.to_estimate_totals();
+    let discrepancy = (precise.total - fast_totals.total).abs();
+
+    if discrepancy > tolerance {
+        tracing::warn!(
+            fast_total = %fast_totals.total,
+            decimal_total = %precise.total,
+            %discrepancy,
+            "fast totals diverge from Decimal, using Decimal result"
+        );
+        precise
+    } else {
+        fast_totals
+    }
+}
+
 /// Convert Decimal to f64 for fast calculations
 #[inline]
 fn decimal_to_f64(d: Decimal) -> f64 {
@@ -263,6 +524,101 @@ mod tests {
         item
     }
 
+    #[test]
+    fn test_explain_totals_matches_calculation() {
+        let items = [create_test_item(10.0, 1000.0, 300.0), create_test_item(2.0, 500.0, 100.0)];
+        let refs: Vec<&EstimateItem> = items.iter().collect();
+        let settings = CalculationSettings::default();
+
+        let steps = explain_totals(&refs, &settings);
+        let totals = calculate_totals(&refs, &settings);
+
+        assert_eq!(steps.first().unwrap().step, "direct_costs_base");
+        assert_eq!(steps.last().unwrap().value, totals.total);
+        let overhead = steps.iter().find(|s| s.step == "overhead").unwrap();
+        assert_eq!(overhead.value, totals.overhead);
+    }
+
+    #[test]
+    fn test_winter_components_applied_per_component() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = crate::estimate::EstimateSection::new(1, "Раздел".to_string());
+        section.add_item(create_test_item(10.0, 1000.0, 300.0));
+        estimate.sections.push(section);
+        let base = calculate_estimate_totals(&estimate);
+
+        for (zone, labor) in [(4, Decimal::new(1038, 3)), (8, Decimal::new(1088, 3))] {
+            estimate.coefficients.apply_winter(zone, "Земляные работы").unwrap();
+            let winter = calculate_estimate_totals(&estimate);
+            let wc = estimate.coefficients.winter_components.clone().unwrap();
+
+            assert_eq!(winter.labor_costs, base.labor_costs * labor);
+            assert_eq!(winter.machine_costs, base.machine_costs * wc.machine_coefficient);
+            assert_eq!(winter.material_costs, base.material_costs);
+            assert!(winter.total > base.total);
+            assert_eq!(winter.total, winter.subtotal + winter.vat);
+        }
+    }
+
+    #[test]
+    fn test_overhead_and_profit_methods() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = crate::estimate::EstimateSection::new(1, "Раздел".to_string());
+        section.add_item(create_test_item(10.0, 1000.0, 300.0));
+        estimate.sections.push(section);
+        estimate.overhead.rate = Decimal::new(12, 2);
+        estimate.profit.rate = Decimal::new(8, 2);
+
+        // ПЗ 10000, ФОТ 3000 + 900
+        let direct = Decimal::new(10000, 0);
+        let labor_fund = Decimal::new(3900, 0);
+        let overheads = [
+            (OverheadMethod::FromLaborCost, labor_fund * Decimal::new(12, 2)),
+            (OverheadMethod::FromDirectCost, direct * Decimal::new(12, 2)),
+            (OverheadMethod::Fixed, Decimal::new(12, 2)),
+        ];
+        let profits = [
+            (ProfitMethod::FromLaborCost, labor_fund * Decimal::new(8, 2)),
+            (ProfitMethod::FromDirectCost, direct * Decimal::new(8, 2)),
+            (ProfitMethod::Fixed, Decimal::new(8, 2)),
+        ];
+
+        for (overhead_method, overhead) in overheads {
+            for (profit_method, profit) in profits {
+                estimate.overhead.method = overhead_method;
+                estimate.profit.method = profit_method;
+
+                let totals = calculate_estimate_totals(&estimate);
+                assert_eq!(totals.overhead, overhead, "{:?}", overhead_method);
+                assert_eq!(totals.profit, profit, "{:?}", profit_method);
+                assert_eq!(totals.subtotal, direct + overhead + profit);
+            }
+        }
+    }
+
+    #[test]
+    fn test_contingency_reserve() {
+        let items = [create_test_item(10.0, 1000.0, 300.0)];
+        let refs: Vec<&EstimateItem> = items.iter().collect();
+
+        let plain = calculate_totals(&refs, &CalculationSettings::default());
+        let settings = CalculationSettings {
+            contingency_rate: Decimal::new(2, 2),
+            ..Default::default()
+        };
+        let reserved = calculate_totals(&refs, &settings);
+
+        assert_eq!(plain.contingency, Decimal::ZERO);
+        assert_eq!(reserved.contingency, plain.subtotal * Decimal::new(2, 2));
+        assert_eq!(reserved.subtotal, plain.subtotal + reserved.contingency);
+        assert_eq!(reserved.vat, reserved.subtotal * settings.vat_rate);
+        assert!(reserved.is_consistent(RECONCILE_TOLERANCE));
+
+        let steps = explain_totals(&refs, &settings);
+        let step = steps.iter().find(|s| s.step == "contingency").unwrap();
+        assert_eq!(step.value, reserved.contingency);
+    }
+
     #[test]
     fn test_fast_calculator() {
         let items: Vec<EstimateItem> = (0..100)
@@ -279,4 +635,68 @@ mod tests {
         assert!(totals.direct_costs > 0.0);
         assert!(totals.overhead > 0.0);
     }
+
+    #[test]
+    fn test_usn_excludes_vat() {
+        let items: Vec<EstimateItem> = (0..10)
+            .map(|i| create_test_item(10.0, 1000.0 + i as f64, 300.0))
+            .collect();
+        let refs: Vec<&EstimateItem> = items.iter().collect();
+
+        let osn = calculate_totals(&refs, &CalculationSettings::default());
+        let usn_settings = CalculationSettings {
+            taxation: TaxationRegime::Usn,
+            ..Default::default()
+        };
+        let usn = calculate_totals(&refs, &usn_settings);
+
+        assert_eq!(usn.vat, Decimal::ZERO);
+        assert_eq!(usn.total, usn.subtotal);
+        assert_eq!(usn.subtotal, osn.subtotal);
+
+        let compensated = calculate_totals(
+            &refs,
+            &CalculationSettings {
+                usn_vat_compensation: Some(Decimal::new(120, 2)),
+                ..usn_settings
+            },
+        );
+
+        assert_eq!(compensated.vat, Decimal::ZERO);
+        assert_eq!(compensated.material_costs, osn.material_costs * Decimal::new(120, 2));
+        assert_eq!(compensated.labor_costs, osn.labor_costs);
+        assert!(compensated.direct_costs > osn.direct_costs);
+    }
+
+    #[test]
+    fn test_reconcile_agreeing_paths() {
+        let items: Vec<EstimateItem> = (0..10)
+            .map(|i| create_test_item(10.0, 1000.0 + i as f64, 300.0))
+            .collect();
+        let refs: Vec<&EstimateItem> = items.iter().collect();
+        let settings = CalculationSettings::default();
+
+        let fast = FastCalculator::from_items(&refs).calculate_totals(&(&settings).into());
+        let reconciled = reconcile(&fast, &refs, &settings);
+
+        assert_eq!(reconciled.total, fast.to_estimate_totals().total);
+    }
+
+    #[test]
+    fn test_reconcile_falls_back_to_decimal() {
+        let items: Vec<EstimateItem> = (0..10)
+            .map(|i| create_test_item(10.0, 1000.0 + i as f64, 300.0))
+            .collect();
+        let refs: Vec<&EstimateItem> = items.iter().collect();
+        let settings = CalculationSettings::default();
+
+        let mut fast = FastCalculator::from_items(&refs).calculate_totals(&(&settings).into());
+        fast.total += 0.05;
+
+        let reconciled = reconcile(&fast, &refs, &settings);
+        let precise = calculate_totals(&refs, &settings);
+
+        assert_eq!(reconciled.total, precise.total);
+        assert_eq!(reconciled.overhead, precise.overhead);
+    }
 }
@@ -5,21 +5,45 @@
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::calculator::apply_markups;
+use crate::error::{DeniDomError, Result as CoreResult};
+use crate::estimate::{CalculationSettings, EstimateTotals};
+
+/// Допустимый диапазон индекса пересчёта: [0.1, 100]
+const INDEX_RANGE: (Decimal, Decimal) = (
+    Decimal::from_parts(1, 0, 0, false, 1),
+    Decimal::ONE_HUNDRED,
+);
+
+/// Допустимый диапазон коэффициентов условий производства работ: [0.5, 3]
+const CONDITION_RANGE: (Decimal, Decimal) = (
+    Decimal::from_parts(5, 0, 0, false, 1),
+    Decimal::from_parts(3, 0, 0, false, 0),
+);
+
 /// Коэффициенты к смете
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Coefficients {
     /// Индекс пересчёта в текущие цены
+    #[serde(with = "crate::serde_decimal")]
     pub index: Decimal,
     /// Коэффициент зимнего удорожания
+    #[serde(with = "crate::serde_decimal::option")]
     pub winter: Option<Decimal>,
     /// Коэффициент стеснённости
+    #[serde(with = "crate::serde_decimal::option")]
     pub cramped: Option<Decimal>,
     /// Региональный коэффициент
+    #[serde(with = "crate::serde_decimal::option")]
     pub regional: Option<Decimal>,
     /// Высотный коэффициент
+    #[serde(with = "crate::serde_decimal::option")]
     pub height: Option<Decimal>,
     /// Пользовательские коэффициенты
     pub custom: Vec<CustomCoefficient>,
+    /// Зимний коэффициент, дифференцированный по составляющим (ОЗП, ЭМ, материалы)
+    #[serde(default)]
+    pub winter_components: Option<WinterCoefficient>,
 }
 
 impl Default for Coefficients {
@@ -31,27 +55,25 @@ impl Default for Coefficients {
             regional: None,
             height: None,
             custom: Vec::new(),
+            winter_components: None,
         }
     }
 }
 
 impl Coefficients {
-    /// Calculate combined coefficient (multiply all applicable coefficients)
+    /// Calculate the combined coefficient of everything that applies to all
+    /// cost components alike: index, scalar winter, cramped and custom ones.
+    /// Regional, height and differentiated winter coefficients spare some
+    /// components, so they are not part of it; see [`Coefficients::component_set`].
     pub fn combined(&self) -> Decimal {
         let mut result = self.index;
         
-        if let Some(winter) = self.winter {
+        if let (Some(winter), None) = (self.winter, &self.winter_components) {
             result *= winter;
         }
         if let Some(cramped) = self.cramped {
             result *= cramped;
         }
-        if let Some(regional) = self.regional {
-            result *= regional;
-        }
-        if let Some(height) = self.height {
-            result *= height;
-        }
         
         for custom in &self.custom {
             if custom.is_active {
@@ -62,6 +84,88 @@ impl Coefficients {
         result
     }
 
+    /// Check coefficients against legal ranges: index in [0.1, 100],
+    /// winter/cramped/regional/height in [0.5, 3], custom values positive.
+    /// Returns every violation found.
+    pub fn validate(&self) -> Result<(), Vec<DeniDomError>> {
+        let mut errors = Vec::new();
+        let mut check = |name: &str, value: Decimal, (min, max): (Decimal, Decimal)| {
+            if value < min || value > max {
+                errors.push(DeniDomError::Validation(format!(
+                    "coefficient {} = {} is outside [{}, {}]",
+                    name, value, min, max
+                )));
+            }
+        };
+
+        check("index", self.index, INDEX_RANGE);
+        let conditions = [
+            ("winter", self.winter),
+            ("cramped", self.cramped),
+            ("regional", self.regional),
+            ("height", self.height),
+        ];
+        for (name, value) in conditions {
+            if let Some(value) = value {
+                check(name, value, CONDITION_RANGE);
+            }
+        }
+
+        for custom in &self.custom {
+            if custom.value <= Decimal::ZERO {
+                errors.push(DeniDomError::Validation(format!(
+                    "custom coefficient '{}' = {} must be positive",
+                    custom.name, custom.value
+                )));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Look up the winter coefficient for a temperature zone and work
+    /// section in [`winter_coefficients`] and apply it per component. The
+    /// scalar `winter` is cleared so it is not applied on top.
+    pub fn apply_winter(&mut self, zone: u8, work_section: &str) -> CoreResult<()> {
+        let section = work_section.trim().to_lowercase();
+        let wc = winter_coefficients()
+            .into_iter()
+            .find(|wc| wc.zone == zone && wc.work_section.to_lowercase() == section)
+            .ok_or_else(|| {
+                DeniDomError::Validation(format!(
+                    "no winter coefficient for zone {} and section '{}'",
+                    zone, work_section
+                ))
+            })?;
+        self.winter = None;
+        self.winter_components = Some(wc);
+        Ok(())
+    }
+
+    /// Per-component coefficients applied on top of [`Coefficients::combined`]:
+    /// regional and height ones (labor and machines only) and the
+    /// differentiated winter coefficient
+    pub fn component_set(&self) -> ComponentCoefficientSet {
+        let mut set = ComponentCoefficientSet::from_coefficients(self);
+        if let Some(wc) = &self.winter_components {
+            set.coefficients.extend(wc.component_coefficients().coefficients);
+        }
+        set
+    }
+
+    /// Scale ОЗП, ЭМ and materials of `totals` by the differentiated winter
+    /// coefficient, if one is set. Markups are not touched, so this runs
+    /// before overhead, profit and VAT are computed.
+    pub fn apply_winter_components(&self, totals: &mut EstimateTotals) {
+        if let Some(wc) = &self.winter_components {
+            wc.component_coefficients().scale_components(totals);
+        }
+    }
+
     /// Add a custom coefficient
     pub fn add_custom(&mut self, name: String, value: Decimal, justification: Option<String>) {
         self.custom.push(CustomCoefficient {
@@ -79,6 +183,7 @@ pub struct CustomCoefficient {
     /// Наименование коэффициента
     pub name: String,
     /// Значение коэффициента
+    #[serde(with = "crate::serde_decimal")]
     pub value: Decimal,
     /// Обоснование применения
     pub justification: Option<String>,
@@ -86,6 +191,115 @@ pub struct CustomCoefficient {
     pub is_active: bool,
 }
 
+/// Составляющая прямых затрат
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CostComponent {
+    /// ОЗП - оплата труда рабочих
+    Labor,
+    /// ЗПМ - зарплата машинистов
+    MachineOperator,
+    /// Материалы
+    Materials,
+    /// Эксплуатация машин и механизмов
+    Machines,
+}
+
+impl CostComponent {
+    /// Все составляющие прямых затрат
+    pub const ALL: [CostComponent; 4] = [
+        Self::Labor,
+        Self::MachineOperator,
+        Self::Materials,
+        Self::Machines,
+    ];
+
+    /// Составляющие, к которым применяются коэффициенты условий производства работ (ОЗП и ЭМ)
+    pub const LABOR_AND_MACHINES: [CostComponent; 3] =
+        [Self::Labor, Self::MachineOperator, Self::Machines];
+}
+
+/// Коэффициент, применяемый к отдельным составляющим затрат
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentCoefficient {
+    /// Наименование коэффициента
+    pub name: String,
+    /// Значение коэффициента
+    pub value: Decimal,
+    /// Составляющие, к которым применяется коэффициент
+    pub targets: Vec<CostComponent>,
+}
+
+/// Набор покомпонентных коэффициентов
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComponentCoefficientSet {
+    /// Коэффициенты
+    pub coefficients: Vec<ComponentCoefficient>,
+}
+
+impl ComponentCoefficientSet {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a set from estimate coefficients: regional and height
+    /// coefficients target labor and machine costs, not materials
+    pub fn from_coefficients(coefficients: &Coefficients) -> Self {
+        let mut set = Self::new();
+        if let Some(regional) = coefficients.regional {
+            set.add("Региональный".to_string(), regional, &CostComponent::LABOR_AND_MACHINES);
+        }
+        if let Some(height) = coefficients.height {
+            set.add("Высотный".to_string(), height, &CostComponent::LABOR_AND_MACHINES);
+        }
+        set
+    }
+
+    /// Add a coefficient targeting the given components
+    pub fn add(&mut self, name: String, value: Decimal, targets: &[CostComponent]) {
+        self.coefficients.push(ComponentCoefficient {
+            name,
+            value,
+            targets: targets.to_vec(),
+        });
+    }
+
+    /// Combined factor for a single component
+    pub fn factor_for(&self, component: CostComponent) -> Decimal {
+        self.coefficients
+            .iter()
+            .filter(|c| c.targets.contains(&component))
+            .fold(Decimal::ONE, |acc, c| acc * c.value)
+    }
+
+    /// Scale only the targeted cost components; direct costs absorb the
+    /// change. Overhead, profit, contingency and VAT are left as they are.
+    pub fn scale_components(&self, totals: &mut EstimateTotals) {
+        let components_before = totals.labor_costs
+            + totals.machine_operator_costs
+            + totals.material_costs
+            + totals.machine_costs;
+
+        totals.labor_costs *= self.factor_for(CostComponent::Labor);
+        totals.machine_operator_costs *= self.factor_for(CostComponent::MachineOperator);
+        totals.material_costs *= self.factor_for(CostComponent::Materials);
+        totals.machine_costs *= self.factor_for(CostComponent::Machines);
+
+        let components_after = totals.labor_costs
+            + totals.machine_operator_costs
+            + totals.material_costs
+            + totals.machine_costs;
+        totals.direct_costs += components_after - components_before;
+    }
+
+    /// Apply coefficients to totals, scaling only the targeted components,
+    /// then recompute overhead, profit, contingency and VAT under `settings`
+    pub fn apply_to_totals(&self, totals: &mut EstimateTotals, settings: &CalculationSettings) {
+        self.scale_components(totals);
+        apply_markups(totals, settings);
+    }
+}
+
 /// Индекс пересчёта цен
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceIndex {
@@ -128,13 +342,78 @@ pub struct WinterCoefficient {
     /// Раздел работ
     pub work_section: String,
     /// Коэффициент к ОЗП
+    #[serde(with = "crate::serde_decimal")]
     pub labor_coefficient: Decimal,
     /// Коэффициент к ЭМ
+    #[serde(with = "crate::serde_decimal")]
     pub machine_coefficient: Decimal,
     /// Коэффициент к материалам
+    #[serde(with = "crate::serde_decimal")]
     pub material_coefficient: Decimal,
 }
 
+impl WinterCoefficient {
+    /// Component coefficients: ОЗП by the labor coefficient, ЭМ (including
+    /// ЗПМ) by the machine coefficient, materials by the material coefficient
+    pub fn component_coefficients(&self) -> ComponentCoefficientSet {
+        let mut set = ComponentCoefficientSet::new();
+        set.add("Зимний к ОЗП".to_string(), self.labor_coefficient, &[CostComponent::Labor]);
+        set.add(
+            "Зимний к ЭМ".to_string(),
+            self.machine_coefficient,
+            &[CostComponent::Machines, CostComponent::MachineOperator],
+        );
+        set.add("Зимний к материалам".to_string(), self.material_coefficient, &[CostComponent::Materials]);
+        set
+    }
+}
+
+/// Зимнее удорожание в десятых долях процента по зонам 1-8: к ОЗП, к ЭМ,
+/// к материалам
+const WINTER_TABLE: [(&str, [[u16; 8]; 3]); 3] = [
+    (
+        "Земляные работы",
+        [
+            [12, 20, 29, 38, 46, 55, 69, 88],
+            [9, 15, 22, 29, 35, 42, 53, 68],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+        ],
+    ),
+    (
+        "Бетонные и железобетонные работы",
+        [
+            [15, 26, 37, 49, 60, 72, 90, 114],
+            [10, 17, 25, 33, 40, 48, 60, 76],
+            [4, 6, 9, 12, 15, 18, 23, 29],
+        ],
+    ),
+    (
+        "Отделочные работы",
+        [
+            [9, 15, 22, 29, 35, 42, 52, 67],
+            [5, 8, 12, 16, 19, 23, 29, 37],
+            [2, 3, 5, 6, 8, 9, 12, 15],
+        ],
+    ),
+];
+
+/// Зимние коэффициенты по разделам работ для всех температурных зон
+pub fn winter_coefficients() -> Vec<WinterCoefficient> {
+    let coefficient = |tenths: u16| Decimal::new(1000 + i64::from(tenths), 3);
+    WINTER_TABLE
+        .iter()
+        .flat_map(|(section, [labor, machine, material])| {
+            (0..8).map(move |zone| WinterCoefficient {
+                zone: zone as u8 + 1,
+                work_section: section.to_string(),
+                labor_coefficient: coefficient(labor[zone]),
+                machine_coefficient: coefficient(machine[zone]),
+                material_coefficient: coefficient(material[zone]),
+            })
+        })
+        .collect()
+}
+
 /// Температурные зоны России
 pub fn temperature_zones() -> Vec<(&'static str, u8)> {
     vec![
@@ -202,15 +481,35 @@ mod tests {
 
     #[test]
     fn test_coefficients_combined() {
-        let mut coef = Coefficients::default();
-        coef.index = Decimal::new(850, 2); // 8.50 (индекс пересчёта)
-        coef.winter = Some(Decimal::new(103, 2)); // 1.03
+        let coef = Coefficients {
+            index: Decimal::new(850, 2),        // 8.50 (индекс пересчёта)
+            winter: Some(Decimal::new(103, 2)), // 1.03
+            ..Default::default()
+        };
         
         let combined = coef.combined();
         // 8.50 * 1.03 = 8.755
         assert!(combined > Decimal::new(875, 2));
     }
 
+    #[test]
+    fn test_validate_out_of_range_index() {
+        let mut coef = Coefficients {
+            winter: Some(Decimal::new(12, 1)),
+            ..Default::default()
+        };
+        assert!(coef.validate().is_ok());
+
+        coef.index = Decimal::new(850, 0);
+        let errors = coef.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], DeniDomError::Validation(msg) if msg.contains("index = 850")));
+
+        coef.height = Some(Decimal::new(4, 0));
+        coef.add_custom("К5".to_string(), Decimal::ZERO, None);
+        assert_eq!(coef.validate().unwrap_err().len(), 3);
+    }
+
     #[test]
     fn test_custom_coefficient() {
         let mut coef = Coefficients::default();
@@ -223,4 +522,73 @@ mod tests {
         assert_eq!(coef.custom.len(), 1);
         assert_eq!(coef.combined(), Decimal::new(120, 2));
     }
+
+    #[test]
+    fn test_apply_winter_by_zone() {
+        let mut coef = Coefficients {
+            winter: Some(Decimal::new(103, 2)),
+            ..Default::default()
+        };
+        coef.apply_winter(4, "Бетонные и железобетонные работы").unwrap();
+        let wc = coef.winter_components.clone().unwrap();
+        assert_eq!(wc.labor_coefficient, Decimal::new(1049, 3));
+        assert_eq!(wc.machine_coefficient, Decimal::new(1033, 3));
+        assert_eq!(wc.material_coefficient, Decimal::new(1012, 3));
+        assert_eq!(coef.winter, None);
+        assert_eq!(coef.combined(), Decimal::ONE);
+
+        coef.apply_winter(8, "Земляные работы").unwrap();
+        let wc = coef.winter_components.clone().unwrap();
+        assert_eq!((wc.zone, wc.labor_coefficient), (8, Decimal::new(1088, 3)));
+        assert_eq!(wc.material_coefficient, Decimal::ONE);
+
+        assert!(matches!(coef.apply_winter(9, "Земляные работы"), Err(DeniDomError::Validation(_))));
+        assert!(coef.apply_winter(4, "Кровельные работы").is_err());
+
+        coef.apply_winter(2, " ЗЕМЛЯНЫЕ работы").unwrap();
+        assert_eq!(coef.winter_components.unwrap().work_section, "Земляные работы");
+    }
+
+    #[test]
+    fn test_height_coefficient_skips_materials() {
+        let coef = Coefficients {
+            height: Some(Decimal::new(120, 2)), // 1.20
+            ..Default::default()
+        };
+        let set = ComponentCoefficientSet::from_coefficients(&coef);
+
+        let mut totals = EstimateTotals {
+            direct_costs: Decimal::new(1000, 0),
+            labor_costs: Decimal::new(300, 0),
+            machine_operator_costs: Decimal::new(100, 0),
+            material_costs: Decimal::new(500, 0),
+            machine_costs: Decimal::new(100, 0),
+            overhead: Decimal::new(48, 0),
+            profit: Decimal::new(32, 0),
+            contingency: Decimal::ZERO,
+            limited_costs: Decimal::ZERO,
+            subtotal: Decimal::new(1080, 0),
+            vat: Decimal::new(216, 0),
+            total: Decimal::new(1296, 0),
+        };
+        set.apply_to_totals(&mut totals, &CalculationSettings::default());
+
+        assert_eq!(totals.labor_costs, Decimal::new(360, 0));
+        assert_eq!(totals.machine_operator_costs, Decimal::new(120, 0));
+        assert_eq!(totals.machine_costs, Decimal::new(120, 0));
+        assert_eq!(totals.material_costs, Decimal::new(500, 0));
+        assert_eq!(totals.direct_costs, Decimal::new(1100, 0));
+        assert_eq!(totals.overhead, Decimal::new(576, 1)); // 12% от 480
+        assert_eq!(totals.total, totals.subtotal + totals.vat);
+
+        // Overhead from direct costs follows the scaled direct costs
+        let settings = CalculationSettings {
+            overhead_method: crate::estimate::OverheadMethod::FromDirectCost,
+            ..Default::default()
+        };
+        set.apply_to_totals(&mut totals, &settings);
+        assert_eq!(totals.direct_costs, Decimal::new(1220, 0));
+        assert_eq!(totals.overhead, Decimal::new(1220, 0) * settings.overhead_rate);
+        assert_eq!(totals.vat, totals.subtotal * settings.vat_rate);
+    }
 }
@@ -0,0 +1,182 @@
+//! Comparison of two estimates
+//!
+//! Items are matched by id, so the diff is meaningful between versions of
+//! the same estimate (e.g. a copy saved before revision).
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::estimate::{Estimate, EstimateItem};
+
+/// Изменение значения поля
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Поле
+    pub field: String,
+    /// Было
+    pub old: String,
+    /// Стало
+    pub new: String,
+}
+
+/// Изменённая позиция
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemChange {
+    /// Идентификатор позиции
+    pub item_id: Uuid,
+    /// Шифр расценки
+    pub code: String,
+    /// Изменённые поля
+    pub changes: Vec<FieldChange>,
+}
+
+/// Различия между двумя сметами
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EstimateDiff {
+    /// Добавленные позиции
+    pub added: Vec<EstimateItem>,
+    /// Удалённые позиции
+    pub removed: Vec<EstimateItem>,
+    /// Изменённые позиции
+    pub changed: Vec<ItemChange>,
+    /// Изменённые настройки расчёта
+    pub settings: Vec<FieldChange>,
+}
+
+impl EstimateDiff {
+    /// Compare `old` against `new`
+    pub fn between(old: &Estimate, new: &Estimate) -> Self {
+        let old_items = old.all_items();
+        let new_items = new.all_items();
+        let mut diff = Self::default();
+
+        for item in &new_items {
+            match old_items.iter().find(|o| o.id == item.id) {
+                Some(previous) => {
+                    let changes = item_changes(previous, item);
+                    if !changes.is_empty() {
+                        diff.changed.push(ItemChange {
+                            item_id: item.id,
+                            code: item.code.clone(),
+                            changes,
+                        });
+                    }
+                }
+                None => diff.added.push((*item).clone()),
+            }
+        }
+        diff.removed = old_items
+            .iter()
+            .filter(|o| !new_items.iter().any(|n| n.id == o.id))
+            .map(|o| (*o).clone())
+            .collect();
+        diff.settings = settings_changes(old, new);
+
+        diff
+    }
+
+    /// Check if the estimates are equivalent
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.settings.is_empty()
+    }
+}
+
+fn push_change<T: PartialEq + ToString>(changes: &mut Vec<FieldChange>, field: &str, old: T, new: T) {
+    if old != new {
+        changes.push(FieldChange {
+            field: field.to_string(),
+            old: old.to_string(),
+            new: new.to_string(),
+        });
+    }
+}
+
+fn optional(value: Option<rust_decimal::Decimal>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn item_changes(old: &EstimateItem, new: &EstimateItem) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    push_change(&mut changes, "code", &old.code, &new.code);
+    push_change(&mut changes, "name", &old.name, &new.name);
+    push_change(&mut changes, "unit", old.unit.abbreviation(), new.unit.abbreviation());
+    push_change(&mut changes, "quantity", old.quantity, new.quantity);
+    push_change(&mut changes, "unit_costs.direct", old.unit_costs.direct, new.unit_costs.direct);
+    push_change(&mut changes, "unit_costs.labor", old.unit_costs.labor, new.unit_costs.labor);
+    push_change(
+        &mut changes,
+        "unit_costs.machine_operator",
+        old.unit_costs.machine_operator,
+        new.unit_costs.machine_operator,
+    );
+    push_change(&mut changes, "unit_costs.materials", old.unit_costs.materials, new.unit_costs.materials);
+    push_change(&mut changes, "unit_costs.machines", old.unit_costs.machines, new.unit_costs.machines);
+    push_change(&mut changes, "coefficient", optional(old.coefficient), optional(new.coefficient));
+    changes
+}
+
+fn settings_changes(old: &Estimate, new: &Estimate) -> Vec<FieldChange> {
+    let (a, b) = (&old.coefficients, &new.coefficients);
+    let mut changes = Vec::new();
+    push_change(&mut changes, "coefficients.index", a.index, b.index);
+    push_change(&mut changes, "coefficients.winter", optional(a.winter), optional(b.winter));
+    push_change(&mut changes, "coefficients.cramped", optional(a.cramped), optional(b.cramped));
+    push_change(&mut changes, "coefficients.regional", optional(a.regional), optional(b.regional));
+    push_change(&mut changes, "coefficients.height", optional(a.height), optional(b.height));
+    push_change(&mut changes, "overhead.rate", old.overhead.rate, new.overhead.rate);
+    push_change(&mut changes, "profit.rate", old.profit.rate, new.profit.rate);
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::estimate::EstimateSection;
+    use crate::units::MeasureUnit;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_diff_between_versions() {
+        let mut old = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        for code in ["ФЕР01-01-001-01", "ФЕР01-01-002-01"] {
+            section.add_item(EstimateItem::new(
+                code.to_string(),
+                "Работа".to_string(),
+                MeasureUnit::CubicMeter,
+                Decimal::TEN,
+            ));
+        }
+        old.sections.push(section);
+
+        let mut new = old.clone();
+        new.sections[0].items[0].quantity = Decimal::new(12, 0);
+        let removed = new.sections[0].items.remove(1);
+        new.sections[0].add_item(EstimateItem::new(
+            "ФЕР01-01-003-01".to_string(),
+            "Работа".to_string(),
+            MeasureUnit::CubicMeter,
+            Decimal::ONE,
+        ));
+        new.coefficients.index = Decimal::new(85, 1);
+
+        let diff = EstimateDiff::between(&old, &new);
+
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(
+            diff.changed[0].changes,
+            vec![FieldChange {
+                field: "quantity".to_string(),
+                old: "10".to_string(),
+                new: "12".to_string(),
+            }]
+        );
+        assert_eq!(diff.removed[0].id, removed.id);
+        assert_eq!(diff.added[0].code, "ФЕР01-01-003-01");
+        assert_eq!(diff.settings[0].field, "coefficients.index");
+        assert!(EstimateDiff::between(&old, &old).is_empty());
+    }
+}
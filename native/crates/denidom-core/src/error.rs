@@ -2,6 +2,8 @@
 //!
 //! Custom error types for estimate calculations and data handling.
 
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use thiserror::Error;
 
 /// Main error type for DeniDom operations
@@ -27,6 +29,13 @@ pub enum DeniDomError {
     #[error("Parse error: {0}")]
     Parse(String),
 
+    /// Parse error with source location
+    #[error("Parse error{context}: {message}")]
+    ParseAt {
+        message: String,
+        context: ParseContext,
+    },
+
     /// IO error
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -44,6 +53,50 @@ pub enum DeniDomError {
     UnitConversion { from: String, to: String },
 }
 
+/// Местоположение ошибки разбора в исходных данных
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseContext {
+    /// Номер строки (с единицы)
+    pub line: Option<usize>,
+    /// Номер столбца (с единицы)
+    pub column: Option<usize>,
+    /// Фрагмент исходной строки
+    pub snippet: Option<String>,
+}
+
+impl ParseContext {
+    /// Create a context pointing at a line
+    pub fn at_line(line: usize) -> Self {
+        Self {
+            line: Some(line),
+            ..Default::default()
+        }
+    }
+
+    /// Set the column
+    pub fn with_column(mut self, column: usize) -> Self {
+        self.column = Some(column);
+        self
+    }
+
+    /// Set the source snippet
+    pub fn with_snippet(mut self, snippet: impl Into<String>) -> Self {
+        self.snippet = Some(snippet.into());
+        self
+    }
+}
+
+impl fmt::Display for ParseContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, " at line {}, column {}", line, column),
+            (Some(line), None) => write!(f, " at line {}", line),
+            (None, Some(column)) => write!(f, " at column {}", column),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
 /// Result type alias for DeniDom operations
 pub type Result<T> = std::result::Result<T, DeniDomError>;
 
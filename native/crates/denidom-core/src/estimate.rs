@@ -3,11 +3,17 @@
 //! Core types for representing construction estimates (сметы).
 
 use chrono::{DateTime, Utc};
-use rust_decimal::Decimal;
+use std::collections::HashSet;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::coefficients::Coefficients;
+use crate::calculator::{apply_index, apply_index_and_markups, apply_markups_shared, sum_item_costs};
+use crate::coefficients::{Coefficients, WinterCoefficient};
+use crate::error::{DeniDomError, Result};
+use crate::normatives::{NormativeBase, NormativeCode};
+use crate::quantity::QuantityExpr;
 use crate::units::MeasureUnit;
 
 /// Смета - основной документ
@@ -33,6 +39,15 @@ pub struct Estimate {
     pub overhead: OverheadSettings,
     /// Сметная прибыль
     pub profit: ProfitSettings,
+    /// Система налогообложения подрядчика
+    #[serde(default)]
+    pub taxation: TaxationRegime,
+    /// Коэффициент компенсации НДС при УСН к материалам и ЭМ
+    #[serde(default, with = "crate::serde_decimal::option")]
+    pub usn_vat_compensation: Option<Decimal>,
+    /// Ставка резерва на непредвиденные работы и затраты
+    #[serde(default, with = "crate::serde_decimal")]
+    pub contingency_rate: Decimal,
     /// Статус
     pub status: EstimateStatus,
     /// Дата создания
@@ -56,12 +71,58 @@ impl Estimate {
             coefficients: Coefficients::default(),
             overhead: OverheadSettings::default(),
             profit: ProfitSettings::default(),
+            taxation: TaxationRegime::Osn,
+            usn_vat_compensation: None,
+            contingency_rate: Decimal::ZERO,
             status: EstimateStatus::Draft,
             created_at: now,
             updated_at: now,
         }
     }
 
+    /// Serialize to the compact bincode format
+    ///
+    /// Field names are not repeated per item, so item-heavy estimates shrink
+    /// by about 45% (100 items: 19 KB vs 35 KB of JSON). `Decimal` fields must
+    /// use `crate::serde_decimal` to be readable back.
+    pub fn to_bincode(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self).map_err(|e| DeniDomError::Serialization(e.to_string()))
+    }
+
+    /// Deserialize from bytes produced by [`Estimate::to_bincode`]
+    pub fn from_bincode(bytes: &[u8]) -> Result<Self> {
+        bincode::deserialize(bytes).map_err(|e| DeniDomError::Serialization(e.to_string()))
+    }
+
+    /// Stable 64-bit FNV-1a hash of the estimate content: header fields,
+    /// sections with their items (including tags), coefficients and rates.
+    /// Timestamps and status are not part of the content.
+    pub fn content_hash(&self) -> Result<u64> {
+        let content = serde_json::to_vec(&(
+            &self.number,
+            &self.name,
+            &self.object,
+            &self.sections,
+            &self.coefficients,
+            &self.overhead,
+            &self.profit,
+        ))?;
+
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+        Ok(content
+            .iter()
+            .fold(FNV_OFFSET, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME)))
+    }
+
+    /// Items carrying the given label, in estimate order
+    pub fn items_with_tag(&self, tag: &str) -> Vec<&EstimateItem> {
+        self.all_items()
+            .into_iter()
+            .filter(|item| item.has_tag(tag))
+            .collect()
+    }
+
     /// Get total items count across all sections
     pub fn items_count(&self) -> usize {
         self.sections.iter().map(|s| s.items.len()).sum()
@@ -72,6 +133,162 @@ impl Estimate {
         self.sections.iter().flat_map(|s| s.items.iter()).collect()
     }
 
+    /// All items paired with the coefficient of their section (1 when none is set)
+    pub fn lines(&self) -> Vec<(&EstimateItem, Decimal)> {
+        self.sections
+            .iter()
+            .flat_map(|s| s.items.iter().map(move |item| (item, s.coefficient_or_one())))
+            .collect()
+    }
+
+    /// Totals of `lines` (items with their section coefficients) under
+    /// `settings` and the estimate coefficients. Every Estimate-level totals
+    /// helper goes through here so their results add up: fixed-sum overhead
+    /// and profit are shared out by the lines' part of the direct costs.
+    pub(crate) fn totals_of<'a>(
+        &self,
+        lines: impl IntoIterator<Item = (&'a EstimateItem, Decimal)>,
+        settings: &CalculationSettings,
+    ) -> EstimateTotals {
+        totals_with(lines, settings, &self.coefficients, self.fixed_markup_base(settings))
+    }
+
+    /// Direct costs of the whole estimate that fixed-sum overhead and profit
+    /// are shared over, `None` when neither is fixed
+    fn fixed_markup_base(&self, settings: &CalculationSettings) -> Option<Decimal> {
+        let fixed = settings.overhead_method == OverheadMethod::Fixed
+            || settings.profit_method == ProfitMethod::Fixed;
+        fixed.then(|| totals_with(self.lines(), settings, &self.coefficients, None).direct_costs)
+    }
+
+    /// Totals of every item on its own, with its section coefficient, the
+    /// estimate coefficients and `settings` applied. The lines add up to the
+    /// estimate totals.
+    pub fn line_totals(&self, settings: &CalculationSettings) -> Vec<(&EstimateItem, EstimateTotals)> {
+        let fixed_base = self.fixed_markup_base(settings);
+        self.lines()
+            .into_iter()
+            .map(|line| (line.0, totals_with([line], settings, &self.coefficients, fixed_base)))
+            .collect()
+    }
+
+    /// Each item's share of the total direct cost (before overhead, profit and VAT)
+    ///
+    /// Shares sum to 1.0 unless the estimate has no direct cost, in which case all are 0.0.
+    pub fn item_contributions(&self) -> Vec<(Uuid, f64)> {
+        let items = self.all_items();
+        let total: Decimal = items.iter().map(|item| item.total_direct_cost()).sum();
+
+        items
+            .iter()
+            .map(|item| {
+                let share = if total.is_zero() {
+                    0.0
+                } else {
+                    (item.total_direct_cost() / total).to_f64().unwrap_or_default()
+                };
+                (item.id, share)
+            })
+            .collect()
+    }
+
+    /// Normative bases referenced by item codes; unrecognized codes are ignored
+    pub fn base_types_used(&self) -> HashSet<NormativeBase> {
+        self.all_items()
+            .iter()
+            .filter_map(|item| NormativeBase::from_code_prefix(&item.code))
+            .collect()
+    }
+
+    /// Items that still have pricing gaps: zero direct cost or unpriced resources
+    pub fn unpriced_items(&self) -> Vec<&EstimateItem> {
+        self.sections
+            .iter()
+            .flat_map(|s| s.items.iter())
+            .filter(|item| {
+                item.unit_costs.direct.is_zero()
+                    || item.resources.iter().any(|r| r.unit_price.is_zero())
+            })
+            .collect()
+    }
+
+    /// Move the estimate to status `to`, rejecting transitions the workflow
+    /// does not allow
+    pub fn transition(&mut self, to: EstimateStatus) -> Result<()> {
+        if !self.status.can_transition_to(to) {
+            return Err(DeniDomError::Validation(format!(
+                "illegal status transition {:?} -> {:?}",
+                self.status, to
+            )));
+        }
+        self.status = to;
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Calendar duration in working days for a crew: total man-hours
+    /// (`labor_hours × quantity`) divided by `crew_size × hours_per_shift`.
+    /// Returns 0.0 for an empty crew or non-positive shift length.
+    pub fn duration_days(&self, crew_size: u32, hours_per_shift: f64) -> f64 {
+        if crew_size == 0 || hours_per_shift <= 0.0 {
+            return 0.0;
+        }
+
+        let man_hours: Decimal = self
+            .all_items()
+            .iter()
+            .map(|item| item.labor_hours * item.quantity)
+            .sum();
+
+        man_hours.to_f64().unwrap_or(0.0) / (crew_size as f64 * hours_per_shift)
+    }
+
+    /// Grand total (with VAT) per unit of a physical metric, e.g. cost per
+    /// м² of building area. Returns zero for a zero metric.
+    pub fn cost_per_metric(&self, metric_quantity: Decimal) -> Decimal {
+        self.cost_per_metric_with_net(metric_quantity).0
+    }
+
+    /// Cost per unit of a physical metric as `(with VAT, without VAT)`.
+    /// Returns zeros for a zero metric.
+    pub fn cost_per_metric_with_net(&self, metric_quantity: Decimal) -> (Decimal, Decimal) {
+        if metric_quantity.is_zero() {
+            return (Decimal::ZERO, Decimal::ZERO);
+        }
+
+        let totals = self.totals_of(self.lines(), &self.calculation_settings());
+        (totals.total / metric_quantity, totals.subtotal / metric_quantity)
+    }
+
+    /// Overhead and profit of each section as `(section id, НР, СП)`, from
+    /// the section's ФОТ and the estimate rates; they add up to the
+    /// estimate's overhead and profit
+    pub fn overhead_profit_by_section(&self) -> Vec<(Uuid, Decimal, Decimal)> {
+        let settings = self.calculation_settings();
+        self.sections
+            .iter()
+            .map(|section| {
+                let totals = self.section_lines_totals(section, &settings);
+                (section.id, totals.overhead, totals.profit)
+            })
+            .collect()
+    }
+
+    /// Multiply the coefficient of every item matching `predicate` by
+    /// `coeff` (e.g. 1.15 for all demolition lines)
+    pub fn apply_item_coefficient(
+        &mut self,
+        predicate: impl Fn(&EstimateItem) -> bool,
+        coeff: Decimal,
+    ) {
+        for item in self.sections.iter_mut().flat_map(|s| s.items.iter_mut()) {
+            if predicate(item) {
+                item.coefficient = Some(item.coefficient.unwrap_or(Decimal::ONE) * coeff);
+            }
+        }
+        self.updated_at = Utc::now();
+    }
+
     /// Get calculation settings from estimate
     pub fn calculation_settings(&self) -> CalculationSettings {
         CalculationSettings {
@@ -79,8 +296,283 @@ impl Estimate {
             profit_rate: self.profit.rate,
             vat_rate: Decimal::new(20, 2), // 20% VAT
             index: self.coefficients.index,
+            taxation: self.taxation,
+            usn_vat_compensation: self.usn_vat_compensation,
+            contingency_rate: self.contingency_rate,
+            overhead_method: self.overhead.method,
+            profit_method: self.profit.method,
         }
     }
+
+    /// Calculate totals with the given coefficients applied: the
+    /// [`Coefficients::combined`] ones to all cost components, regional,
+    /// height and differentiated winter ones only to their own
+    pub fn totals_with_coefficients(&self, coefficients: &Coefficients) -> EstimateTotals {
+        let settings = CalculationSettings {
+            index: coefficients.combined(),
+            ..self.calculation_settings()
+        };
+        let mut totals = sum_item_costs(self.lines());
+        apply_index(&mut totals, &settings);
+        coefficients.component_set().scale_components(&mut totals);
+        apply_markups_shared(&mut totals, &settings, Decimal::ONE);
+        totals
+    }
+
+    /// Element-wise change of totals when switching from the current
+    /// coefficients to `with` (positive values mean the estimate grows)
+    pub fn coefficient_delta(&self, with: &Coefficients) -> EstimateTotals {
+        self.totals_with_coefficients(with) - self.totals_with_coefficients(&self.coefficients)
+    }
+
+    /// Per-component increase of the totals from applying a winter
+    /// coefficient on top of the current estimate
+    pub fn component_delta_for(&self, wc: &WinterCoefficient) -> EstimateTotals {
+        let settings = self.calculation_settings();
+        let base = self.totals_of(self.lines(), &settings);
+        let mut winter = base;
+        wc.component_coefficients().apply_to_totals(&mut winter, &settings);
+        winter - base
+    }
+
+    /// Копеечные расхождения: sum of per-line totals each rounded to kopecks
+    /// minus the grand total rounded to kopecks. Positive when line rounding
+    /// overstates the estimate.
+    pub fn rounding_discrepancy(&self, settings: &CalculationSettings) -> Decimal {
+        let line_totals: Decimal = self
+            .line_totals(settings)
+            .iter()
+            .map(|(_, totals)| round_kopecks(totals.total))
+            .sum();
+        line_totals - round_kopecks(self.totals_of(self.lines(), settings).total)
+    }
+
+    /// Totals at the базовый уровень цен (index 1.0) and at the текущий
+    /// уровень (the estimate's index), in that order
+    pub fn totals_both_levels(&self) -> (EstimateTotals, EstimateTotals) {
+        let lines = self.lines();
+        let current = self.calculation_settings();
+        let base = CalculationSettings {
+            index: Decimal::ONE,
+            ..current
+        };
+        (self.totals_of(lines.clone(), &base), self.totals_of(lines, &current))
+    }
+
+    /// Check the estimate for data that cannot be calculated: an empty name,
+    /// items without a code, negative quantities or unit costs, negative
+    /// overhead or profit rates. Returns every problem found; coefficients
+    /// are checked separately by [`Coefficients::validate`].
+    pub fn validate(&self) -> std::result::Result<(), Vec<DeniDomError>> {
+        let mut errors = Vec::new();
+
+        if self.name.trim().is_empty() {
+            errors.push(DeniDomError::Validation("estimate name is empty".to_string()));
+        }
+        if self.overhead.rate < Decimal::ZERO {
+            errors.push(DeniDomError::Validation(format!(
+                "overhead rate {} is negative",
+                self.overhead.rate
+            )));
+        }
+        if self.profit.rate < Decimal::ZERO {
+            errors.push(DeniDomError::Validation(format!(
+                "profit rate {} is negative",
+                self.profit.rate
+            )));
+        }
+
+        for item in self.all_items() {
+            if item.code.trim().is_empty() {
+                errors.push(DeniDomError::Validation(format!(
+                    "item {} '{}' has no code",
+                    item.position, item.name
+                )));
+            }
+            if item.quantity < Decimal::ZERO {
+                errors.push(DeniDomError::Validation(format!(
+                    "item {}: quantity {} is negative",
+                    item.code, item.quantity
+                )));
+            }
+            let costs = &item.unit_costs;
+            let components = [
+                ("direct", costs.direct),
+                ("labor", costs.labor),
+                ("machine_operator", costs.machine_operator),
+                ("materials", costs.materials),
+                ("machines", costs.machines),
+            ];
+            for (name, value) in components {
+                if value < Decimal::ZERO {
+                    errors.push(DeniDomError::Validation(format!(
+                        "item {}: unit cost {} = {} is negative",
+                        item.code, name, value
+                    )));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Scale every item's quantity by `factor`, e.g. 1.1 to increase volumes
+    /// by 10%. Labor and machine hours are per-unit norms and stay as they
+    /// are. The factor must be positive.
+    pub fn scale_quantities(&mut self, factor: Decimal) -> Result<()> {
+        if factor <= Decimal::ZERO {
+            return Err(DeniDomError::Validation(format!(
+                "quantity scale factor must be positive, got {}",
+                factor
+            )));
+        }
+
+        for item in self.sections.iter_mut().flat_map(|s| s.items.iter_mut()) {
+            item.quantity *= factor;
+        }
+        self.updated_at = Utc::now();
+
+        Ok(())
+    }
+
+    /// Merge sections whose names match (case-insensitive, trimmed) into the
+    /// first of them, then renumber. When the merged sections have different
+    /// coefficients, both are folded into their items' own coefficients and
+    /// the target keeps none, so every effective coefficient and the estimate
+    /// totals are unchanged.
+    pub fn merge_sections_by_name(&mut self) {
+        let key = |name: &str| name.trim().to_lowercase();
+        let mut merged: Vec<EstimateSection> = Vec::with_capacity(self.sections.len());
+
+        for section in std::mem::take(&mut self.sections) {
+            let target = merged.iter_mut().find(|s| key(&s.name) == key(&section.name));
+            let Some(target) = target else {
+                merged.push(section);
+                continue;
+            };
+
+            let mut section = section;
+            if section.coefficient_or_one() != target.coefficient_or_one() {
+                target.fold_coefficient_into_items();
+                section.fold_coefficient_into_items();
+            }
+            target.items.append(&mut section.items);
+        }
+
+        self.sections = merged;
+        self.renumber();
+    }
+
+    /// Number sections from 1 and item positions from 1 through the whole estimate
+    pub fn renumber(&mut self) {
+        let mut position = 0;
+        for (index, section) in self.sections.iter_mut().enumerate() {
+            section.number = index as u32 + 1;
+            for item in &mut section.items {
+                position += 1;
+                item.position = position;
+            }
+        }
+        self.updated_at = Utc::now();
+    }
+
+    /// Effective coefficient of an item: uniform estimate coefficients
+    /// ([`Coefficients::combined`]) × section coefficient × item coefficient.
+    /// `None` if the item id is unknown.
+    pub fn effective_coefficient(&self, item_id: Uuid) -> Option<Decimal> {
+        self.sections.iter().find_map(|section| {
+            section.items.iter().find(|item| item.id == item_id).map(|item| {
+                self.coefficients.combined()
+                    * section.coefficient_or_one()
+                    * item.coefficient.unwrap_or(Decimal::ONE)
+            })
+        })
+    }
+
+    /// Totals grouped by normative collection (e.g. "ФЕР01"), in order of
+    /// first appearance. Items with unparsable codes go to "Прочее", last.
+    pub fn costs_by_collection(&self) -> Vec<(String, EstimateTotals)> {
+        const OTHER: &str = "Прочее";
+
+        let mut groups: Vec<(String, Vec<(&EstimateItem, Decimal)>)> = Vec::new();
+        let mut other: Vec<(&EstimateItem, Decimal)> = Vec::new();
+
+        for line in self.lines() {
+            match NormativeCode::parse(&line.0.code) {
+                Some(code) => {
+                    let key = code.collection_key();
+                    match groups.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, lines)) => lines.push(line),
+                        None => groups.push((key, vec![line])),
+                    }
+                }
+                None => other.push(line),
+            }
+        }
+        if !other.is_empty() {
+            groups.push((OTHER.to_string(), other));
+        }
+
+        let settings = self.calculation_settings();
+        groups
+            .into_iter()
+            .map(|(key, lines)| (key, self.totals_of(lines, &settings)))
+            .collect()
+    }
+
+    /// Calculate totals for each section, in section order
+    pub fn section_totals(&self) -> Vec<(Uuid, EstimateTotals)> {
+        let settings = self.calculation_settings();
+        self.sections
+            .iter()
+            .map(|section| (section.id, self.section_lines_totals(section, &settings)))
+            .collect()
+    }
+
+    /// Calculate totals for a single section, `None` if the id is unknown
+    pub fn section_total(&self, section_id: Uuid) -> Option<EstimateTotals> {
+        let settings = self.calculation_settings();
+        self.sections
+            .iter()
+            .find(|section| section.id == section_id)
+            .map(|section| self.section_lines_totals(section, &settings))
+    }
+
+    fn section_lines_totals(&self, section: &EstimateSection, settings: &CalculationSettings) -> EstimateTotals {
+        let factor = section.coefficient_or_one();
+        self.totals_of(section.items.iter().map(|item| (item, factor)), settings)
+    }
+}
+
+/// Totals of `lines` under `settings`, with the differentiated winter
+/// coefficient of `coefficients` scaling the cost components before markups.
+/// The lines carry fixed-sum overhead and profit in proportion of their
+/// direct costs to `fixed_base`; without a base (or with a zero one) they
+/// carry all of it.
+fn totals_with<'a>(
+    lines: impl IntoIterator<Item = (&'a EstimateItem, Decimal)>,
+    settings: &CalculationSettings,
+    coefficients: &Coefficients,
+    fixed_base: Option<Decimal>,
+) -> EstimateTotals {
+    let mut totals = sum_item_costs(lines);
+    apply_index(&mut totals, settings);
+    coefficients.apply_winter_components(&mut totals);
+    let fixed_share = match fixed_base {
+        Some(base) if !base.is_zero() => totals.direct_costs / base,
+        _ => Decimal::ONE,
+    };
+    apply_markups_shared(&mut totals, settings, fixed_share);
+    totals
+}
+
+/// Round to kopecks, half away from zero
+fn round_kopecks(value: Decimal) -> Decimal {
+    value.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
 }
 
 /// Раздел сметы
@@ -94,6 +586,9 @@ pub struct EstimateSection {
     pub name: String,
     /// Позиции раздела
     pub items: Vec<EstimateItem>,
+    /// Коэффициент к разделу
+    #[serde(default, with = "crate::serde_decimal::option")]
+    pub coefficient: Option<Decimal>,
 }
 
 impl EstimateSection {
@@ -104,6 +599,7 @@ impl EstimateSection {
             number,
             name,
             items: Vec::new(),
+            coefficient: None,
         }
     }
 
@@ -111,6 +607,29 @@ impl EstimateSection {
     pub fn add_item(&mut self, item: EstimateItem) {
         self.items.push(item);
     }
+
+    /// Multiply the section coefficient into every item coefficient and
+    /// clear it; the effective coefficient of each item is unchanged
+    fn fold_coefficient_into_items(&mut self) {
+        if let Some(coefficient) = self.coefficient.take() {
+            for item in &mut self.items {
+                item.coefficient = Some(item.coefficient.unwrap_or(Decimal::ONE) * coefficient);
+            }
+        }
+    }
+
+    /// Section coefficient, 1 when none is set
+    pub fn coefficient_or_one(&self) -> Decimal {
+        self.coefficient.unwrap_or(Decimal::ONE)
+    }
+
+    /// Calculate totals for this section's items, scaled by the section coefficient
+    pub fn calculate_totals(&self, settings: &CalculationSettings) -> EstimateTotals {
+        let factor = self.coefficient_or_one();
+        let mut totals = sum_item_costs(self.items.iter().map(|item| (item, factor)));
+        apply_index_and_markups(&mut totals, settings);
+        totals
+    }
 }
 
 /// Позиция сметы
@@ -127,15 +646,27 @@ pub struct EstimateItem {
     /// Единица измерения
     pub unit: MeasureUnit,
     /// Количество
+    #[serde(with = "crate::serde_decimal")]
     pub quantity: Decimal,
     /// Единичные расценки
     pub unit_costs: UnitCosts,
     /// Ресурсы
     pub resources: Vec<Resource>,
     /// Затраты труда рабочих (чел-часы)
+    #[serde(with = "crate::serde_decimal")]
     pub labor_hours: Decimal,
     /// Затраты машинного времени (маш-часы)
+    #[serde(with = "crate::serde_decimal")]
     pub machine_hours: Decimal,
+    /// Коэффициент к позиции
+    #[serde(default, with = "crate::serde_decimal::option")]
+    pub coefficient: Option<Decimal>,
+    /// Пользовательские метки («под вопросом», «утверждено»)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Формула, по которой получено количество
+    #[serde(default)]
+    pub quantity_expr: Option<QuantityExpr>,
 }
 
 impl EstimateItem {
@@ -152,27 +683,71 @@ impl EstimateItem {
             resources: Vec::new(),
             labor_hours: Decimal::ZERO,
             machine_hours: Decimal::ZERO,
+            coefficient: None,
+            tags: Vec::new(),
+            quantity_expr: None,
         }
     }
 
+    /// Set the quantity from a formula such as `12*3,5+2`, keeping the
+    /// formula for review; the quantity is unchanged if it does not evaluate
+    pub fn set_quantity_formula(&mut self, src: &str) -> Result<()> {
+        let expr = QuantityExpr::eval(src)?;
+        self.quantity = expr.value;
+        self.quantity_expr = Some(expr);
+        Ok(())
+    }
+
+    /// Add a label unless the item already carries it
+    pub fn add_tag(&mut self, tag: &str) {
+        if !self.has_tag(tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    /// Check if the item carries a label
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Quantity scaled by the item coefficient (1.0 when none is set)
+    pub fn effective_quantity(&self) -> Decimal {
+        self.quantity * self.coefficient.unwrap_or(Decimal::ONE)
+    }
+
     /// Calculate total cost for this item
     pub fn total_direct_cost(&self) -> Decimal {
-        self.quantity * self.unit_costs.direct
+        self.effective_quantity() * self.unit_costs.direct
     }
 
     /// Calculate total labor cost
     pub fn total_labor_cost(&self) -> Decimal {
-        self.quantity * self.unit_costs.labor
+        self.effective_quantity() * self.unit_costs.labor
+    }
+
+    /// Calculate total machine operator cost
+    pub fn total_machine_operator_cost(&self) -> Decimal {
+        self.effective_quantity() * self.unit_costs.machine_operator
     }
 
     /// Calculate total material cost
     pub fn total_material_cost(&self) -> Decimal {
-        self.quantity * self.unit_costs.materials
+        self.effective_quantity() * self.unit_costs.materials
     }
 
     /// Calculate total machine cost
     pub fn total_machine_cost(&self) -> Decimal {
-        self.quantity * self.unit_costs.machines
+        self.effective_quantity() * self.unit_costs.machines
+    }
+
+    /// Recompute the cost of all resources in RUB for the effective quantity
+    pub fn total_resources_cost(&self) -> Decimal {
+        self.effective_quantity() * self.resources.iter().map(Resource::cost).sum::<Decimal>()
+    }
+
+    /// Apply the unit's rounding rule to the quantity (whole pieces for counts)
+    pub fn normalize_quantity(&mut self) {
+        self.quantity = self.unit.round_quantity(self.quantity);
     }
 }
 
@@ -180,14 +755,19 @@ impl EstimateItem {
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct UnitCosts {
     /// Прямые затраты (всего)
+    #[serde(with = "crate::serde_decimal")]
     pub direct: Decimal,
     /// ОЗП - оплата труда рабочих
+    #[serde(with = "crate::serde_decimal")]
     pub labor: Decimal,
     /// ЗПМ - зарплата машинистов
+    #[serde(with = "crate::serde_decimal")]
     pub machine_operator: Decimal,
     /// Материалы
+    #[serde(with = "crate::serde_decimal")]
     pub materials: Decimal,
     /// Эксплуатация машин и механизмов
+    #[serde(with = "crate::serde_decimal")]
     pub machines: Decimal,
 }
 
@@ -230,9 +810,59 @@ pub struct Resource {
     /// Единица измерения
     pub unit: MeasureUnit,
     /// Норма расхода на единицу работы
+    #[serde(with = "crate::serde_decimal")]
     pub consumption_rate: Decimal,
-    /// Цена за единицу
+    /// Цена за единицу (в валюте `currency`)
+    #[serde(with = "crate::serde_decimal")]
     pub unit_price: Decimal,
+    /// Валюта цены
+    #[serde(default)]
+    pub currency: Currency,
+    /// Курс валюты к рублю
+    #[serde(default = "default_fx_rate", with = "crate::serde_decimal")]
+    pub fx_rate: Decimal,
+}
+
+fn default_fx_rate() -> Decimal {
+    Decimal::ONE
+}
+
+impl Resource {
+    /// Unit price converted to RUB
+    pub fn unit_price_rub(&self) -> Decimal {
+        match self.currency {
+            Currency::Rub => self.unit_price,
+            _ => self.unit_price * self.fx_rate,
+        }
+    }
+
+    /// Resource cost per unit of work, in RUB
+    pub fn cost(&self) -> Decimal {
+        self.consumption_rate * self.unit_price_rub()
+    }
+}
+
+/// Валюта цены
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Currency {
+    /// Российский рубль
+    #[default]
+    Rub,
+    /// Доллар США
+    Usd,
+    /// Евро
+    Eur,
+}
+
+impl Currency {
+    /// ISO 4217 code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Rub => "RUB",
+            Self::Usd => "USD",
+            Self::Eur => "EUR",
+        }
+    }
 }
 
 /// Тип ресурса
@@ -288,15 +918,48 @@ pub struct Contractor {
     pub license: Option<String>,
 }
 
+/// Категория работ для нормативов накладных расходов и сметной прибыли
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WorkCategory {
+    /// Общестроительные работы (новое строительство)
+    #[default]
+    Construction,
+    /// Ремонтно-строительные работы (ФЕРр)
+    Repair,
+    /// Монтаж оборудования (ФЕРм)
+    Installation,
+}
+
+/// Average normative overhead and profit rates (share of ФОТ) for a work
+/// category per МДС 81-33.2004 and МДС 81-25.2001
+pub fn default_rates(category: WorkCategory) -> (Decimal, Decimal) {
+    match category {
+        WorkCategory::Construction => (Decimal::new(112, 2), Decimal::new(65, 2)),
+        WorkCategory::Repair => (Decimal::new(95, 2), Decimal::new(50, 2)),
+        WorkCategory::Installation => (Decimal::new(80, 2), Decimal::new(60, 2)),
+    }
+}
+
 /// Настройки накладных расходов
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OverheadSettings {
     /// Ставка накладных расходов (от ФОТ)
+    #[serde(with = "crate::serde_decimal")]
     pub rate: Decimal,
     /// Метод расчёта
     pub method: OverheadMethod,
 }
 
+impl OverheadSettings {
+    /// Overhead from ФОТ at the МДС rate for `category`
+    pub fn for_category(category: WorkCategory) -> Self {
+        Self {
+            rate: default_rates(category).0,
+            method: OverheadMethod::FromLaborCost,
+        }
+    }
+}
+
 impl Default for OverheadSettings {
     fn default() -> Self {
         Self {
@@ -307,9 +970,10 @@ impl Default for OverheadSettings {
 }
 
 /// Метод расчёта накладных расходов
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OverheadMethod {
     /// От фонда оплаты труда (ФОТ = ОЗП + ЗПМ)
+    #[default]
     FromLaborCost,
     /// От прямых затрат
     FromDirectCost,
@@ -321,11 +985,22 @@ pub enum OverheadMethod {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProfitSettings {
     /// Ставка сметной прибыли (от ФОТ)
+    #[serde(with = "crate::serde_decimal")]
     pub rate: Decimal,
     /// Метод расчёта
     pub method: ProfitMethod,
 }
 
+impl ProfitSettings {
+    /// Profit from ФОТ at the МДС rate for `category`
+    pub fn for_category(category: WorkCategory) -> Self {
+        Self {
+            rate: default_rates(category).1,
+            method: ProfitMethod::FromLaborCost,
+        }
+    }
+}
+
 impl Default for ProfitSettings {
     fn default() -> Self {
         Self {
@@ -336,9 +1011,10 @@ impl Default for ProfitSettings {
 }
 
 /// Метод расчёта сметной прибыли
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProfitMethod {
     /// От фонда оплаты труда
+    #[default]
     FromLaborCost,
     /// От прямых затрат
     FromDirectCost,
@@ -347,9 +1023,10 @@ pub enum ProfitMethod {
 }
 
 /// Статус сметы
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EstimateStatus {
     /// Черновик
+    #[default]
     Draft,
     /// В работе
     InProgress,
@@ -361,9 +1038,21 @@ pub enum EstimateStatus {
     Archived,
 }
 
-impl Default for EstimateStatus {
-    fn default() -> Self {
-        Self::Draft
+impl EstimateStatus {
+    /// Check whether the workflow allows moving from this status to `to`:
+    /// Draft → InProgress → OnReview → Approved → Archived, with OnReview
+    /// able to return to InProgress and InProgress back to Draft
+    pub fn can_transition_to(self, to: EstimateStatus) -> bool {
+        use EstimateStatus::*;
+        matches!(
+            (self, to),
+            (Draft, InProgress)
+                | (InProgress, Draft)
+                | (InProgress, OnReview)
+                | (OnReview, InProgress)
+                | (OnReview, Approved)
+                | (Approved, Archived)
+        )
     }
 }
 
@@ -378,6 +1067,21 @@ pub struct CalculationSettings {
     pub vat_rate: Decimal,
     /// Индекс пересчёта
     pub index: Decimal,
+    /// Система налогообложения подрядчика
+    #[serde(default)]
+    pub taxation: TaxationRegime,
+    /// Коэффициент компенсации НДС при УСН к материалам и ЭМ (например, 1.20)
+    #[serde(default)]
+    pub usn_vat_compensation: Option<Decimal>,
+    /// Ставка резерва средств на непредвиденные работы и затраты (от итога без НДС)
+    #[serde(default)]
+    pub contingency_rate: Decimal,
+    /// Метод расчёта накладных расходов (при `Fixed` ставка — сумма в рублях)
+    #[serde(default)]
+    pub overhead_method: OverheadMethod,
+    /// Метод расчёта сметной прибыли (при `Fixed` ставка — сумма в рублях)
+    #[serde(default)]
+    pub profit_method: ProfitMethod,
 }
 
 impl Default for CalculationSettings {
@@ -387,10 +1091,70 @@ impl Default for CalculationSettings {
             profit_rate: Decimal::new(8, 2),
             vat_rate: Decimal::new(20, 2),
             index: Decimal::ONE,
+            taxation: TaxationRegime::Osn,
+            usn_vat_compensation: None,
+            contingency_rate: Decimal::ZERO,
+            overhead_method: OverheadMethod::FromLaborCost,
+            profit_method: ProfitMethod::FromLaborCost,
+        }
+    }
+}
+
+impl CalculationSettings {
+    /// Settings of `estimate` with `overrides` applied on top; fields left
+    /// as `None` keep the estimate's values
+    pub fn from_estimate_with(estimate: &Estimate, overrides: SettingsOverride) -> Self {
+        overrides.apply(estimate.calculation_settings())
+    }
+}
+
+/// Временные переопределения настроек расчёта сметы
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SettingsOverride {
+    /// Индекс пересчёта
+    pub index: Option<Decimal>,
+    /// Ставка накладных расходов
+    pub overhead_rate: Option<Decimal>,
+    /// Ставка сметной прибыли
+    pub profit_rate: Option<Decimal>,
+    /// Ставка НДС
+    pub vat_rate: Option<Decimal>,
+    /// Начислять ли НДС
+    pub include_vat: Option<bool>,
+}
+
+impl SettingsOverride {
+    /// Apply the overrides on top of base settings
+    pub fn apply(&self, mut settings: CalculationSettings) -> CalculationSettings {
+        if let Some(index) = self.index {
+            settings.index = index;
+        }
+        if let Some(overhead_rate) = self.overhead_rate {
+            settings.overhead_rate = overhead_rate;
         }
+        if let Some(profit_rate) = self.profit_rate {
+            settings.profit_rate = profit_rate;
+        }
+        if let Some(vat_rate) = self.vat_rate {
+            settings.vat_rate = vat_rate;
+        }
+        if self.include_vat == Some(false) {
+            settings.vat_rate = Decimal::ZERO;
+        }
+        settings
     }
 }
 
+/// Система налогообложения
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaxationRegime {
+    /// Общая система (с НДС)
+    #[default]
+    Osn,
+    /// Упрощённая система (без НДС)
+    Usn,
+}
+
 /// Итоги сметы
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct EstimateTotals {
@@ -408,7 +1172,13 @@ pub struct EstimateTotals {
     pub overhead: Decimal,
     /// Сметная прибыль
     pub profit: Decimal,
-    /// Итого без НДС
+    /// Резерв средств на непредвиденные работы и затраты
+    #[serde(default)]
+    pub contingency: Decimal,
+    /// Лимитированные затраты, см. [`Estimate::apply_limited_costs`]
+    #[serde(default)]
+    pub limited_costs: Decimal,
+    /// Итого без НДС (включая резерв и лимитированные затраты)
     pub subtotal: Decimal,
     /// НДС
     pub vat: Decimal,
@@ -416,16 +1186,138 @@ pub struct EstimateTotals {
     pub total: Decimal,
 }
 
+/// Налоговая база сметы по составляющим (в текущих ценах)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaxBase {
+    /// Материалы (входной НДС может быть принят к вычету)
+    pub vatable_materials: Decimal,
+    /// ФОТ (ОЗП + ЗПМ)
+    pub labor_base: Decimal,
+    /// Эксплуатация машин без ЗПМ
+    pub machine_base: Decimal,
+}
+
 impl EstimateTotals {
+    /// Indexed component bases for VAT and tax analysis: materials, ФОТ
+    /// (labor plus machine operator wages) and machine costs
+    pub fn tax_base(&self) -> TaxBase {
+        TaxBase {
+            vatable_materials: self.material_costs,
+            labor_base: self.labor_costs + self.machine_operator_costs,
+            machine_base: self.machine_costs,
+        }
+    }
+
     /// Format as Russian rubles
     pub fn format_rub(&self) -> String {
         format!("{:.2} ₽", self.total)
     }
+
+    /// Check that subtotal and total add up from their parts within `tol`
+    pub fn is_consistent(&self, tol: Decimal) -> bool {
+        let parts = self.direct_costs
+            + self.overhead
+            + self.profit
+            + self.contingency
+            + self.limited_costs;
+        (self.subtotal - parts).abs() <= tol
+            && (self.total - (self.subtotal + self.vat)).abs() <= tol
+    }
+
+    /// Split VAT into recoverable and non-recoverable parts, given the
+    /// recoverable fraction in `[0, 1]`
+    pub fn vat_split(&self, recoverable_fraction: Decimal) -> Result<(Decimal, Decimal)> {
+        if recoverable_fraction < Decimal::ZERO || recoverable_fraction > Decimal::ONE {
+            return Err(DeniDomError::Validation(format!(
+                "recoverable VAT fraction must be within [0, 1], got {}",
+                recoverable_fraction
+            )));
+        }
+
+        let recoverable = self.vat * recoverable_fraction;
+        Ok((recoverable, self.vat - recoverable))
+    }
+
+    /// Single effective index: indexed direct costs divided by the direct
+    /// costs in base prices. Returns zero when the base is zero.
+    pub fn effective_index(&self, base_direct_costs: Decimal) -> Decimal {
+        if base_direct_costs.is_zero() {
+            return Decimal::ZERO;
+        }
+        self.direct_costs / base_direct_costs
+    }
+
+    /// Share of VAT in the grand total (1/6 ≈ 16.7% at a 20% rate).
+    /// Returns zero when the total is zero.
+    pub fn vat_share(&self) -> f64 {
+        if self.total.is_zero() {
+            return 0.0;
+        }
+        (self.vat / self.total).to_f64().unwrap_or(0.0)
+    }
+
+    /// Себестоимость: direct costs plus overhead, without profit and VAT
+    pub fn cost_price(&self) -> Decimal {
+        self.direct_costs + self.overhead
+    }
+
+    /// Markup of the grand total over [`cost_price`](Self::cost_price)
+    /// (`total / cost_price - 1`). Returns zero when the cost price is zero.
+    pub fn markup_over_cost(&self) -> f64 {
+        let cost_price = self.cost_price();
+        if cost_price.is_zero() {
+            return 0.0;
+        }
+        (self.total / cost_price - Decimal::ONE).to_f64().unwrap_or(0.0)
+    }
+}
+
+impl std::ops::Add for EstimateTotals {
+    type Output = EstimateTotals;
+
+    fn add(self, rhs: EstimateTotals) -> EstimateTotals {
+        EstimateTotals {
+            direct_costs: self.direct_costs + rhs.direct_costs,
+            labor_costs: self.labor_costs + rhs.labor_costs,
+            machine_operator_costs: self.machine_operator_costs + rhs.machine_operator_costs,
+            material_costs: self.material_costs + rhs.material_costs,
+            machine_costs: self.machine_costs + rhs.machine_costs,
+            overhead: self.overhead + rhs.overhead,
+            profit: self.profit + rhs.profit,
+            contingency: self.contingency + rhs.contingency,
+            limited_costs: self.limited_costs + rhs.limited_costs,
+            subtotal: self.subtotal + rhs.subtotal,
+            vat: self.vat + rhs.vat,
+            total: self.total + rhs.total,
+        }
+    }
+}
+
+impl std::ops::Sub for EstimateTotals {
+    type Output = EstimateTotals;
+
+    fn sub(self, rhs: EstimateTotals) -> EstimateTotals {
+        EstimateTotals {
+            direct_costs: self.direct_costs - rhs.direct_costs,
+            labor_costs: self.labor_costs - rhs.labor_costs,
+            machine_operator_costs: self.machine_operator_costs - rhs.machine_operator_costs,
+            material_costs: self.material_costs - rhs.material_costs,
+            machine_costs: self.machine_costs - rhs.machine_costs,
+            overhead: self.overhead - rhs.overhead,
+            profit: self.profit - rhs.profit,
+            contingency: self.contingency - rhs.contingency,
+            limited_costs: self.limited_costs - rhs.limited_costs,
+            subtotal: self.subtotal - rhs.subtotal,
+            vat: self.vat - rhs.vat,
+            total: self.total - rhs.total,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::calculator::calculate_totals;
 
     #[test]
     fn test_estimate_creation() {
@@ -471,4 +1363,807 @@ mod tests {
 
         assert_eq!(item.total_direct_cost(), Decimal::new(50000, 0));
     }
+
+    fn priced_item(quantity: i64, direct: i64, labor: i64) -> EstimateItem {
+        let mut item = EstimateItem::new(
+            "ФЕР01-01-001-01".to_string(),
+            "Разработка грунта".to_string(),
+            MeasureUnit::CubicMeter,
+            Decimal::new(quantity, 0),
+        );
+        item.unit_costs = UnitCosts::new(
+            Decimal::new(direct, 0),
+            Decimal::new(labor, 0),
+            Decimal::ZERO,
+            Decimal::new(direct - labor, 0),
+            Decimal::ZERO,
+        );
+        item
+    }
+
+    #[test]
+    fn test_section_total() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+
+        let mut earthworks = EstimateSection::new(1, "Земляные работы".to_string());
+        earthworks.add_item(priced_item(10, 500, 200));
+        let mut finishing = EstimateSection::new(2, "Отделка".to_string());
+        finishing.add_item(priced_item(20, 300, 100));
+        finishing.add_item(priced_item(5, 1000, 400));
+
+        let finishing_id = finishing.id;
+        estimate.sections.push(earthworks);
+        estimate.sections.push(finishing);
+
+        let total = estimate.section_total(finishing_id).unwrap();
+        let (_, expected) = estimate
+            .section_totals()
+            .into_iter()
+            .find(|(id, _)| *id == finishing_id)
+            .unwrap();
+
+        assert_eq!(total.direct_costs, Decimal::new(11000, 0));
+        assert_eq!(total.total, expected.total);
+        assert!(estimate.section_total(Uuid::new_v4()).is_none());
+    }
+
+    #[test]
+    fn test_costs_by_collection() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Работы".to_string());
+        section.add_item(priced_item(10, 500, 200));
+        let mut plaster = priced_item(20, 300, 100);
+        plaster.code = "ФЕР15-02-016-03".to_string();
+        section.add_item(plaster);
+        section.add_item(priced_item(2, 100, 50));
+        estimate.sections.push(section);
+
+        let groups = estimate.costs_by_collection();
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, "ФЕР01");
+        assert_eq!(groups[0].1.direct_costs, Decimal::new(5200, 0));
+        assert_eq!(groups[1].0, "ФЕР15");
+        assert_eq!(groups[1].1.direct_costs, Decimal::new(6000, 0));
+    }
+
+    #[test]
+    fn test_scale_quantities() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Работы".to_string());
+        let mut item = priced_item(10, 500, 200);
+        item.labor_hours = Decimal::new(40, 0);
+        section.add_item(item);
+        section.add_item(priced_item(3, 1000, 400));
+        estimate.sections.push(section);
+
+        let labor_hours = |estimate: &Estimate| -> Decimal {
+            estimate.all_items().iter().map(|item| item.labor_hours * item.quantity).sum()
+        };
+        let before = crate::calculator::calculate_estimate_totals(&estimate);
+        let hours_before = labor_hours(&estimate);
+        estimate.scale_quantities(Decimal::new(11, 1)).unwrap();
+        let after = crate::calculator::calculate_estimate_totals(&estimate);
+
+        assert_eq!(after.direct_costs, before.direct_costs * Decimal::new(11, 1));
+        assert_eq!(estimate.sections[0].items[0].labor_hours, Decimal::new(40, 0));
+        assert_eq!(labor_hours(&estimate), hours_before * Decimal::new(11, 1));
+        assert!(estimate.scale_quantities(Decimal::ZERO).is_err());
+        assert!(estimate.scale_quantities(Decimal::new(-1, 0)).is_err());
+    }
+
+    #[test]
+    fn test_merge_sections_by_name() {
+        let item = |code: &str| {
+            EstimateItem::new(
+                code.to_string(),
+                "Работа".to_string(),
+                MeasureUnit::SquareMeter,
+                Decimal::ONE,
+            )
+        };
+
+        let mut estimate = Estimate::new("Тест".to_string(), "Объект".to_string());
+        let mut finishing = EstimateSection::new(1, "Отделочные работы".to_string());
+        finishing.coefficient = Some(Decimal::new(115, 2));
+        finishing.add_item(item("ФЕР15-01-001-01"));
+        let mut roofing = EstimateSection::new(2, "Кровля".to_string());
+        roofing.add_item(item("ФЕР12-01-001-01"));
+        let mut finishing_again = EstimateSection::new(3, "  отделочные РАБОТЫ ".to_string());
+        finishing_again.coefficient = Some(Decimal::new(12, 1));
+        finishing_again.add_item(item("ФЕР15-02-001-01"));
+        finishing_again.add_item(item("ФЕР15-04-001-01"));
+        estimate.sections = vec![finishing, roofing, finishing_again];
+        for (offset, item) in estimate.sections.iter_mut().flat_map(|s| s.items.iter_mut()).enumerate() {
+            item.unit_costs = UnitCosts::new(
+                Decimal::new(701 + offset as i64, 0),
+                Decimal::new(300, 0),
+                Decimal::ZERO,
+                Decimal::new(401 + offset as i64, 0),
+                Decimal::ZERO,
+            );
+        }
+        let moved_id = estimate.sections[2].items[0].id;
+        let kept_id = estimate.sections[0].items[0].id;
+        let before = crate::calculator::calculate_estimate_totals(&estimate);
+
+        estimate.merge_sections_by_name();
+
+        let after = crate::calculator::calculate_estimate_totals(&estimate);
+        assert_eq!(after.direct_costs, before.direct_costs);
+        assert_eq!(after.total, before.total);
+        assert_eq!(estimate.effective_coefficient(kept_id), Some(Decimal::new(115, 2)));
+
+        assert_eq!(estimate.sections.len(), 2);
+        assert_eq!(estimate.sections[0].name, "Отделочные работы");
+        assert_eq!(estimate.sections[0].items.len(), 3);
+        assert_eq!(estimate.sections[1].number, 2);
+        assert_eq!(estimate.effective_coefficient(moved_id), Some(Decimal::new(12, 1)));
+
+        let positions: Vec<u32> = estimate.all_items().iter().map(|i| i.position).collect();
+        assert_eq!(positions, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_vat_split() {
+        let totals = EstimateTotals {
+            vat: Decimal::new(123457, 2),
+            ..Default::default()
+        };
+
+        let (recoverable, non_recoverable) = totals.vat_split(Decimal::new(8, 1)).unwrap();
+        assert_eq!(recoverable + non_recoverable, totals.vat);
+        assert_eq!(recoverable, Decimal::new(987656, 3));
+
+        assert!(totals.vat_split(Decimal::new(11, 1)).is_err());
+        assert!(totals.vat_split(Decimal::new(-1, 1)).is_err());
+    }
+
+    #[test]
+    fn test_effective_index_within_component_indices() {
+        use crate::coefficients::{ComponentCoefficientSet, CostComponent};
+
+        let base = EstimateTotals {
+            direct_costs: Decimal::new(1000, 0),
+            labor_costs: Decimal::new(300, 0),
+            material_costs: Decimal::new(500, 0),
+            machine_costs: Decimal::new(200, 0),
+            ..Default::default()
+        };
+
+        let mut indices = ComponentCoefficientSet::new();
+        indices.add("ОЗП".to_string(), Decimal::new(2500, 2), &[CostComponent::Labor]);
+        indices.add("М".to_string(), Decimal::new(650, 2), &[CostComponent::Materials]);
+        indices.add("ЭМ".to_string(), Decimal::new(900, 2), &[CostComponent::Machines]);
+
+        let mut indexed = base;
+        indices.apply_to_totals(&mut indexed, &CalculationSettings::default());
+
+        let effective = indexed.effective_index(base.direct_costs);
+        assert!(effective > Decimal::new(650, 2) && effective < Decimal::new(2500, 2));
+        assert_eq!(effective, Decimal::new(1255, 2));
+        assert_eq!(indexed.effective_index(Decimal::ZERO), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_items_with_tag() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        for quantity in 1..=3 {
+            section.add_item(priced_item(quantity, 100, 10));
+        }
+        section.items[0].add_tag("под вопросом");
+        section.items[0].add_tag("под вопросом");
+        section.items[2].add_tag("под вопросом");
+        section.items[2].add_tag("утверждено");
+        estimate.sections.push(section);
+
+        let questioned = estimate.items_with_tag("под вопросом");
+        assert_eq!(questioned.len(), 2);
+        assert_eq!(questioned[0].tags, vec!["под вопросом".to_string()]);
+        assert_eq!(estimate.items_with_tag("утверждено").len(), 1);
+
+        let restored = Estimate::from_bincode(&estimate.to_bincode().unwrap()).unwrap();
+        assert_eq!(restored.items_with_tag("под вопросом").len(), 2);
+
+        let hash = estimate.content_hash().unwrap();
+        estimate.sections[0].items[1].add_tag("утверждено");
+        assert_ne!(estimate.content_hash().unwrap(), hash);
+    }
+
+    #[test]
+    fn test_cost_per_metric() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        section.add_item(priced_item(10, 1000, 0));
+        estimate.sections.push(section);
+
+        // Без ФОТ накладных и прибыли нет: 10 000 ₽ + НДС 20% = 12 000 ₽ на 100 м²
+        let area = Decimal::new(100, 0);
+        assert_eq!(estimate.cost_per_metric(area), Decimal::new(120, 0));
+        assert_eq!(
+            estimate.cost_per_metric_with_net(area),
+            (Decimal::new(120, 0), Decimal::new(100, 0))
+        );
+        assert_eq!(estimate.cost_per_metric(Decimal::ZERO), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_settings_from_estimate_with_index_override() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        estimate.coefficients.index = Decimal::new(850, 2);
+        estimate.overhead.rate = Decimal::new(95, 2);
+        estimate.profit.rate = Decimal::new(50, 2);
+
+        let settings = CalculationSettings::from_estimate_with(
+            &estimate,
+            SettingsOverride {
+                index: Some(Decimal::new(912, 2)),
+                ..Default::default()
+            },
+        );
+
+        let base = estimate.calculation_settings();
+        assert_eq!(settings.index, Decimal::new(912, 2));
+        assert_eq!(settings.overhead_rate, base.overhead_rate);
+        assert_eq!(settings.profit_rate, base.profit_rate);
+        assert_eq!(settings.vat_rate, base.vat_rate);
+    }
+
+    #[test]
+    fn test_overhead_profit_by_section() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        estimate.coefficients.index = Decimal::new(850, 2);
+        let mut earthworks = EstimateSection::new(1, "Земляные работы".to_string());
+        earthworks.add_item(priced_item(10, 1000, 300));
+        let mut masonry = EstimateSection::new(2, "Кладка".to_string());
+        masonry.add_item(priced_item(3, 5000, 1200));
+        masonry.add_item(priced_item(7, 200, 50));
+        estimate.sections.push(earthworks);
+        estimate.sections.push(masonry);
+
+        let by_section = estimate.overhead_profit_by_section();
+        assert_eq!(by_section.len(), 2);
+        assert_eq!(by_section[0].0, estimate.sections[0].id);
+
+        let totals = crate::calculator::calculate_estimate_totals(&estimate);
+        let overhead: Decimal = by_section.iter().map(|(_, overhead, _)| *overhead).sum();
+        let profit: Decimal = by_section.iter().map(|(_, _, profit)| *profit).sum();
+        assert_eq!(overhead, totals.overhead);
+        assert_eq!(profit, totals.profit);
+    }
+
+    #[test]
+    fn test_apply_item_coefficient_to_code_prefix() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        let mut demolition = priced_item(10, 1000, 300);
+        demolition.code = "ФЕР46-04-001-01".to_string();
+        section.add_item(demolition);
+        section.add_item(priced_item(5, 2000, 500));
+        estimate.sections.push(section);
+
+        let settings = estimate.calculation_settings();
+        let line_total = |estimate: &Estimate, index: usize| {
+            crate::calculator::calculate_totals(&[&estimate.sections[0].items[index]], &settings).total
+        };
+        let before = [line_total(&estimate, 0), line_total(&estimate, 1)];
+
+        estimate.apply_item_coefficient(|item| item.code.starts_with("ФЕР46"), Decimal::new(115, 2));
+
+        assert_eq!(line_total(&estimate, 0), before[0] * Decimal::new(115, 2));
+        assert_eq!(line_total(&estimate, 1), before[1]);
+        assert_eq!(estimate.sections[0].items[1].coefficient, None);
+    }
+
+    #[test]
+    fn test_status_transition() {
+        let mut estimate = Estimate::new("Тест".to_string(), "Объект".to_string());
+        estimate.transition(EstimateStatus::InProgress).unwrap();
+        assert_eq!(estimate.status, EstimateStatus::InProgress);
+
+        let err = estimate.transition(EstimateStatus::Archived).unwrap_err();
+        assert!(matches!(err, DeniDomError::Validation(_)));
+        assert_eq!(estimate.status, EstimateStatus::InProgress);
+    }
+
+    #[test]
+    fn test_repair_rates_differ_from_construction() {
+        let construction = default_rates(WorkCategory::Construction);
+        let repair = default_rates(WorkCategory::Repair);
+        assert_ne!(repair, construction);
+        assert!(repair.0 < construction.0);
+        assert!(repair.1 < construction.1);
+
+        let overhead = OverheadSettings::for_category(WorkCategory::Repair);
+        let profit = ProfitSettings::for_category(WorkCategory::Repair);
+        assert_eq!(overhead.rate, Decimal::new(95, 2));
+        assert_eq!(profit.rate, Decimal::new(50, 2));
+        assert_eq!(overhead.method, OverheadMethod::FromLaborCost);
+    }
+
+    #[test]
+    fn test_duration_days() {
+        let mut estimate = Estimate::new("Тест".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел 1".to_string());
+        for (hours, quantity) in [(12, 10), (2, 100)] {
+            let mut item = EstimateItem::new(
+                "ФЕР01-01-001-01".to_string(),
+                "Работа".to_string(),
+                MeasureUnit::CubicMeter,
+                Decimal::new(quantity, 0),
+            );
+            item.labor_hours = Decimal::new(hours, 0);
+            section.add_item(item);
+        }
+        estimate.sections.push(section);
+
+        // 12×10 + 2×100 = 320 чел-ч; бригада 4 × 8 ч = 32 чел-ч в смену
+        assert_eq!(estimate.duration_days(4, 8.0), 10.0);
+        assert_eq!(estimate.duration_days(0, 8.0), 0.0);
+    }
+
+    #[test]
+    fn test_normalize_quantity() {
+        let mut pieces = EstimateItem::new(
+            "ФЕР20-02-001-01".to_string(),
+            "Установка решёток".to_string(),
+            MeasureUnit::Unit,
+            Decimal::new(32, 1),
+        );
+        pieces.normalize_quantity();
+        assert_eq!(pieces.quantity, Decimal::new(4, 0));
+
+        let mut volume = EstimateItem::new(
+            "ФЕР01-01-001-01".to_string(),
+            "Разработка грунта".to_string(),
+            MeasureUnit::CubicMeter,
+            Decimal::new(32, 1),
+        );
+        volume.normalize_quantity();
+        assert_eq!(volume.quantity, Decimal::new(32, 1));
+    }
+
+    #[test]
+    fn test_effective_coefficient() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        estimate.coefficients.index = Decimal::new(850, 2);
+
+        let mut section = EstimateSection::new(1, "Работы".to_string());
+        section.coefficient = Some(Decimal::new(115, 2));
+        let mut item = priced_item(10, 500, 200);
+        item.coefficient = Some(Decimal::new(12, 1));
+        let item_id = item.id;
+        section.add_item(item);
+        estimate.sections.push(section);
+
+        let expected = Decimal::new(850, 2) * Decimal::new(115, 2) * Decimal::new(12, 1);
+        assert_eq!(estimate.effective_coefficient(item_id), Some(expected));
+        assert_eq!(estimate.effective_coefficient(Uuid::new_v4()), None);
+    }
+
+    #[test]
+    fn test_section_coefficient_scales_totals() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Работы".to_string());
+        section.add_item(priced_item(10, 500, 200));
+        estimate.sections.push(section);
+        let plain = estimate.section_totals()[0].1;
+
+        estimate.sections[0].coefficient = Some(Decimal::new(115, 2));
+        let scaled = estimate.section_totals()[0].1;
+        let settings = estimate.calculation_settings();
+
+        assert_eq!(scaled.direct_costs, plain.direct_costs * Decimal::new(115, 2));
+        assert_eq!(scaled.total, estimate.sections[0].calculate_totals(&settings).total);
+        assert_eq!(scaled.total, crate::calculator::calculate_estimate_totals(&estimate).total);
+        assert!(scaled.total > plain.total);
+    }
+
+    #[test]
+    fn test_section_totals_sum_with_winter() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut earthworks = EstimateSection::new(1, "Земляные работы".to_string());
+        earthworks.add_item(priced_item(10, 500, 200));
+        let mut finishing = EstimateSection::new(2, "Отделка".to_string());
+        finishing.coefficient = Some(Decimal::new(115, 2));
+        finishing.add_item(priced_item(4, 900, 350));
+        estimate.sections.push(earthworks);
+        estimate.sections.push(finishing);
+        estimate.overhead.method = OverheadMethod::FromDirectCost;
+        let plain = crate::calculator::calculate_estimate_totals(&estimate);
+
+        estimate.coefficients.apply_winter(6, "Земляные работы").unwrap();
+        let total = crate::calculator::calculate_estimate_totals(&estimate);
+        let sum = estimate
+            .section_totals()
+            .into_iter()
+            .fold(EstimateTotals::default(), |acc, (_, totals)| acc + totals);
+
+        assert!(total.labor_costs > plain.labor_costs);
+        assert_eq!(sum.direct_costs, total.direct_costs);
+        assert_eq!(sum.overhead, total.overhead);
+        assert_eq!(sum.vat, total.vat);
+        assert_eq!(sum.total, total.total);
+        assert_eq!(total.overhead, total.direct_costs * estimate.overhead.rate);
+    }
+
+    #[test]
+    fn test_section_totals_sum_with_fixed_markups() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut earthworks = EstimateSection::new(1, "Земляные работы".to_string());
+        earthworks.add_item(priced_item(10, 500, 200));
+        earthworks.add_item(priced_item(3, 700, 100));
+        let mut finishing = EstimateSection::new(2, "Отделка".to_string());
+        finishing.coefficient = Some(Decimal::new(115, 2));
+        finishing.add_item(priced_item(4, 900, 350));
+        estimate.sections.push(earthworks);
+        estimate.sections.push(finishing);
+        estimate.overhead.method = OverheadMethod::Fixed;
+        estimate.overhead.rate = Decimal::new(15_000, 0);
+        estimate.profit.method = ProfitMethod::Fixed;
+        estimate.profit.rate = Decimal::new(8_000, 0);
+
+        let total = crate::calculator::calculate_estimate_totals(&estimate);
+        let sections = estimate
+            .section_totals()
+            .into_iter()
+            .fold(EstimateTotals::default(), |acc, (_, totals)| acc + totals);
+        let lines = estimate
+            .line_totals(&estimate.calculation_settings())
+            .into_iter()
+            .fold(EstimateTotals::default(), |acc, (_, totals)| acc + totals);
+        let (overhead, profit) = estimate
+            .overhead_profit_by_section()
+            .into_iter()
+            .fold((Decimal::ZERO, Decimal::ZERO), |acc, (_, o, p)| (acc.0 + o, acc.1 + p));
+
+        let close = |a: Decimal, b: Decimal| (a - b).abs() < Decimal::new(1, 20);
+        assert_eq!(total.overhead, Decimal::new(15_000, 0));
+        assert_eq!(total.profit, Decimal::new(8_000, 0));
+        for sum in [sections, lines] {
+            assert!(close(sum.overhead, total.overhead), "{}", sum.overhead);
+            assert!(close(sum.profit, total.profit), "{}", sum.profit);
+            assert!(close(sum.total, total.total), "{}", sum.total);
+        }
+        assert!(close(overhead, total.overhead) && close(profit, total.profit));
+        assert!(estimate.rounding_discrepancy(&estimate.calculation_settings()).abs() <= Decimal::new(3, 2));
+    }
+
+    #[test]
+    fn test_regional_and_height_spare_materials() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        section.add_item(priced_item(10, 500, 200));
+        estimate.sections.push(section);
+
+        let raised = Coefficients {
+            regional: Some(Decimal::new(12, 1)),
+            height: Some(Decimal::new(11, 1)),
+            cramped: Some(Decimal::new(115, 2)),
+            ..estimate.coefficients.clone()
+        };
+        let base = estimate.totals_with_coefficients(&estimate.coefficients);
+        let totals = estimate.totals_with_coefficients(&raised);
+
+        assert_eq!(raised.combined(), Decimal::new(115, 2));
+        assert_eq!(totals.material_costs, base.material_costs * Decimal::new(115, 2));
+        assert_eq!(totals.labor_costs, base.labor_costs * Decimal::new(115, 2) * Decimal::new(132, 2));
+        assert_eq!(totals.direct_costs, totals.labor_costs + totals.material_costs);
+    }
+
+    #[test]
+    fn test_coefficient_delta_winter() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Земляные работы".to_string());
+        section.add_item(priced_item(10, 500, 200));
+        estimate.sections.push(section);
+
+        let with_winter = Coefficients {
+            winter: Some(Decimal::new(103, 2)), // 1.03
+            ..estimate.coefficients.clone()
+        };
+        let delta = estimate.coefficient_delta(&with_winter);
+
+        let items = estimate.all_items();
+        let base = calculate_totals(&items, &estimate.calculation_settings());
+        let winter_settings = CalculationSettings {
+            index: Decimal::new(103, 2),
+            ..estimate.calculation_settings()
+        };
+        let winter = calculate_totals(&items, &winter_settings);
+
+        assert!(delta.labor_costs > Decimal::ZERO);
+        assert_eq!(delta.labor_costs, winter.labor_costs - base.labor_costs);
+        assert_eq!(delta.total, winter.total - base.total);
+    }
+
+    #[test]
+    fn test_component_delta_for_winter() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Земляные работы".to_string());
+        section.add_item(priced_item(10, 500, 200));
+        estimate.sections.push(section);
+
+        let wc = WinterCoefficient {
+            zone: 4,
+            work_section: "Земляные работы".to_string(),
+            labor_coefficient: Decimal::new(105, 2),
+            machine_coefficient: Decimal::new(103, 2),
+            material_coefficient: Decimal::new(101, 2),
+        };
+        let delta = estimate.component_delta_for(&wc);
+        let base = crate::calculator::calculate_estimate_totals(&estimate);
+
+        assert_eq!(delta.labor_costs, base.labor_costs * (wc.labor_coefficient - Decimal::ONE));
+        assert_eq!(
+            delta.material_costs,
+            base.material_costs * (wc.material_coefficient - Decimal::ONE)
+        );
+        assert_eq!(delta.machine_costs, Decimal::ZERO);
+        assert!(delta.total > Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_validate_collects_problems() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Земляные работы".to_string());
+        section.add_item(priced_item(10, 500, 200));
+        estimate.sections.push(section);
+        assert!(estimate.validate().is_ok());
+
+        estimate.name = " ".to_string();
+        estimate.sections[0].items[0].quantity = Decimal::new(-1, 0);
+        estimate.sections[0].items[0].code.clear();
+        let errors = estimate.validate().unwrap_err();
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[test]
+    fn test_set_quantity_formula() {
+        let mut item = priced_item(10, 500, 200);
+        item.set_quantity_formula("12*3,5+2").unwrap();
+        assert_eq!(item.quantity, Decimal::new(44, 0));
+        assert_eq!(item.quantity_expr.as_ref().unwrap().source, "12*3,5+2");
+
+        assert!(item.set_quantity_formula("1/0").is_err());
+        assert_eq!(item.quantity, Decimal::new(44, 0));
+    }
+
+    #[test]
+    fn test_totals_both_levels() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Земляные работы".to_string());
+        section.add_item(priced_item(10, 500, 200));
+        estimate.sections.push(section);
+        estimate.coefficients.index = Decimal::new(85, 1);
+
+        let (base, current) = estimate.totals_both_levels();
+        assert_eq!(base.direct_costs, Decimal::new(5000, 0));
+        assert_eq!(current.direct_costs, base.direct_costs * estimate.coefficients.index);
+    }
+
+    #[test]
+    fn test_rounding_discrepancy() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Материалы".to_string());
+        for _ in 0..3 {
+            let mut item = EstimateItem::new(
+                "ФССЦ-01.7.15.03-0042".to_string(),
+                "Болты".to_string(),
+                MeasureUnit::Unit,
+                Decimal::ONE,
+            );
+            let half_kopeck = Decimal::new(5, 3);
+            item.unit_costs =
+                UnitCosts::new(half_kopeck, Decimal::ZERO, Decimal::ZERO, half_kopeck, Decimal::ZERO);
+            section.add_item(item);
+        }
+        estimate.sections.push(section);
+
+        let settings = CalculationSettings {
+            overhead_rate: Decimal::ZERO,
+            profit_rate: Decimal::ZERO,
+            vat_rate: Decimal::ZERO,
+            ..Default::default()
+        };
+        // Each line rounds 0.005 up to 0.01 (0.03 in total), the grand total 0.015 to 0.02
+        assert_eq!(estimate.rounding_discrepancy(&settings), Decimal::new(1, 2));
+
+        estimate.sections[0].items.truncate(1);
+        assert_eq!(estimate.rounding_discrepancy(&settings), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_usd_resource_cost_in_rub() {
+        let mut item = EstimateItem::new(
+            "ФЕР08-01-001-01".to_string(),
+            "Монтаж импортного оборудования".to_string(),
+            MeasureUnit::Unit,
+            Decimal::from(2),
+        );
+        item.resources.push(Resource {
+            id: Uuid::new_v4(),
+            resource_type: ResourceType::Equipment,
+            code: "IMP-001".to_string(),
+            name: "Насос".to_string(),
+            unit: MeasureUnit::Unit,
+            consumption_rate: Decimal::ONE,
+            unit_price: Decimal::from(1500),
+            currency: Currency::Usd,
+            fx_rate: Decimal::new(925, 1),
+        });
+
+        assert_eq!(item.resources[0].unit_price_rub(), Decimal::from(138_750));
+        assert_eq!(item.total_resources_cost(), Decimal::from(277_500));
+    }
+
+    #[test]
+    fn test_resource_defaults_to_rub() {
+        let json = r#"{"id":"00000000-0000-0000-0000-000000000000","resource_type":"Material",
+            "code":"М-1","name":"Цемент","unit":"Ton","consumption_rate":"2","unit_price":"100"}"#;
+        let resource: Resource = serde_json::from_str(json).unwrap();
+
+        assert_eq!(resource.currency, Currency::Rub);
+        assert_eq!(resource.fx_rate, Decimal::ONE);
+        assert_eq!(resource.cost(), Decimal::from(200));
+    }
+
+    #[test]
+    fn test_unpriced_items() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        section.add_item(priced_item(10, 500, 200));
+        section.add_item(priced_item(5, 0, 0));
+        let unpriced_id = section.items[1].id;
+        estimate.sections.push(section);
+
+        let unpriced = estimate.unpriced_items();
+        assert_eq!(unpriced.len(), 1);
+        assert_eq!(unpriced[0].id, unpriced_id);
+    }
+
+    #[test]
+    fn test_item_contributions() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        section.add_item(priced_item(1, 750, 300));
+        section.add_item(priced_item(1, 250, 100));
+        estimate.sections.push(section);
+
+        let contributions = estimate.item_contributions();
+        assert_eq!(contributions.len(), 2);
+        assert_eq!(contributions[0].1, 0.75);
+        assert_eq!(contributions[1].1, 0.25);
+
+        let sum: f64 = contributions.iter().map(|(_, share)| share).sum();
+        assert!((sum - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_bincode_round_trip() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        section.coefficient = Some(Decimal::new(115, 2));
+        for _ in 0..100 {
+            section.add_item(priced_item(10, 500, 200));
+        }
+        estimate.sections.push(section);
+
+        let bytes = estimate.to_bincode().unwrap();
+        let restored = Estimate::from_bincode(&bytes).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&restored).unwrap(),
+            serde_json::to_value(&estimate).unwrap()
+        );
+        assert!(bytes.len() < serde_json::to_vec(&estimate).unwrap().len());
+    }
+
+    #[test]
+    fn test_bincode_round_trip_with_winter() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Земляные работы".to_string());
+        section.add_item(priced_item(10, 500, 200));
+        estimate.sections.push(section);
+        estimate.coefficients.apply_winter(4, "Земляные работы").unwrap();
+
+        let restored = Estimate::from_bincode(&estimate.to_bincode().unwrap()).unwrap();
+
+        let winter = restored.coefficients.winter_components.as_ref().unwrap();
+        assert_eq!(winter.labor_coefficient, Decimal::new(1038, 3));
+        assert_eq!(
+            crate::calculator::calculate_estimate_totals(&restored).total,
+            crate::calculator::calculate_estimate_totals(&estimate).total
+        );
+    }
+
+    #[test]
+    fn test_base_types_used() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        section.add_item(priced_item(1, 100, 50));
+        let mut gesn = priced_item(1, 100, 50);
+        gesn.code = "ГЭСН06-01-001-01".to_string();
+        section.add_item(gesn);
+        let mut custom = priced_item(1, 100, 50);
+        custom.code = "Прайс-лист".to_string();
+        section.add_item(custom);
+        estimate.sections.push(section);
+
+        let bases = estimate.base_types_used();
+        assert_eq!(bases.len(), 2);
+        assert!(bases.contains(&NormativeBase::FER));
+        assert!(bases.contains(&NormativeBase::GESN));
+    }
+
+    #[test]
+    fn test_tax_base_matches_components() {
+        let mut item = priced_item(10, 500, 200);
+        item.unit_costs.machine_operator = Decimal::new(20, 0);
+        item.unit_costs.machines = Decimal::new(80, 0);
+        item.unit_costs.materials = Decimal::new(200, 0);
+        let settings = CalculationSettings {
+            index: Decimal::new(85, 1),
+            ..Default::default()
+        };
+        let totals = calculate_totals(&[&item], &settings);
+
+        let base = totals.tax_base();
+        assert_eq!(base.vatable_materials, totals.material_costs);
+        assert_eq!(base.labor_base, totals.labor_costs + totals.machine_operator_costs);
+        assert_eq!(base.machine_base, totals.machine_costs);
+        assert_eq!(base.labor_base, Decimal::new(18_700, 0));
+    }
+
+    #[test]
+    fn test_calculation_settings_carry_taxation() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Земляные работы".to_string());
+        section.add_item(priced_item(10, 500, 200));
+        estimate.sections.push(section);
+        estimate.taxation = TaxationRegime::Usn;
+        estimate.usn_vat_compensation = Some(Decimal::new(120, 2));
+
+        let settings = estimate.calculation_settings();
+        assert_eq!(settings.taxation, TaxationRegime::Usn);
+        assert_eq!(settings.usn_vat_compensation, Some(Decimal::new(120, 2)));
+
+        let totals = crate::calculator::calculate_estimate_totals(&estimate);
+        assert_eq!(totals.vat, Decimal::ZERO);
+        assert_eq!(totals.total, totals.subtotal);
+        let (_, current) = estimate.totals_both_levels();
+        assert_eq!(current.vat, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_vat_share() {
+        let totals = calculate_totals(&[&priced_item(10, 500, 200)], &CalculationSettings::default());
+        assert!((totals.vat_share() - 1.0 / 6.0).abs() < 1e-9);
+        assert_eq!(EstimateTotals::default().vat_share(), 0.0);
+    }
+
+    #[test]
+    fn test_cost_price_excludes_profit() {
+        let totals = calculate_totals(&[&priced_item(10, 500, 200)], &CalculationSettings::default());
+        let cost_price = totals.cost_price();
+
+        assert_eq!(cost_price, totals.subtotal - totals.contingency - totals.profit);
+        let expected = (totals.profit + totals.contingency + totals.vat) / cost_price;
+        assert!((totals.markup_over_cost() - expected.to_f64().unwrap()).abs() < 1e-9);
+        assert!(totals.markup_over_cost() > totals.vat_share());
+        assert_eq!(EstimateTotals::default().markup_over_cost(), 0.0);
+    }
+
+    #[test]
+    fn test_totals_consistency() {
+        let items = [priced_item(10, 500, 200)];
+        let refs: Vec<&EstimateItem> = items.iter().collect();
+        let mut totals = calculate_totals(&refs, &CalculationSettings::default());
+        assert!(totals.is_consistent(Decimal::ZERO));
+
+        totals.vat += Decimal::ONE;
+        assert!(!totals.is_consistent(Decimal::new(1, 2)));
+        assert!(totals.is_consistent(Decimal::ONE));
+    }
 }
@@ -0,0 +1,173 @@
+//! Localized formatting of estimate totals
+//!
+//! The API and PDF render the same totals with the same labels; the report
+//! built here carries label, raw value and formatted string for each field.
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+use crate::estimate::EstimateTotals;
+
+/// Язык подписей отчёта
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    /// Русский
+    #[default]
+    Ru,
+    /// Английский
+    En,
+}
+
+/// Format a ruble amount with thousands grouped by no-break spaces and a
+/// decimal comma, e.g. `1 234 567,89 ₽`. Matches the WASM `format_rub`:
+/// same separators, midpoints rounded away from zero.
+pub fn format_rub_grouped(value: Decimal) -> String {
+    const NBSP: char = '\u{a0}';
+
+    let rounded = value.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero);
+    let text = format!("{:.2}", rounded.abs());
+    let (int_part, frac_part) = text.split_once('.').unwrap_or((&text, "00"));
+
+    let mut grouped = String::with_capacity(int_part.len() + int_part.len() / 3);
+    for (i, ch) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            grouped.push(NBSP);
+        }
+        grouped.push(ch);
+    }
+
+    let sign = if rounded.is_sign_negative() && !rounded.is_zero() { "-" } else { "" };
+    format!("{}{},{}{}₽", sign, grouped, frac_part, NBSP)
+}
+
+/// Строка отчёта
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportLine {
+    /// Подпись
+    pub label: String,
+    /// Значение
+    #[serde(with = "crate::serde_decimal")]
+    pub value: Decimal,
+    /// Отформатированное значение
+    pub formatted: String,
+}
+
+impl ReportLine {
+    fn new(label: &str, value: Decimal) -> Self {
+        Self {
+            label: label.to_string(),
+            value,
+            formatted: format_rub_grouped(value),
+        }
+    }
+}
+
+/// Итоги сметы с подписями для вывода
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TotalsReport {
+    /// Прямые затраты
+    pub direct_costs: ReportLine,
+    /// ОЗП (оплата труда рабочих)
+    pub labor_costs: ReportLine,
+    /// ЗПМ (зарплата машинистов)
+    pub machine_operator_costs: ReportLine,
+    /// Материалы
+    pub material_costs: ReportLine,
+    /// Эксплуатация машин
+    pub machine_costs: ReportLine,
+    /// Накладные расходы
+    pub overhead: ReportLine,
+    /// Сметная прибыль
+    pub profit: ReportLine,
+    /// Резерв средств на непредвиденные работы и затраты
+    pub contingency: ReportLine,
+    /// Лимитированные затраты
+    pub limited_costs: ReportLine,
+    /// Итого без НДС
+    pub subtotal: ReportLine,
+    /// НДС
+    pub vat: ReportLine,
+    /// Всего с НДС
+    pub total: ReportLine,
+}
+
+impl EstimateTotals {
+    /// Build a labelled, formatted report of the totals
+    pub fn to_report(&self, locale: Locale) -> TotalsReport {
+        let labels = match locale {
+            Locale::Ru => [
+                "Прямые затраты",
+                "Оплата труда рабочих",
+                "Зарплата машинистов",
+                "Материалы",
+                "Эксплуатация машин",
+                "Накладные расходы",
+                "Сметная прибыль",
+                "Резерв на непредвиденные затраты",
+                "Лимитированные затраты",
+                "Итого без НДС",
+                "НДС",
+                "Всего с НДС",
+            ],
+            Locale::En => [
+                "Direct costs",
+                "Labor",
+                "Machine operators",
+                "Materials",
+                "Machine operation",
+                "Overhead",
+                "Profit",
+                "Contingency",
+                "Limited costs",
+                "Subtotal",
+                "VAT",
+                "Total",
+            ],
+        };
+
+        TotalsReport {
+            direct_costs: ReportLine::new(labels[0], self.direct_costs),
+            labor_costs: ReportLine::new(labels[1], self.labor_costs),
+            machine_operator_costs: ReportLine::new(labels[2], self.machine_operator_costs),
+            material_costs: ReportLine::new(labels[3], self.material_costs),
+            machine_costs: ReportLine::new(labels[4], self.machine_costs),
+            overhead: ReportLine::new(labels[5], self.overhead),
+            profit: ReportLine::new(labels[6], self.profit),
+            contingency: ReportLine::new(labels[7], self.contingency),
+            limited_costs: ReportLine::new(labels[8], self.limited_costs),
+            subtotal: ReportLine::new(labels[9], self.subtotal),
+            vat: ReportLine::new(labels[10], self.vat),
+            total: ReportLine::new(labels[11], self.total),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_rub_grouped() {
+        assert_eq!(format_rub_grouped(Decimal::new(123456789, 2)), "1\u{a0}234\u{a0}567,89\u{a0}₽");
+        assert_eq!(format_rub_grouped(Decimal::new(999, 0)), "999,00\u{a0}₽");
+        assert_eq!(format_rub_grouped(Decimal::new(-1000005, 3)), "-1\u{a0}000,01\u{a0}₽");
+        assert_eq!(format_rub_grouped(Decimal::new(125, 3)), "0,13\u{a0}₽");
+        assert_eq!(format_rub_grouped(Decimal::ZERO), "0,00\u{a0}₽");
+    }
+
+    #[test]
+    fn test_russian_report_labels() {
+        let totals = EstimateTotals {
+            overhead: Decimal::new(1234567, 1),
+            ..Default::default()
+        };
+
+        let report = totals.to_report(Locale::Ru);
+        assert_eq!(report.overhead.label, "Накладные расходы");
+        assert_eq!(report.overhead.value, Decimal::new(1234567, 1));
+        assert_eq!(report.overhead.formatted, "123\u{a0}456,70\u{a0}₽");
+
+        assert_eq!(report.limited_costs.label, "Лимитированные затраты");
+        assert_eq!(totals.to_report(Locale::En).overhead.label, "Overhead");
+    }
+}
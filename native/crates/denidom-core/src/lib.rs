@@ -7,12 +7,28 @@ pub mod estimate;
 pub mod calculator;
 pub mod normatives;
 pub mod coefficients;
+pub mod diff;
+pub mod format;
 pub mod units;
 pub mod error;
+pub mod report;
+pub mod limited;
+pub mod resources;
+pub mod summary;
+pub mod quantity;
+
+mod serde_decimal;
 
 pub use estimate::*;
 pub use calculator::*;
 pub use normatives::*;
 pub use coefficients::*;
+pub use diff::*;
+pub use format::*;
 pub use units::*;
 pub use error::*;
+pub use report::*;
+pub use limited::*;
+pub use resources::*;
+pub use summary::*;
+pub use quantity::*;
@@ -0,0 +1,211 @@
+//! Limited costs (лимитированные затраты)
+//!
+//! Temporary buildings, winter works and similar costs are charged as a
+//! percentage of the main works once those are calculated.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::estimate::{CalculationSettings, Estimate, EstimateTotals, TaxationRegime};
+
+/// База начисления лимитированных затрат
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LimitedBase {
+    /// Итого СМР без НДС
+    #[default]
+    Smr,
+    /// Прямые затраты
+    DirectCosts,
+    /// ФОТ (ОЗП + ЗПМ)
+    LaborFund,
+}
+
+impl LimitedBase {
+    /// Base amount taken from the calculated totals
+    pub fn amount(&self, totals: &EstimateTotals) -> Decimal {
+        match self {
+            LimitedBase::Smr => totals.subtotal,
+            LimitedBase::DirectCosts => totals.direct_costs,
+            LimitedBase::LaborFund => totals.labor_costs + totals.machine_operator_costs,
+        }
+    }
+}
+
+/// Лимитированные затраты
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LimitedCost {
+    /// Наименование (например, «Временные здания и сооружения»)
+    pub name: String,
+    /// Ставка (доля от базы, например 0.018)
+    pub rate: Decimal,
+    /// База начисления
+    #[serde(default)]
+    pub base: LimitedBase,
+}
+
+/// Строка расчёта лимитированных затрат
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LimitedCostLine {
+    /// Наименование
+    pub name: String,
+    /// Сумма базы
+    pub base_amount: Decimal,
+    /// Ставка
+    pub rate: Decimal,
+    /// Сумма затрат
+    pub amount: Decimal,
+}
+
+/// Итоги с начисленными лимитированными затратами
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LimitedCostsCalculation {
+    /// Итоги сметы; сумма строк учтена в `limited_costs`
+    pub totals: EstimateTotals,
+    /// Начисленные лимитированные затраты по строкам
+    pub lines: Vec<LimitedCostLine>,
+}
+
+impl Estimate {
+    /// Itemized limited costs, each computed from the main-works `totals`
+    pub fn limited_cost_lines(
+        &self,
+        totals: &EstimateTotals,
+        limited: &[LimitedCost],
+    ) -> Vec<LimitedCostLine> {
+        limited
+            .iter()
+            .map(|cost| {
+                let base_amount = cost.base.amount(totals);
+                LimitedCostLine {
+                    name: cost.name.clone(),
+                    base_amount,
+                    rate: cost.rate,
+                    amount: base_amount * cost.rate,
+                }
+            })
+            .collect()
+    }
+
+    /// Add limited costs to `limited_costs` and the subtotal and recompute
+    /// VAT and the total under `settings`, the settings `totals` were
+    /// calculated with. Rates are applied to the main-works totals, not to
+    /// each other. Returns the augmented totals with the itemized lines.
+    pub fn apply_limited_costs(
+        &self,
+        totals: &EstimateTotals,
+        limited: &[LimitedCost],
+        settings: &CalculationSettings,
+    ) -> LimitedCostsCalculation {
+        let lines = self.limited_cost_lines(totals, limited);
+        let extra: Decimal = lines.iter().map(|line| line.amount).sum();
+
+        let mut augmented = *totals;
+        augmented.limited_costs += extra;
+        augmented.subtotal += extra;
+        augmented.vat = match settings.taxation {
+            TaxationRegime::Osn => augmented.subtotal * settings.vat_rate,
+            TaxationRegime::Usn => Decimal::ZERO,
+        };
+        augmented.total = augmented.subtotal + augmented.vat;
+        LimitedCostsCalculation {
+            totals: augmented,
+            lines,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::calculate_estimate_totals;
+    use crate::estimate::{EstimateItem, EstimateSection, UnitCosts};
+    use crate::units::MeasureUnit;
+
+    #[test]
+    fn test_apply_two_limited_costs() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        let mut item = EstimateItem::new(
+            "ФЕР01-01-001-01".to_string(),
+            "Работа".to_string(),
+            MeasureUnit::CubicMeter,
+            Decimal::new(10, 0),
+        );
+        item.unit_costs = UnitCosts::new(
+            Decimal::new(1000, 0),
+            Decimal::new(400, 0),
+            Decimal::new(100, 0),
+            Decimal::new(500, 0),
+            Decimal::ZERO,
+        );
+        section.add_item(item);
+        estimate.sections.push(section);
+
+        let totals = calculate_estimate_totals(&estimate);
+        let limited = [
+            LimitedCost {
+                name: "Временные здания и сооружения".to_string(),
+                rate: Decimal::new(18, 3),
+                base: LimitedBase::Smr,
+            },
+            LimitedCost {
+                name: "Зимнее удорожание".to_string(),
+                rate: Decimal::new(5, 2),
+                base: LimitedBase::LaborFund,
+            },
+        ];
+
+        let lines = estimate.limited_cost_lines(&totals, &limited);
+        assert_eq!(lines[0].amount, totals.subtotal * Decimal::new(18, 3));
+        assert_eq!(lines[1].amount, Decimal::new(5000, 0) * Decimal::new(5, 2));
+
+        let settings = estimate.calculation_settings();
+        let applied = estimate.apply_limited_costs(&totals, &limited, &settings);
+        let augmented = applied.totals;
+        assert_eq!(applied.lines.len(), 2);
+        assert_eq!(applied.lines[1].amount, lines[1].amount);
+        assert_eq!(augmented.limited_costs, lines[0].amount + lines[1].amount);
+        assert_eq!(augmented.contingency, totals.contingency);
+        let expected_subtotal = totals.subtotal + lines[0].amount + lines[1].amount;
+        assert_eq!(augmented.subtotal, expected_subtotal);
+        assert_eq!(augmented.vat, expected_subtotal * Decimal::new(20, 2));
+        assert_eq!(augmented.total, augmented.subtotal + augmented.vat);
+        assert_eq!(augmented.direct_costs, totals.direct_costs);
+        assert!(augmented.is_consistent(Decimal::ZERO));
+    }
+
+    #[test]
+    fn test_limited_costs_under_usn() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        let mut item = EstimateItem::new(
+            "ФЕР01-01-001-01".to_string(),
+            "Работа".to_string(),
+            MeasureUnit::CubicMeter,
+            Decimal::new(10, 0),
+        );
+        item.unit_costs = UnitCosts::new(
+            Decimal::new(1000, 0),
+            Decimal::new(400, 0),
+            Decimal::new(100, 0),
+            Decimal::new(500, 0),
+            Decimal::ZERO,
+        );
+        section.add_item(item);
+        estimate.sections.push(section);
+        estimate.taxation = TaxationRegime::Usn;
+
+        let totals = calculate_estimate_totals(&estimate);
+        let limited = [LimitedCost {
+            name: "Временные здания и сооружения".to_string(),
+            rate: Decimal::new(18, 3),
+            base: LimitedBase::Smr,
+        }];
+        let settings = estimate.calculation_settings();
+        let augmented = estimate.apply_limited_costs(&totals, &limited, &settings).totals;
+
+        assert_eq!(augmented.vat, Decimal::ZERO);
+        assert_eq!(augmented.total, totals.subtotal * Decimal::new(1018, 3));
+        assert!(augmented.is_consistent(Decimal::ZERO));
+    }
+}
@@ -5,7 +5,8 @@
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::estimate::UnitCosts;
+use crate::calculator::calculate_totals;
+use crate::estimate::{CalculationSettings, EstimateItem, EstimateTotals, UnitCosts};
 use crate::units::MeasureUnit;
 
 /// Нормативная расценка (ФЕР/ГЭСН/ТЕР)
@@ -85,6 +86,45 @@ impl NormativeItem {
     pub fn is_ter(&self) -> bool {
         matches!(self.base_type, NormativeBase::TER)
     }
+
+    /// Compute unit costs from resource norms and their base prices
+    ///
+    /// Used for resource-model normatives (ФСНБ-2022) that come without
+    /// precomputed costs. Resources without a price contribute nothing.
+    pub fn price_from_resources(&self) -> UnitCosts {
+        let mut costs = UnitCosts::default();
+        for resource in &self.resources {
+            let cost = resource.consumption * resource.base_price.unwrap_or_default();
+            match resource.resource_type {
+                ResourceNormType::Labor => costs.labor += cost,
+                ResourceNormType::MachineOperator => costs.machine_operator += cost,
+                ResourceNormType::Machine => costs.machines += cost,
+                ResourceNormType::Material => costs.materials += cost,
+            }
+        }
+        costs.direct = costs.labor + costs.machine_operator + costs.materials + costs.machines;
+        costs
+    }
+
+    /// Build an estimate item for this normative at `quantity`
+    pub fn to_estimate_item(&self, quantity: Decimal) -> EstimateItem {
+        let mut item = EstimateItem::new(
+            self.code.clone(),
+            self.name.clone(),
+            self.unit.clone(),
+            quantity,
+        );
+        item.unit_costs = self.costs;
+        item.labor_hours = self.labor_norm;
+        item.machine_hours = self.machine_norm;
+        item
+    }
+
+    /// Full totals (overhead, profit, VAT) of this normative alone at `quantity`
+    pub fn preview(&self, quantity: Decimal, settings: &CalculationSettings) -> EstimateTotals {
+        let item = self.to_estimate_item(quantity);
+        calculate_totals(&[&item], settings)
+    }
 }
 
 /// Тип нормативной базы
@@ -156,6 +196,53 @@ impl std::fmt::Display for NormativeBase {
     }
 }
 
+/// Разобранный шифр расценки (например, "ФЕР01-01-001-01")
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NormativeCode {
+    /// Нормативная база
+    pub base: NormativeBase,
+    /// Префикс шифра в исходном написании ("ФЕР", "ФЕРм", "ГЭСН")
+    pub prefix: String,
+    /// Номер сборника ("01")
+    pub collection: String,
+    /// Остальные части шифра (отдел, раздел, таблица, позиция)
+    pub parts: Vec<String>,
+}
+
+impl NormativeCode {
+    /// Parse a normative code; `None` if there is no known base prefix
+    /// or no numeric collection number
+    pub fn parse(code: &str) -> Option<Self> {
+        let code = code.trim();
+        let base = NormativeBase::from_code_prefix(code)?;
+
+        let prefix_end = code
+            .char_indices()
+            .find(|(_, c)| !c.is_alphabetic())
+            .map(|(i, _)| i)
+            .unwrap_or(code.len());
+        let (prefix, rest) = code.split_at(prefix_end);
+
+        let mut parts = rest.trim_start_matches('-').split('-').map(str::to_string);
+        let collection = parts.next()?;
+        if collection.is_empty() || !collection.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        Some(Self {
+            base,
+            prefix: prefix.to_string(),
+            collection,
+            parts: parts.collect(),
+        })
+    }
+
+    /// Collection key such as "ФЕР01" or "ФЕРм08"
+    pub fn collection_key(&self) -> String {
+        format!("{}{}", self.prefix, self.collection)
+    }
+}
+
 /// Норма расхода ресурса
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceNorm {
@@ -293,6 +380,57 @@ pub fn fer_collections() -> Vec<NormativeCollection> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_price_from_resources() {
+        let mut item = NormativeItem::new(
+            "ГЭСН01-01-001-01".to_string(),
+            "Разработка грунта".to_string(),
+            NormativeBase::GESN,
+        );
+        let norm = |resource_type, consumption, base_price| ResourceNorm {
+            code: String::new(),
+            name: String::new(),
+            resource_type,
+            unit: MeasureUnit::Unit,
+            consumption: Decimal::new(consumption, 0),
+            base_price,
+        };
+        item.resources = vec![
+            norm(ResourceNormType::Labor, 10, Some(Decimal::new(500, 0))),
+            norm(ResourceNormType::Machine, 2, Some(Decimal::new(1500, 0))),
+            norm(ResourceNormType::Material, 3, None),
+        ];
+
+        let costs = item.price_from_resources();
+        assert_eq!(costs.labor, Decimal::new(5000, 0));
+        assert_eq!(costs.machines, Decimal::new(3000, 0));
+        assert_eq!(costs.materials, Decimal::ZERO);
+        assert_eq!(costs.direct, Decimal::new(8000, 0));
+    }
+
+    #[test]
+    fn test_preview_at_quantity() {
+        let mut item = NormativeItem::new(
+            "ФЕР01-01-001-01".to_string(),
+            "Разработка грунта".to_string(),
+            NormativeBase::FER,
+        );
+        item.costs = UnitCosts::new(
+            Decimal::new(5000, 0),
+            Decimal::new(1200, 0),
+            Decimal::new(300, 0),
+            Decimal::ZERO,
+            Decimal::new(3500, 0),
+        );
+
+        let settings = CalculationSettings::default();
+        let totals = item.preview(Decimal::new(100, 0), &settings);
+
+        assert_eq!(totals.direct_costs, Decimal::new(500_000, 0));
+        assert_eq!(totals.overhead, Decimal::new(150_000, 0) * settings.overhead_rate);
+        assert!(totals.total > totals.subtotal);
+    }
+
     #[test]
     fn test_normative_base_from_code() {
         assert_eq!(
@@ -320,4 +458,16 @@ mod tests {
         assert_eq!(item.collection(), Some("ФЕР15"));
         assert!(item.is_fer());
     }
+
+    #[test]
+    fn test_normative_code_parse() {
+        let code = NormativeCode::parse("ФЕРм08-02-142-01").unwrap();
+        assert_eq!(code.base, NormativeBase::FERm);
+        assert_eq!(code.collection, "08");
+        assert_eq!(code.collection_key(), "ФЕРм08");
+        assert_eq!(code.parts, vec!["02", "142", "01"]);
+
+        assert!(NormativeCode::parse("Прайс-лист").is_none());
+        assert!(NormativeCode::parse("ФЕР").is_none());
+    }
 }
@@ -0,0 +1,175 @@
+//! Quantities entered as arithmetic formulas
+//!
+//! Estimators often enter volumes as the measurement behind them, e.g.
+//! `12*3,5+2` (two walls plus an opening). The source formula is kept next to
+//! its value so a reviewer can check how the quantity was obtained.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::error::{DeniDomError, Result};
+
+/// Формула объёма работ
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuantityExpr {
+    /// Исходная формула
+    pub source: String,
+    /// Вычисленное значение
+    #[serde(with = "crate::serde_decimal")]
+    pub value: Decimal,
+}
+
+impl QuantityExpr {
+    /// Evaluate a formula with `+ - * /`, parentheses, unary minus and
+    /// decimal numbers (`.` or `,` as the separator)
+    ///
+    /// Fails with [`DeniDomError::Validation`] on malformed input, division
+    /// by zero or overflow.
+    pub fn eval(src: &str) -> Result<QuantityExpr> {
+        let mut parser = Parser {
+            src,
+            chars: src.char_indices().peekable(),
+        };
+        let value = parser.expr()?;
+        parser.skip_whitespace();
+        if let Some(&(pos, c)) = parser.chars.peek() {
+            return Err(parser.error(pos, &format!("unexpected '{}'", c)));
+        }
+
+        Ok(QuantityExpr {
+            source: src.trim().to_string(),
+            value,
+        })
+    }
+}
+
+/// Recursive descent over `expr = term (('+'|'-') term)*`,
+/// `term = factor (('*'|'/') factor)*`, `factor = '-' factor | number | '(' expr ')'`
+struct Parser<'a> {
+    src: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn expr(&mut self) -> Result<Decimal> {
+        let mut value = self.term()?;
+        while let Some(op) = self.next_op(&['+', '-']) {
+            let pos = self.position();
+            let rhs = self.term()?;
+            let result = if op == '+' { value.checked_add(rhs) } else { value.checked_sub(rhs) };
+            value = result.ok_or_else(|| self.error(pos, "overflow"))?;
+        }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<Decimal> {
+        let mut value = self.factor()?;
+        while let Some(op) = self.next_op(&['*', '/']) {
+            let pos = self.position();
+            let rhs = self.factor()?;
+            value = if op == '*' {
+                value.checked_mul(rhs).ok_or_else(|| self.error(pos, "overflow"))?
+            } else if rhs.is_zero() {
+                return Err(self.error(pos, "division by zero"));
+            } else {
+                value.checked_div(rhs).ok_or_else(|| self.error(pos, "overflow"))?
+            };
+        }
+        Ok(value)
+    }
+
+    fn factor(&mut self) -> Result<Decimal> {
+        self.skip_whitespace();
+        let pos = self.position();
+        match self.chars.peek().map(|&(_, c)| c) {
+            Some('-') => {
+                self.chars.next();
+                Ok(-self.factor()?)
+            }
+            Some('(') => {
+                self.chars.next();
+                let value = self.expr()?;
+                self.skip_whitespace();
+                match self.chars.next() {
+                    Some((_, ')')) => Ok(value),
+                    _ => Err(self.error(pos, "unclosed parenthesis")),
+                }
+            }
+            Some(c) if c.is_ascii_digit() => self.number(),
+            Some(c) => Err(self.error(pos, &format!("unexpected '{}'", c))),
+            None => Err(self.error(pos, "unexpected end of formula")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Decimal> {
+        let pos = self.position();
+        let mut text = String::new();
+        while let Some(&(_, c)) = self.chars.peek() {
+            match c {
+                '0'..='9' => text.push(c),
+                '.' | ',' => text.push('.'),
+                _ => break,
+            }
+            self.chars.next();
+        }
+        Decimal::from_str(&text).map_err(|_| self.error(pos, &format!("invalid number '{}'", text)))
+    }
+
+    /// Consume the next operator if it is one of `ops`
+    fn next_op(&mut self, ops: &[char]) -> Option<char> {
+        self.skip_whitespace();
+        let &(_, c) = self.chars.peek()?;
+        if ops.contains(&c) {
+            self.chars.next();
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|&(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    fn position(&mut self) -> usize {
+        self.chars.peek().map_or(self.src.len(), |&(pos, _)| pos)
+    }
+
+    fn error(&self, pos: usize, message: &str) -> DeniDomError {
+        DeniDomError::Validation(format!(
+            "quantity formula '{}': {} at position {}",
+            self.src,
+            message,
+            pos + 1
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eval_formula() {
+        let expr = QuantityExpr::eval("12*3,5+2").unwrap();
+        assert_eq!(expr.value, Decimal::new(44, 0));
+        assert_eq!(expr.source, "12*3,5+2");
+
+        assert_eq!(QuantityExpr::eval(" (10 - 2.5) / 3 * -2 ").unwrap().value, Decimal::from_str("-5").unwrap());
+        assert_eq!(QuantityExpr::eval("2-3-4").unwrap().value, Decimal::new(-5, 0));
+    }
+
+    #[test]
+    fn test_eval_rejects_invalid() {
+        for src in ["", "12*", "(1+2", "1+2)", "3 x 4", "1..5", "5/(2-2)"] {
+            assert!(
+                matches!(QuantityExpr::eval(src), Err(DeniDomError::Validation(_))),
+                "{:?}",
+                src
+            );
+        }
+        let err = QuantityExpr::eval("5/(2-2)").unwrap_err().to_string();
+        assert!(err.contains("division by zero"), "{}", err);
+    }
+}
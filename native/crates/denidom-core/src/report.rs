@@ -0,0 +1,173 @@
+//! Calculation report with non-fatal warnings
+//!
+//! Advisories that should not stop a calculation (inconsistent unit costs,
+//! an index left at 1.0, empty quantities) are collected alongside totals.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::calculator::calculate_estimate_totals;
+use crate::estimate::{Estimate, EstimateTotals};
+
+/// Вид предупреждения расчёта
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CalculationWarningKind {
+    /// Прямые затраты не равны сумме составляющих
+    UnitCostsMismatch,
+    /// Индекс пересчёта равен 1.0 (возможно, не задан)
+    DefaultIndex,
+    /// Нулевое количество
+    ZeroQuantity,
+    /// В смете смешаны разные нормативные базы
+    MixedNormativeBases,
+}
+
+/// Предупреждение расчёта
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalculationWarning {
+    /// Вид предупреждения
+    pub kind: CalculationWarningKind,
+    /// Позиция сметы (`None` для предупреждений уровня сметы)
+    pub item_id: Option<Uuid>,
+    /// Поле, к которому относится предупреждение
+    pub field: String,
+    /// Текст предупреждения
+    pub message: String,
+}
+
+/// Итоги расчёта с предупреждениями
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalculationReport {
+    /// Итоги сметы
+    pub totals: EstimateTotals,
+    /// Предупреждения
+    pub warnings: Vec<CalculationWarning>,
+}
+
+impl CalculationReport {
+    /// Check if the calculation produced no warnings
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+/// Calculate estimate totals and collect non-fatal warnings
+pub fn calculate_estimate_report(estimate: &Estimate) -> CalculationReport {
+    let mut warnings = Vec::new();
+
+    if estimate.coefficients.index == Decimal::ONE {
+        warnings.push(CalculationWarning {
+            kind: CalculationWarningKind::DefaultIndex,
+            item_id: None,
+            field: "coefficients.index".to_string(),
+            message: "Индекс пересчёта равен 1.0 — смета в базисных ценах".to_string(),
+        });
+    }
+
+    let bases = estimate.base_types_used();
+    if bases.len() > 1 {
+        let mut names: Vec<&str> = bases.iter().map(|base| base.display_name()).collect();
+        names.sort_unstable();
+        warnings.push(CalculationWarning {
+            kind: CalculationWarningKind::MixedNormativeBases,
+            item_id: None,
+            field: "code".to_string(),
+            message: format!("В смете смешаны нормативные базы: {}", names.join(", ")),
+        });
+    }
+
+    for item in estimate.all_items() {
+        if !item.unit_costs.validate() {
+            warnings.push(CalculationWarning {
+                kind: CalculationWarningKind::UnitCostsMismatch,
+                item_id: Some(item.id),
+                field: "unit_costs.direct".to_string(),
+                message: format!(
+                    "{}: прямые затраты {} не равны сумме составляющих",
+                    item.code, item.unit_costs.direct
+                ),
+            });
+        }
+        if item.quantity.is_zero() {
+            warnings.push(CalculationWarning {
+                kind: CalculationWarningKind::ZeroQuantity,
+                item_id: Some(item.id),
+                field: "quantity".to_string(),
+                message: format!("{}: нулевое количество", item.code),
+            });
+        }
+    }
+
+    CalculationReport {
+        totals: calculate_estimate_totals(estimate),
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::estimate::{EstimateItem, EstimateSection, UnitCosts};
+    use crate::units::MeasureUnit;
+
+    #[test]
+    fn test_invalid_unit_costs_warning() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        estimate.coefficients.index = Decimal::new(850, 2);
+
+        let mut section = EstimateSection::new(1, "Работы".to_string());
+        let mut valid = EstimateItem::new(
+            "ФЕР01-01-001-01".to_string(),
+            "Разработка грунта".to_string(),
+            MeasureUnit::CubicMeter,
+            Decimal::new(10, 0),
+        );
+        valid.unit_costs = UnitCosts::new(
+            Decimal::new(500, 0),
+            Decimal::new(200, 0),
+            Decimal::ZERO,
+            Decimal::new(300, 0),
+            Decimal::ZERO,
+        );
+        let mut invalid = valid.clone();
+        invalid.id = Uuid::new_v4();
+        invalid.unit_costs.direct = Decimal::new(600, 0);
+
+        section.add_item(valid);
+        section.add_item(invalid.clone());
+        estimate.sections.push(section);
+
+        let report = calculate_estimate_report(&estimate);
+
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].kind, CalculationWarningKind::UnitCostsMismatch);
+        assert_eq!(report.warnings[0].item_id, Some(invalid.id));
+        assert_eq!(report.totals.direct_costs, Decimal::new(93500, 0)); // 1100 * 10 * 8.5
+    }
+
+    #[test]
+    fn test_mixed_bases_warning() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        estimate.coefficients.index = Decimal::new(850, 2);
+
+        let mut section = EstimateSection::new(1, "Работы".to_string());
+        for code in ["ФЕР01-01-001-01", "ГЭСН01-01-001-01"] {
+            let mut item = EstimateItem::new(
+                code.to_string(),
+                "Разработка грунта".to_string(),
+                MeasureUnit::CubicMeter,
+                Decimal::ONE,
+            );
+            item.unit_costs = UnitCosts::new(Decimal::ONE, Decimal::ONE, Decimal::ZERO, Decimal::ZERO, Decimal::ZERO);
+            section.add_item(item);
+        }
+        estimate.sections.push(section);
+
+        let report = calculate_estimate_report(&estimate);
+
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].kind, CalculationWarningKind::MixedNormativeBases);
+        assert!(report.warnings[0].message.contains("ГЭСН, ФЕР"));
+    }
+}
@@ -0,0 +1,449 @@
+//! Resource statement (ведомость ресурсов) and resource-method totals
+//!
+//! Aggregates item resources across an estimate so each material, machine
+//! or labor resource appears once with its total quantity and cost, and
+//! prices an estimate by the resource-index method (РИМ) from a price table.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::apply_markups;
+use crate::error::{DeniDomError, Result};
+use crate::estimate::{Estimate, EstimateItem, EstimateTotals, ResourceType};
+use crate::units::MeasureUnit;
+
+/// Строка ведомости ресурсов
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceSummary {
+    /// Код ресурса (нормализованный)
+    pub code: String,
+    /// Наименование
+    pub name: String,
+    /// Тип ресурса
+    pub resource_type: ResourceType,
+    /// Единица измерения
+    pub unit: MeasureUnit,
+    /// Общее количество
+    pub quantity: Decimal,
+    /// Общая стоимость в рублях
+    pub cost: Decimal,
+}
+
+/// Canonical form of a resource code: without whitespace, uppercased, with
+/// all dash variants (hyphen, non-breaking hyphen, en/em dash, minus) as `-`
+pub fn normalize_resource_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2212}' => '-',
+            c => c,
+        })
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Aggregate resources of all items, merging rows with equal normalized codes
+///
+/// Quantities follow the priced volume: the item quantity times the item and
+/// section coefficients. Rows keep the order of first appearance; name and
+/// unit come from the first row.
+pub fn aggregate_resources(estimate: &Estimate) -> Vec<ResourceSummary> {
+    let aggregator = ResourceAggregator::from_estimate(estimate);
+    let mut entries: Vec<&AggregateEntry> = aggregator.entries.values().collect();
+    entries.sort_by_key(|entry| entry.first_seen);
+    entries.into_iter().map(|entry| entry.summary.clone()).collect()
+}
+
+/// Строка ведомости с числом ресурсов позиций, из которых она собрана
+#[derive(Debug, Clone)]
+struct AggregateEntry {
+    summary: ResourceSummary,
+    sources: usize,
+    /// Порядковый номер первого появления ресурса
+    first_seen: usize,
+}
+
+/// Incrementally maintained resource statement
+///
+/// Editing one item updates the aggregate in O(resources of the item)
+/// instead of re-running [`aggregate_resources`] over the whole estimate;
+/// [`aggregate_resources`] itself is built on it. Callers keep it in step
+/// with their edits through `add_item` / `remove_item`.
+#[derive(Debug, Clone, Default)]
+pub struct ResourceAggregator {
+    entries: HashMap<String, AggregateEntry>,
+    next_seen: usize,
+}
+
+impl ResourceAggregator {
+    /// Create an empty aggregator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Aggregate all items of an estimate
+    pub fn from_estimate(estimate: &Estimate) -> Self {
+        let mut aggregator = Self::new();
+        for (item, factor) in estimate.lines() {
+            aggregator.add_item(item, factor);
+        }
+        aggregator
+    }
+
+    /// Add an item's resources to the aggregate; `section_coefficient` is
+    /// the coefficient of the section holding the item
+    pub fn add_item(&mut self, item: &EstimateItem, section_coefficient: Decimal) {
+        let volume = item.effective_quantity() * section_coefficient;
+        for resource in &item.resources {
+            let code = normalize_resource_code(&resource.code);
+            let quantity = volume * resource.consumption_rate;
+            let cost = volume * resource.cost();
+
+            let first_seen = self.next_seen;
+            let entry = self.entries.entry(code.clone()).or_insert_with(|| AggregateEntry {
+                summary: ResourceSummary {
+                    code,
+                    name: resource.name.clone(),
+                    resource_type: resource.resource_type,
+                    unit: resource.unit.clone(),
+                    quantity: Decimal::ZERO,
+                    cost: Decimal::ZERO,
+                },
+                sources: 0,
+                first_seen,
+            });
+            entry.summary.quantity += quantity;
+            entry.summary.cost += cost;
+            entry.sources += 1;
+            self.next_seen += 1;
+        }
+    }
+
+    /// Remove an item previously passed to [`ResourceAggregator::add_item`]
+    /// with the same section coefficient.
+    /// Rows left without contributing resources are dropped.
+    pub fn remove_item(&mut self, item: &EstimateItem, section_coefficient: Decimal) {
+        let volume = item.effective_quantity() * section_coefficient;
+        for resource in &item.resources {
+            let code = normalize_resource_code(&resource.code);
+            let Some(entry) = self.entries.get_mut(&code) else {
+                continue;
+            };
+
+            entry.sources = entry.sources.saturating_sub(1);
+            if entry.sources == 0 {
+                self.entries.remove(&code);
+                continue;
+            }
+            entry.summary.quantity -= volume * resource.consumption_rate;
+            entry.summary.cost -= volume * resource.cost();
+        }
+    }
+
+    /// Current rows, ordered by code
+    pub fn rows(&self) -> Vec<ResourceSummary> {
+        let mut rows: Vec<ResourceSummary> =
+            self.entries.values().map(|entry| entry.summary.clone()).collect();
+        rows.sort_by(|a, b| a.code.cmp(&b.code));
+        rows
+    }
+
+    /// Number of distinct resources
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if no resources are aggregated
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Текущие цены ресурсов в рублях по нормализованному коду
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourcePriceTable {
+    prices: HashMap<String, Decimal>,
+}
+
+impl ResourcePriceTable {
+    /// Create an empty price table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the RUB price of a resource
+    pub fn insert(&mut self, code: &str, price: Decimal) {
+        self.prices.insert(normalize_resource_code(code), price);
+    }
+
+    /// RUB price of a resource, if known
+    pub fn get(&self, code: &str) -> Option<Decimal> {
+        self.prices.get(&normalize_resource_code(code)).copied()
+    }
+
+    /// Number of priced resources
+    pub fn len(&self) -> usize {
+        self.prices.len()
+    }
+
+    /// Check if the table has no prices
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+}
+
+impl Estimate {
+    /// Resource codes (normalized) that appear with more than one distinct
+    /// unit price in RUB, with those prices in ascending order. Codes are
+    /// sorted; an empty result means imported prices are consistent.
+    pub fn resource_price_conflicts(&self) -> Vec<(String, Vec<Decimal>)> {
+        let mut prices: BTreeMap<String, BTreeSet<Decimal>> = BTreeMap::new();
+        for item in self.all_items() {
+            for resource in &item.resources {
+                prices
+                    .entry(normalize_resource_code(&resource.code))
+                    .or_default()
+                    .insert(resource.unit_price_rub());
+            }
+        }
+
+        prices
+            .into_iter()
+            .filter(|(_, prices)| prices.len() > 1)
+            .map(|(code, prices)| (code, prices.into_iter().collect()))
+            .collect()
+    }
+
+    /// Totals by the resource method (РИМ): every item resource is priced
+    /// from `prices` at `quantity × consumption_rate`, the sums form labor,
+    /// machine and material (including equipment) costs, and overhead,
+    /// profit and VAT are applied on top. Prices are current, so the
+    /// estimate index is not applied.
+    ///
+    /// Fails with [`DeniDomError::Calculation`] listing every resource code
+    /// missing from `prices`.
+    pub fn calculate_resource_method(&self, prices: &ResourcePriceTable) -> Result<EstimateTotals> {
+        let mut totals = EstimateTotals::default();
+        let mut unpriced = BTreeSet::new();
+
+        for (item, factor) in self.lines() {
+            for resource in &item.resources {
+                let Some(price) = prices.get(&resource.code) else {
+                    unpriced.insert(normalize_resource_code(&resource.code));
+                    continue;
+                };
+                let cost = item.effective_quantity() * factor * resource.consumption_rate * price;
+                match resource.resource_type {
+                    ResourceType::Labor => totals.labor_costs += cost,
+                    ResourceType::Machine => totals.machine_costs += cost,
+                    ResourceType::Material | ResourceType::Equipment => {
+                        totals.material_costs += cost
+                    }
+                }
+            }
+        }
+
+        if !unpriced.is_empty() {
+            let codes: Vec<String> = unpriced.into_iter().collect();
+            return Err(DeniDomError::Calculation(format!(
+                "unpriced resources: {}",
+                codes.join(", ")
+            )));
+        }
+
+        totals.direct_costs = totals.labor_costs + totals.material_costs + totals.machine_costs;
+        apply_markups(&mut totals, &self.calculation_settings());
+        Ok(totals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::estimate::{Currency, EstimateItem, EstimateSection, Resource};
+    use uuid::Uuid;
+
+    fn item_with_resource(code: &str, quantity: i64) -> EstimateItem {
+        let mut item = EstimateItem::new(
+            "ФЕР08-02-001-01".to_string(),
+            "Кладка".to_string(),
+            MeasureUnit::CubicMeter,
+            Decimal::new(quantity, 0),
+        );
+        item.resources.push(Resource {
+            id: Uuid::new_v4(),
+            resource_type: ResourceType::Material,
+            code: code.to_string(),
+            name: "Раствор".to_string(),
+            unit: MeasureUnit::CubicMeter,
+            consumption_rate: Decimal::new(25, 2),
+            unit_price: Decimal::new(4000, 0),
+            currency: Currency::Rub,
+            fx_rate: Decimal::ONE,
+        });
+        item
+    }
+
+    fn resource(resource_type: ResourceType, code: &str, consumption_rate: Decimal) -> Resource {
+        Resource {
+            id: Uuid::new_v4(),
+            resource_type,
+            code: code.to_string(),
+            name: String::new(),
+            unit: MeasureUnit::Unit,
+            consumption_rate,
+            unit_price: Decimal::ZERO,
+            currency: Currency::Rub,
+            fx_rate: Decimal::ONE,
+        }
+    }
+
+    fn resource_estimate() -> Estimate {
+        let mut item = EstimateItem::new(
+            "ГЭСН08-02-001-01".to_string(),
+            "Кладка".to_string(),
+            MeasureUnit::CubicMeter,
+            Decimal::new(10, 0),
+        );
+        item.resources = vec![
+            resource(ResourceType::Labor, "1-100-30", Decimal::new(5, 0)),
+            resource(ResourceType::Machine, "91.05.05-015", Decimal::new(1, 1)),
+            resource(ResourceType::Material, "04.3.01.09-0014", Decimal::new(25, 2)),
+        ];
+
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        section.add_item(item);
+        estimate.sections.push(section);
+        estimate
+    }
+
+    #[test]
+    fn test_resource_method_totals() {
+        let estimate = resource_estimate();
+        let mut prices = ResourcePriceTable::new();
+        prices.insert("1-100-30", Decimal::new(600, 0));
+        prices.insert("91.05.05-015", Decimal::new(2000, 0));
+        prices.insert("04.3.01.09-0014", Decimal::new(6000, 0));
+
+        let totals = estimate.calculate_resource_method(&prices).unwrap();
+
+        // ОЗП 10 × 5 × 600 = 30 000; ЭМ 10 × 0.1 × 2000 = 2 000; М 10 × 0.25 × 6000 = 15 000
+        assert_eq!(totals.labor_costs, Decimal::new(30_000, 0));
+        assert_eq!(totals.machine_costs, Decimal::new(2_000, 0));
+        assert_eq!(totals.material_costs, Decimal::new(15_000, 0));
+        assert_eq!(totals.direct_costs, Decimal::new(47_000, 0));
+        // НР 12% и СП 8% от ФОТ 30 000, НДС 20%
+        assert_eq!(totals.subtotal, Decimal::new(53_000, 0));
+        assert_eq!(totals.total, Decimal::new(63_600, 0));
+    }
+
+    #[test]
+    fn test_resource_method_reports_unpriced_codes() {
+        let estimate = resource_estimate();
+        let mut prices = ResourcePriceTable::new();
+        prices.insert("1-100-30", Decimal::new(600, 0));
+
+        let err = estimate.calculate_resource_method(&prices).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("04.3.01.09-0014"));
+        assert!(message.contains("91.05.05-015"));
+    }
+
+    #[test]
+    fn test_resource_price_conflicts() {
+        let mut cheaper = item_with_resource("04.3.01.09-0014", 2);
+        cheaper.resources[0].unit_price = Decimal::new(3800, 0);
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        section.add_item(item_with_resource("04.3.01.09-0014", 10));
+        section.add_item(item_with_resource("04.3.01.09-0014 ", 5));
+        section.add_item(cheaper);
+        estimate.sections.push(section);
+
+        let conflicts = estimate.resource_price_conflicts();
+        assert_eq!(
+            conflicts,
+            vec![(
+                "04.3.01.09-0014".to_string(),
+                vec![Decimal::new(3800, 0), Decimal::new(4000, 0)]
+            )]
+        );
+
+        estimate.sections[0].items.pop();
+        assert!(estimate.resource_price_conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_normalize_resource_code() {
+        assert_eq!(normalize_resource_code(" 101-0782 "), "101-0782");
+        assert_eq!(normalize_resource_code("101\u{2011}0782"), "101-0782");
+        assert_eq!(normalize_resource_code("91.01.01–035"), "91.01.01-035");
+        assert_eq!(normalize_resource_code("с101-0782"), "С101-0782");
+    }
+
+    #[test]
+    fn test_aggregate_merges_equal_codes() {
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        section.add_item(item_with_resource("101-0782", 10));
+        section.add_item(item_with_resource(" 101\u{2011}0782", 2));
+        estimate.sections.push(section);
+
+        let rows = aggregate_resources(&estimate);
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].code, "101-0782");
+        assert_eq!(rows[0].quantity, Decimal::new(3, 0));
+        assert_eq!(rows[0].cost, Decimal::new(12000, 0));
+    }
+
+    #[test]
+    fn test_resource_statement_follows_coefficients() {
+        let mut item = item_with_resource("101-0782", 10);
+        item.coefficient = Some(Decimal::new(12, 1));
+        item.unit_costs.direct = item.resources[0].cost();
+        item.unit_costs.materials = item.unit_costs.direct;
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        section.add_item(item);
+        estimate.sections.push(section);
+
+        let rows = aggregate_resources(&estimate);
+        // 10 × 1.2 × 0.25
+        assert_eq!(rows[0].quantity, Decimal::new(3, 0));
+        let item = &estimate.sections[0].items[0];
+        assert_eq!(rows[0].cost, item.total_resources_cost());
+        assert_eq!(item.total_resources_cost(), item.total_material_cost());
+    }
+
+    #[test]
+    fn test_aggregator_add_and_remove() {
+        let first = item_with_resource("101-0782", 10);
+        let mut second = item_with_resource("101-0782", 2);
+        second.resources.push(resource(ResourceType::Labor, "1-100-30", Decimal::new(5, 0)));
+
+        let factor = Decimal::new(115, 2);
+        let mut aggregator = ResourceAggregator::new();
+        aggregator.add_item(&first, factor);
+        aggregator.add_item(&second, factor);
+        assert_eq!(aggregator.len(), 2);
+
+        aggregator.remove_item(&second, factor);
+
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        section.coefficient = Some(factor);
+        section.add_item(first);
+        estimate.sections.push(section);
+        let fresh = aggregate_resources(&estimate);
+
+        let rows = aggregator.rows();
+        assert_eq!(rows.len(), fresh.len());
+        assert_eq!(rows[0].code, fresh[0].code);
+        assert_eq!(rows[0].quantity, fresh[0].quantity);
+        assert_eq!(rows[0].cost, fresh[0].cost);
+    }
+}
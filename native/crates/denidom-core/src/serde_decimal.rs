@@ -0,0 +1,46 @@
+//! Format-aware `Decimal` deserialization
+//!
+//! `rust_decimal` deserializes via `deserialize_any`, which non-self-describing
+//! formats such as bincode do not support. Fields stored in binary form use
+//! `#[serde(with = "crate::serde_decimal")]`: human-readable formats (JSON)
+//! keep accepting both strings and numbers, binary formats read the string
+//! that `Decimal` serializes to.
+
+use rust_decimal::Decimal;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+pub(crate) fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+    Serialize::serialize(value, serializer)
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+    if deserializer.is_human_readable() {
+        <Decimal as Deserialize>::deserialize(deserializer)
+    } else {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+/// The same for `Option<Decimal>` fields
+pub(crate) mod option {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &Option<Decimal>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Decimal>, D::Error> {
+        if deserializer.is_human_readable() {
+            Option::<Decimal>::deserialize(deserializer)
+        } else {
+            Option::<String>::deserialize(deserializer)?
+                .map(|value| value.parse().map_err(de::Error::custom))
+                .transpose()
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Summary estimate (сводный сметный расчёт, ССР)
+//!
+//! A project groups object estimates into numbered chapters; the summary
+//! adds up the grand totals of every member estimate.
+
+use serde::{Deserialize, Serialize};
+
+use crate::calculator::calculate_estimate_totals;
+use crate::estimate::{Estimate, EstimateTotals};
+
+/// Глава сводного сметного расчёта
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryChapter {
+    /// Номер главы
+    pub number: u32,
+    /// Наименование главы
+    pub name: String,
+    /// Объектные сметы главы
+    pub estimates: Vec<Estimate>,
+}
+
+/// Сводный сметный расчёт
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SummaryEstimate {
+    /// Главы в порядке номеров
+    pub chapters: Vec<SummaryChapter>,
+}
+
+/// Итоги главы
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChapterTotals {
+    /// Номер главы
+    pub number: u32,
+    /// Наименование главы
+    pub name: String,
+    /// Итоги по главе
+    pub totals: EstimateTotals,
+}
+
+/// Итоги сводного сметного расчёта
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryTotals {
+    /// Итоги по главам
+    pub chapters: Vec<ChapterTotals>,
+    /// Итого по проекту
+    pub total: EstimateTotals,
+}
+
+impl SummaryEstimate {
+    /// Create an empty summary
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an estimate to chapter `number`, creating the chapter if needed.
+    /// The name of an existing chapter is kept.
+    pub fn add_chapter(&mut self, number: u32, name: impl Into<String>, estimate: Estimate) {
+        match self.chapters.binary_search_by_key(&number, |chapter| chapter.number) {
+            Ok(index) => self.chapters[index].estimates.push(estimate),
+            Err(index) => self.chapters.insert(
+                index,
+                SummaryChapter {
+                    number,
+                    name: name.into(),
+                    estimates: vec![estimate],
+                },
+            ),
+        }
+    }
+
+    /// Per-chapter subtotals and the project-wide total
+    pub fn totals(&self) -> SummaryTotals {
+        let chapters: Vec<ChapterTotals> = self
+            .chapters
+            .iter()
+            .map(|chapter| ChapterTotals {
+                number: chapter.number,
+                name: chapter.name.clone(),
+                totals: chapter
+                    .estimates
+                    .iter()
+                    .map(calculate_estimate_totals)
+                    .fold(EstimateTotals::default(), |acc, totals| acc + totals),
+            })
+            .collect();
+
+        let total = chapters
+            .iter()
+            .fold(EstimateTotals::default(), |acc, chapter| acc + chapter.totals);
+
+        SummaryTotals { chapters, total }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::estimate::{EstimateItem, EstimateSection, UnitCosts};
+    use crate::units::MeasureUnit;
+    use rust_decimal::Decimal;
+
+    fn object_estimate(name: &str, direct: i64) -> Estimate {
+        let mut estimate = Estimate::new(name.to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        let mut item = EstimateItem::new(
+            "ФЕР01-01-001-01".to_string(),
+            "Работа".to_string(),
+            MeasureUnit::CubicMeter,
+            Decimal::new(10, 0),
+        );
+        item.unit_costs = UnitCosts::new(
+            Decimal::new(direct, 0),
+            Decimal::new(direct, 0),
+            Decimal::ZERO,
+            Decimal::ZERO,
+            Decimal::ZERO,
+        );
+        section.add_item(item);
+        estimate.sections.push(section);
+        estimate
+    }
+
+    #[test]
+    fn test_project_total_is_sum_of_members() {
+        let first = object_estimate("Жилой дом", 1000);
+        let second = object_estimate("Наружные сети", 250);
+        let expected =
+            calculate_estimate_totals(&first).total + calculate_estimate_totals(&second).total;
+
+        let mut summary = SummaryEstimate::new();
+        summary.add_chapter(6, "Наружные сети", second);
+        summary.add_chapter(2, "Основные объекты строительства", first);
+
+        let totals = summary.totals();
+        assert_eq!(totals.total.total, expected);
+        assert_eq!(totals.chapters.len(), 2);
+        assert_eq!(totals.chapters[0].number, 2);
+        assert_eq!(
+            totals.chapters[0].totals.total + totals.chapters[1].totals.total,
+            totals.total.total
+        );
+    }
+}
@@ -2,11 +2,29 @@
 //!
 //! Standard units of measurement used in Russian construction norms.
 
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::error::DeniDomError;
+
+/// Физическая величина единицы измерения
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    /// Длина
+    Length,
+    /// Площадь
+    Area,
+    /// Объём
+    Volume,
+    /// Масса
+    Mass,
+    /// Количество штук
+    Count,
+}
+
 /// Единица измерения
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MeasureUnit {
     // Линейные
     /// Метр (м)
@@ -48,6 +66,7 @@ pub enum MeasureUnit {
 
     // Штучные
     /// Штука (шт)
+    #[default]
     Unit,
     /// 10 штук (10 шт)
     Unit10,
@@ -229,11 +248,70 @@ impl MeasureUnit {
     pub fn is_time(&self) -> bool {
         matches!(self, Self::ManHour | Self::MachineHour | Self::Shift)
     }
-}
 
-impl Default for MeasureUnit {
-    fn default() -> Self {
-        Self::Unit
+    /// Check if this unit counts discrete pieces or sets
+    pub fn is_count(&self) -> bool {
+        matches!(
+            self,
+            Self::Unit | Self::Unit10 | Self::Unit100 | Self::Unit1000 | Self::Set
+        )
+    }
+
+    /// Dimension and size in the base unit (м, м², м³, кг, шт); `None` for
+    /// units that only convert to themselves
+    fn dimension(&self) -> Option<(Dimension, f64)> {
+        let dimension = match self {
+            Self::Meter | Self::LinearMeter | Self::LinearMeter100 => (Dimension::Length, self.factor()),
+            Self::Kilometer => (Dimension::Length, 1000.0),
+            Self::Hectare => (Dimension::Area, 10_000.0),
+            Self::Liter => (Dimension::Volume, 0.001),
+            Self::Ton => (Dimension::Mass, 1000.0),
+            Self::Kilogram | Self::Kilogram100 => (Dimension::Mass, self.factor()),
+            Self::Set => return None,
+            _ if self.is_area() => (Dimension::Area, self.factor()),
+            _ if self.is_volume() => (Dimension::Volume, self.factor()),
+            _ if self.is_count() => (Dimension::Count, self.factor()),
+            _ => return None,
+        };
+        Some(dimension)
+    }
+
+    /// Convert `quantity` expressed in this unit into `to`, e.g. 250 м² into
+    /// 2.5 "100 м²". Only units of the same dimension convert; `Other` units
+    /// never do.
+    pub fn convert(&self, quantity: f64, to: &MeasureUnit) -> Result<f64, DeniDomError> {
+        let error = || DeniDomError::UnitConversion {
+            from: self.abbreviation().to_string(),
+            to: to.abbreviation().to_string(),
+        };
+        if matches!(self, Self::Other(_)) || matches!(to, Self::Other(_)) {
+            return Err(error());
+        }
+        if self == to {
+            return Ok(quantity);
+        }
+
+        match (self.dimension(), to.dimension()) {
+            (Some((from_dim, from_size)), Some((to_dim, to_size))) if from_dim == to_dim => {
+                Ok(quantity * from_size / to_size)
+            }
+            _ => Err(error()),
+        }
+    }
+
+    /// Round a quantity up to whole pieces for count units; continuous
+    /// units are returned unchanged. For "100 шт" 0.325 becomes 0.33.
+    pub fn round_quantity(&self, q: Decimal) -> Decimal {
+        if !self.is_count() {
+            return q;
+        }
+        let factor = match self {
+            Self::Unit10 => Decimal::TEN,
+            Self::Unit100 => Decimal::ONE_HUNDRED,
+            Self::Unit1000 => Decimal::ONE_THOUSAND,
+            _ => Decimal::ONE,
+        };
+        ((q * factor).ceil() / factor).normalize()
     }
 }
 
@@ -273,4 +351,29 @@ mod tests {
         assert_eq!(MeasureUnit::SquareMeter100.factor(), 100.0);
         assert_eq!(MeasureUnit::SquareMeter.factor(), 1.0);
     }
+
+    #[test]
+    fn test_convert() {
+        let m2 = MeasureUnit::SquareMeter;
+        let m2_100 = MeasureUnit::SquareMeter100;
+        assert_eq!(m2.convert(250.0, &m2_100).unwrap(), 2.5);
+        assert_eq!(m2_100.convert(2.5, &m2).unwrap(), 250.0);
+        assert_eq!(MeasureUnit::Hectare.convert(1.0, &m2_100).unwrap(), 100.0);
+        assert_eq!(m2.convert(7.0, &m2).unwrap(), 7.0);
+
+        assert!(matches!(
+            MeasureUnit::CubicMeter.convert(1.0, &MeasureUnit::Kilogram),
+            Err(DeniDomError::UnitConversion { .. })
+        ));
+        let other = MeasureUnit::Other("рулон".to_string());
+        assert!(other.convert(1.0, &other).is_err());
+    }
+
+    #[test]
+    fn test_round_quantity() {
+        assert_eq!(MeasureUnit::Set.round_quantity(Decimal::new(21, 1)), Decimal::new(3, 0));
+        assert_eq!(MeasureUnit::Unit100.round_quantity(Decimal::new(325, 3)), Decimal::new(33, 2));
+        assert_eq!(MeasureUnit::Unit.round_quantity(Decimal::new(5, 0)), Decimal::new(5, 0));
+        assert_eq!(MeasureUnit::Ton.round_quantity(Decimal::new(1234, 3)), Decimal::new(1234, 3));
+    }
 }
@@ -0,0 +1,34 @@
+//! Database error types
+
+use thiserror::Error;
+
+/// Error type for database operations
+#[derive(Error, Debug)]
+pub enum DbError {
+    /// SQLite error
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    /// Connection pool error
+    #[error("Connection pool error: {0}")]
+    Pool(#[from] r2d2::Error),
+
+    /// Stored data could not be (de)serialized
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// Record does not exist
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    /// Operation rejected by domain rules
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    /// Estimate could not be encoded or decoded
+    #[error("Encoding error: {0}")]
+    Encoding(#[from] denidom_core::DeniDomError),
+}
+
+/// Result type alias for database operations
+pub type DbResult<T> = std::result::Result<T, DbError>;
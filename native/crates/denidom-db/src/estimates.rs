@@ -0,0 +1,261 @@
+//! Estimate persistence
+//!
+//! Estimates are stored as JSON in `estimates.data`, or as bincode in
+//! `estimates.data_bin` (with empty `data`); the indexed columns
+//! (number, name, object, status) are kept in sync for listing and search.
+//!
+//! Every save, status change and deletion appends a row to `estimate_audit`
+//! with the caller-supplied actor; call these inside a transaction so the
+//! change and its audit row are committed together.
+//!
+//! Snapshots copy the estimate as JSON into `estimate_versions` under an
+//! increasing version number so it can be restored later.
+
+use chrono::{DateTime, Utc};
+use denidom_core::{DeniDomError, Estimate, EstimateStatus};
+use rusqlite::{params, Connection, OptionalExtension};
+use uuid::Uuid;
+
+use crate::error::{DbError, DbResult};
+
+/// Database representation of an estimate status
+pub(crate) fn status_to_str(status: EstimateStatus) -> &'static str {
+    match status {
+        EstimateStatus::Draft => "draft",
+        EstimateStatus::InProgress => "in_progress",
+        EstimateStatus::OnReview => "on_review",
+        EstimateStatus::Approved => "approved",
+        EstimateStatus::Archived => "archived",
+    }
+}
+
+/// Запись журнала изменений сметы
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    /// Порядковый номер записи
+    pub id: i64,
+    /// Смета
+    pub estimate_id: Uuid,
+    /// Действие: `save`, `status`, `restore` или `delete`
+    pub action: String,
+    /// Кто внёс изменение
+    pub actor: String,
+    /// Когда
+    pub changed_at: DateTime<Utc>,
+    /// Подробности (например, смена статуса)
+    pub detail: Option<String>,
+}
+
+fn append_audit(
+    conn: &Connection,
+    estimate_id: Uuid,
+    action: &str,
+    actor: &str,
+    detail: Option<String>,
+) -> DbResult<()> {
+    conn.execute(
+        "INSERT INTO estimate_audit (estimate_id, action, actor, changed_at, detail)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![estimate_id.to_string(), action, actor, Utc::now().to_rfc3339(), detail],
+    )?;
+    Ok(())
+}
+
+/// Audit entries of an estimate, oldest first
+pub(crate) fn audit_log(conn: &Connection, estimate_id: Uuid) -> DbResult<Vec<AuditEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, action, actor, changed_at, detail
+         FROM estimate_audit
+         WHERE estimate_id = ?1
+         ORDER BY id",
+    )?;
+
+    let rows = stmt
+        .query_map([estimate_id.to_string()], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    rows.into_iter()
+        .map(|(id, action, actor, changed_at, detail)| {
+            let changed_at = DateTime::parse_from_rfc3339(&changed_at)
+                .map_err(|err| DbError::Validation(format!("invalid audit timestamp: {}", err)))?
+                .with_timezone(&Utc);
+            Ok(AuditEntry {
+                id,
+                estimate_id,
+                action,
+                actor,
+                changed_at,
+                detail,
+            })
+        })
+        .collect()
+}
+
+/// Storage format of the estimate body
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageFormat {
+    /// JSON text in `data`
+    #[default]
+    Json,
+    /// Compact bincode in `data_bin`
+    Bincode,
+}
+
+/// Insert or replace an estimate on behalf of `actor`
+pub(crate) fn save_estimate(
+    conn: &Connection,
+    estimate: &Estimate,
+    format: StorageFormat,
+    actor: &str,
+) -> DbResult<()> {
+    write_estimate(conn, estimate, format)?;
+    append_audit(conn, estimate.id, "save", actor, None)
+}
+
+fn write_estimate(conn: &Connection, estimate: &Estimate, format: StorageFormat) -> DbResult<()> {
+    let (data, data_bin) = match format {
+        StorageFormat::Json => (serde_json::to_string(estimate)?, None),
+        StorageFormat::Bincode => (String::new(), Some(estimate.to_bincode()?)),
+    };
+    conn.execute(
+        "INSERT INTO estimates (id, number, name, object, status, data, data_bin, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(id) DO UPDATE SET
+            number = excluded.number,
+            name = excluded.name,
+            object = excluded.object,
+            status = excluded.status,
+            data = excluded.data,
+            data_bin = excluded.data_bin,
+            updated_at = excluded.updated_at",
+        params![
+            estimate.id.to_string(),
+            estimate.number,
+            estimate.name,
+            estimate.object,
+            status_to_str(estimate.status),
+            data,
+            data_bin,
+            estimate.created_at.to_rfc3339(),
+            estimate.updated_at.to_rfc3339(),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Load an estimate by id
+pub(crate) fn load_estimate(conn: &Connection, id: Uuid) -> DbResult<Option<Estimate>> {
+    Ok(load_with_format(conn, id)?.map(|(estimate, _)| estimate))
+}
+
+/// All stored estimates, oldest first
+pub(crate) fn list_estimates(conn: &Connection) -> DbResult<Vec<Estimate>> {
+    let mut stmt = conn.prepare("SELECT data, data_bin FROM estimates ORDER BY created_at, id")?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<Vec<u8>>>(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    rows.into_iter()
+        .map(|(data, data_bin)| match data_bin {
+            Some(data_bin) => Ok(Estimate::from_bincode(&data_bin)?),
+            None => Ok(serde_json::from_str(&data)?),
+        })
+        .collect()
+}
+
+/// Load an estimate together with the format it is stored in
+fn load_with_format(conn: &Connection, id: Uuid) -> DbResult<Option<(Estimate, StorageFormat)>> {
+    let row: Option<(String, Option<Vec<u8>>)> = conn
+        .query_row(
+            "SELECT data, data_bin FROM estimates WHERE id = ?1",
+            [id.to_string()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    match row {
+        Some((_, Some(data_bin))) => Ok(Some((
+            Estimate::from_bincode(&data_bin)?,
+            StorageFormat::Bincode,
+        ))),
+        Some((data, None)) => Ok(Some((serde_json::from_str(&data)?, StorageFormat::Json))),
+        None => Ok(None),
+    }
+}
+
+/// Move a stored estimate to a new status on behalf of `actor`, keeping its
+/// storage format. Call inside a transaction so the read and write are atomic.
+pub(crate) fn update_status(
+    conn: &Connection,
+    id: Uuid,
+    to: EstimateStatus,
+    actor: &str,
+) -> DbResult<()> {
+    let (mut estimate, format) = load_with_format(conn, id)?
+        .ok_or_else(|| DbError::NotFound(format!("estimate {}", id)))?;
+    let from = estimate.status;
+
+    estimate.transition(to).map_err(|err| match err {
+        DeniDomError::Validation(message) => DbError::Validation(message),
+        other => other.into(),
+    })?;
+
+    write_estimate(conn, &estimate, format)?;
+    let detail = format!("{} -> {}", status_to_str(from), status_to_str(to));
+    append_audit(conn, id, "status", actor, Some(detail))
+}
+
+/// Store the current state of an estimate as a new version, returning its
+/// number (starting at 1)
+pub(crate) fn snapshot_estimate(conn: &Connection, id: Uuid) -> DbResult<u32> {
+    let estimate = load_estimate(conn, id)?
+        .ok_or_else(|| DbError::NotFound(format!("estimate {}", id)))?;
+    let version: u32 = conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) + 1 FROM estimate_versions WHERE estimate_id = ?1",
+        [id.to_string()],
+        |row| row.get(0),
+    )?;
+
+    conn.execute(
+        "INSERT INTO estimate_versions (estimate_id, version, data, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![id.to_string(), version, serde_json::to_string(&estimate)?, Utc::now().to_rfc3339()],
+    )?;
+    Ok(version)
+}
+
+/// Overwrite an estimate with a stored version on behalf of `actor`, keeping
+/// its storage format. Call inside a transaction so the read and write are atomic.
+pub(crate) fn restore_estimate(conn: &Connection, id: Uuid, version: u32, actor: &str) -> DbResult<()> {
+    let data: String = conn
+        .query_row(
+            "SELECT data FROM estimate_versions WHERE estimate_id = ?1 AND version = ?2",
+            params![id.to_string(), version],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| DbError::NotFound(format!("estimate {} version {}", id, version)))?;
+    let estimate: Estimate = serde_json::from_str(&data)?;
+    let format = load_with_format(conn, id)?.map_or(StorageFormat::Json, |(_, format)| format);
+
+    write_estimate(conn, &estimate, format)?;
+    append_audit(conn, id, "restore", actor, Some(format!("version {}", version)))
+}
+
+/// Delete an estimate on behalf of `actor`, returning whether it existed.
+/// The audit trail of a deleted estimate is kept.
+pub(crate) fn delete_estimate(conn: &Connection, id: Uuid, actor: &str) -> DbResult<bool> {
+    let deleted = conn.execute("DELETE FROM estimates WHERE id = ?1", [id.to_string()])? > 0;
+    if deleted {
+        append_audit(conn, id, "delete", actor, None)?;
+    }
+    Ok(deleted)
+}
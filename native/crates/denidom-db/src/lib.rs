@@ -3,4 +3,13 @@
 //! Provides database operations for estimates and normatives.
 //! Supports SQLite (local) and PostgreSQL (server).
 
+pub mod error;
+mod estimates;
+mod normatives;
+pub mod pool;
 pub mod sqlite;
+
+pub use error::{DbError, DbResult};
+pub use estimates::{AuditEntry, StorageFormat};
+pub use pool::{DbPool, PooledConn, DEFAULT_POOL_SIZE};
+pub use sqlite::Database;
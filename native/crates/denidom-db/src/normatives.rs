@@ -0,0 +1,294 @@
+//! Normative persistence
+
+use denidom_core::{MeasureUnit, NormativeBase, NormativeItem, ResourceNorm, ResourceNormType, UnitCosts};
+use std::str::FromStr;
+
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use rust_decimal::Decimal;
+
+use crate::error::DbResult;
+
+/// Decimal stored as TEXT in column `index`
+fn decimal_at(row: &Row, index: usize) -> rusqlite::Result<Decimal> {
+    parse_decimal(row.get(index)?, index)
+}
+
+/// Optional decimal stored as TEXT in column `index`
+fn optional_decimal_at(row: &Row, index: usize) -> rusqlite::Result<Option<Decimal>> {
+    row.get::<_, Option<String>>(index)?
+        .map(|text| parse_decimal(text, index))
+        .transpose()
+}
+
+fn parse_decimal(text: String, index: usize) -> rusqlite::Result<Decimal> {
+    Decimal::from_str(&text)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
+/// Lowercased code and name that [`search_normatives`] matches against
+///
+/// SQLite folds case for ASCII only, so the key is lowercased here to make
+/// Cyrillic search case-insensitive.
+fn search_key(code: &str, name: &str) -> String {
+    format!("{}\n{}", code, name).to_lowercase()
+}
+
+/// Fill the search key of rows written before it existed
+pub(crate) fn fill_search_keys(conn: &Connection) -> rusqlite::Result<()> {
+    let rows = conn
+        .prepare("SELECT id, code, name FROM normatives WHERE search_key IS NULL")?
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut stmt = conn.prepare("UPDATE normatives SET search_key = ?1 WHERE id = ?2")?;
+    for (id, code, name) in rows {
+        stmt.execute(params![search_key(&code, &name), id])?;
+    }
+    Ok(())
+}
+
+/// Escape the LIKE wildcards `%` and `_` (and the escape character itself)
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Insert or replace normatives by code, returning the number of rows written
+pub(crate) fn insert_normatives(conn: &Connection, items: &[NormativeItem]) -> DbResult<usize> {
+    let mut stmt = conn.prepare(
+        "INSERT INTO normatives (code, name, unit, base_type, direct_cost, labor_cost,
+            machine_op_cost, material_cost, machine_cost, labor_norm, machine_norm, section, notes,
+            search_key)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+         ON CONFLICT(code) DO UPDATE SET
+            name = excluded.name,
+            unit = excluded.unit,
+            base_type = excluded.base_type,
+            direct_cost = excluded.direct_cost,
+            labor_cost = excluded.labor_cost,
+            machine_op_cost = excluded.machine_op_cost,
+            material_cost = excluded.material_cost,
+            machine_cost = excluded.machine_cost,
+            labor_norm = excluded.labor_norm,
+            machine_norm = excluded.machine_norm,
+            section = excluded.section,
+            notes = excluded.notes,
+            search_key = excluded.search_key",
+    )?;
+
+    for item in items {
+        stmt.execute(params![
+            item.code,
+            item.name,
+            item.unit.abbreviation(),
+            item.base_type.display_name(),
+            item.costs.direct.to_string(),
+            item.costs.labor.to_string(),
+            item.costs.machine_operator.to_string(),
+            item.costs.materials.to_string(),
+            item.costs.machines.to_string(),
+            item.labor_norm.to_string(),
+            item.machine_norm.to_string(),
+            item.section,
+            item.notes,
+            search_key(&item.code, &item.name),
+        ])?;
+        replace_resource_norms(conn, &item.code, &item.resources)?;
+    }
+
+    Ok(items.len())
+}
+
+fn resource_type_name(resource_type: ResourceNormType) -> &'static str {
+    match resource_type {
+        ResourceNormType::Material => "material",
+        ResourceNormType::Machine => "machine",
+        ResourceNormType::Labor => "labor",
+        ResourceNormType::MachineOperator => "machine_operator",
+    }
+}
+
+fn parse_resource_type(name: &str) -> ResourceNormType {
+    match name {
+        "machine" => ResourceNormType::Machine,
+        "labor" => ResourceNormType::Labor,
+        "machine_operator" => ResourceNormType::MachineOperator,
+        _ => ResourceNormType::Material,
+    }
+}
+
+/// Replace the resource norms of a normative
+pub(crate) fn replace_resource_norms(
+    conn: &Connection,
+    normative_code: &str,
+    norms: &[ResourceNorm],
+) -> DbResult<()> {
+    conn.execute(
+        "DELETE FROM normative_resources WHERE normative_code = ?1",
+        [normative_code],
+    )?;
+
+    let mut stmt = conn.prepare(
+        "INSERT INTO normative_resources
+            (normative_code, resource_code, name, type, unit, consumption, base_price)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?;
+    for norm in norms {
+        stmt.execute(params![
+            normative_code,
+            norm.code,
+            norm.name,
+            resource_type_name(norm.resource_type),
+            norm.unit.abbreviation(),
+            norm.consumption.to_string(),
+            norm.base_price.map(|price| price.to_string()),
+        ])?;
+    }
+    Ok(())
+}
+
+/// Resource norms of a normative in insertion order
+pub(crate) fn get_resource_norms(conn: &Connection, normative_code: &str) -> DbResult<Vec<ResourceNorm>> {
+    let mut stmt = conn.prepare(
+        "SELECT resource_code, name, type, unit, consumption, base_price
+         FROM normative_resources
+         WHERE normative_code = ?1
+         ORDER BY id",
+    )?;
+
+    let norms = stmt
+        .query_map([normative_code], |row| {
+            Ok(ResourceNorm {
+                code: row.get(0)?,
+                name: row.get(1)?,
+                resource_type: parse_resource_type(&row.get::<_, String>(2)?),
+                unit: MeasureUnit::from(row.get::<_, String>(3)?.as_str()),
+                consumption: decimal_at(row, 4)?,
+                base_price: optional_decimal_at(row, 5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(norms)
+}
+
+/// Load the normative stored under `code` together with its resource norms
+pub(crate) fn get_normative(conn: &Connection, code: &str) -> DbResult<Option<NormativeItem>> {
+    let mut item = conn
+        .query_row(
+            "SELECT code, name, unit, base_type, direct_cost, labor_cost, machine_op_cost,
+                    material_cost, machine_cost, labor_norm, machine_norm, section, notes
+             FROM normatives
+             WHERE code = ?1",
+            [code],
+            row_to_normative,
+        )
+        .optional()?;
+    if let Some(item) = item.as_mut() {
+        item.resources = get_resource_norms(conn, code)?;
+    }
+    Ok(item)
+}
+
+/// Overwrite the normative stored under `code` (the code itself may change),
+/// returning whether a row was updated
+pub(crate) fn update_normative(conn: &Connection, code: &str, item: &NormativeItem) -> DbResult<bool> {
+    let updated = conn.execute(
+        "UPDATE normatives SET
+            code = ?1, name = ?2, unit = ?3, base_type = ?4, direct_cost = ?5, labor_cost = ?6,
+            machine_op_cost = ?7, material_cost = ?8, machine_cost = ?9, labor_norm = ?10,
+            machine_norm = ?11, section = ?12, notes = ?13, search_key = ?14
+         WHERE code = ?15",
+        params![
+            item.code,
+            item.name,
+            item.unit.abbreviation(),
+            item.base_type.display_name(),
+            item.costs.direct.to_string(),
+            item.costs.labor.to_string(),
+            item.costs.machine_operator.to_string(),
+            item.costs.materials.to_string(),
+            item.costs.machines.to_string(),
+            item.labor_norm.to_string(),
+            item.machine_norm.to_string(),
+            item.section,
+            item.notes,
+            search_key(&item.code, &item.name),
+            code,
+        ],
+    )?;
+    if updated > 0 && item.code != code {
+        conn.execute(
+            "UPDATE normative_resources SET normative_code = ?1 WHERE normative_code = ?2",
+            params![item.code, code],
+        )?;
+    }
+    Ok(updated > 0)
+}
+
+/// Delete the normative stored under `code` with its resource norms,
+/// returning whether it existed
+pub(crate) fn delete_normative(conn: &Connection, code: &str) -> DbResult<bool> {
+    conn.execute("DELETE FROM normative_resources WHERE normative_code = ?1", [code])?;
+    Ok(conn.execute("DELETE FROM normatives WHERE code = ?1", [code])? > 0)
+}
+
+/// Find normatives whose code or name contains `query`, ignoring case
+///
+/// `query` is matched literally. `base` restricts results to one normative
+/// base. Results are ordered by code.
+pub(crate) fn search_normatives(
+    conn: &Connection,
+    query: &str,
+    base: Option<NormativeBase>,
+    limit: usize,
+) -> DbResult<Vec<NormativeItem>> {
+    let pattern = format!("%{}%", escape_like(&query.to_lowercase()));
+    let mut stmt = conn.prepare(
+        "SELECT code, name, unit, base_type, direct_cost, labor_cost, machine_op_cost,
+                material_cost, machine_cost, labor_norm, machine_norm, section, notes
+         FROM normatives
+         WHERE search_key LIKE ?1 ESCAPE '\\' AND (?2 IS NULL OR base_type = ?2)
+         ORDER BY code
+         LIMIT ?3",
+    )?;
+
+    let items = stmt
+        .query_map(
+            params![pattern, base.map(|b| b.display_name()), limit as i64],
+            row_to_normative,
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(items)
+}
+
+fn row_to_normative(row: &Row) -> rusqlite::Result<NormativeItem> {
+    let code: String = row.get(0)?;
+    let base_type: String = row.get(3)?;
+    let base_type = NormativeBase::from_code_prefix(&base_type)
+        .or_else(|| NormativeBase::from_code_prefix(&code))
+        .unwrap_or(NormativeBase::FER);
+
+    let mut item = NormativeItem::new(code, row.get(1)?, base_type);
+    item.unit = MeasureUnit::from(row.get::<_, String>(2)?.as_str());
+    item.costs = UnitCosts::new(
+        decimal_at(row, 4)?,
+        decimal_at(row, 5)?,
+        decimal_at(row, 6)?,
+        decimal_at(row, 7)?,
+        decimal_at(row, 8)?,
+    );
+    item.labor_norm = decimal_at(row, 9)?;
+    item.machine_norm = decimal_at(row, 10)?;
+    item.section = row.get(11)?;
+    item.notes = row.get(12)?;
+    Ok(item)
+}
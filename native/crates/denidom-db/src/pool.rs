@@ -0,0 +1,198 @@
+//! SQLite connection pool for the async API
+//!
+//! A single `rusqlite::Connection` serializes all access. `DbPool` hands out
+//! pooled connections so concurrent requests do not wait on each other.
+//!
+//! File databases are opened in WAL mode: readers never block each other and
+//! are not blocked by a writer, while writes are still serialized by SQLite.
+
+use denidom_core::{Estimate, NormativeBase, NormativeItem};
+use r2d2::PooledConnection;
+use r2d2_sqlite::SqliteConnectionManager;
+use uuid::Uuid;
+
+use crate::error::DbResult;
+use crate::estimates::{self, AuditEntry, StorageFormat};
+use crate::normatives;
+use crate::sqlite::migrate_connection;
+
+/// Default number of pooled connections
+pub const DEFAULT_POOL_SIZE: u32 = 8;
+
+/// Pooled SQLite connection
+pub type PooledConn = PooledConnection<SqliteConnectionManager>;
+
+/// SQLite connection pool, cheap to clone
+#[derive(Clone)]
+pub struct DbPool {
+    pool: r2d2::Pool<SqliteConnectionManager>,
+}
+
+impl DbPool {
+    /// Open a pool over a SQLite file with WAL journaling
+    pub fn open(path: &str, max_size: u32) -> DbResult<Self> {
+        let manager = SqliteConnectionManager::file(path).with_init(|conn| {
+            conn.execute_batch(
+                "PRAGMA journal_mode = WAL;
+                 PRAGMA synchronous = NORMAL;
+                 PRAGMA busy_timeout = 5000;",
+            )
+        });
+        let pool = r2d2::Pool::builder().max_size(max_size).build(manager)?;
+        Ok(Self { pool })
+    }
+
+    /// Open a single-connection pool over a private in-memory database (for testing)
+    pub fn open_in_memory() -> DbResult<Self> {
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .build(SqliteConnectionManager::memory())?;
+        Ok(Self { pool })
+    }
+
+    /// Get a connection from the pool
+    pub fn get(&self) -> DbResult<PooledConn> {
+        Ok(self.pool.get()?)
+    }
+
+    /// Run database migrations
+    pub fn migrate(&self) -> DbResult<()> {
+        let conn = self.get()?;
+        migrate_connection(&conn)?;
+        Ok(())
+    }
+
+    /// Insert or replace an estimate as JSON on behalf of `actor`
+    pub fn save_estimate(&self, estimate: &Estimate, actor: &str) -> DbResult<()> {
+        self.save_estimate_as(estimate, StorageFormat::Json, actor)
+    }
+
+    /// Insert or replace an estimate in the given storage format on behalf of `actor`
+    pub fn save_estimate_as(
+        &self,
+        estimate: &Estimate,
+        format: StorageFormat,
+        actor: &str,
+    ) -> DbResult<()> {
+        let mut conn = self.get()?;
+        let tx = conn.transaction()?;
+        estimates::save_estimate(&tx, estimate, format, actor)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Audit entries of an estimate, oldest first
+    pub fn audit_log(&self, estimate_id: Uuid) -> DbResult<Vec<AuditEntry>> {
+        estimates::audit_log(&*self.get()?, estimate_id)
+    }
+
+    /// Load an estimate by id
+    pub fn load_estimate(&self, id: Uuid) -> DbResult<Option<Estimate>> {
+        estimates::load_estimate(&*self.get()?, id)
+    }
+
+    /// Insert or replace normatives atomically
+    pub fn insert_normatives_bulk(&self, items: &[NormativeItem]) -> DbResult<usize> {
+        let mut conn = self.get()?;
+        let tx = conn.transaction()?;
+        let count = normatives::insert_normatives(&tx, items)?;
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// Load a normative by code
+    pub fn get_normative(&self, code: &str) -> DbResult<Option<NormativeItem>> {
+        normatives::get_normative(&*self.get()?, code)
+    }
+
+    /// Find normatives by code or name fragment, optionally within one base
+    pub fn search_normatives(
+        &self,
+        query: &str,
+        base: Option<NormativeBase>,
+        limit: usize,
+    ) -> DbResult<Vec<NormativeItem>> {
+        normatives::search_normatives(&*self.get()?, query, base, limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_concurrent_queries() {
+        let path = std::env::temp_dir().join(format!("denidom-pool-{}.db", std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+
+        let pool = DbPool::open(&path, 4).unwrap();
+        pool.migrate().unwrap();
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    let conn = pool.get().unwrap();
+                    conn.execute(
+                        "INSERT INTO normatives (code, name, unit, base_type) VALUES (?1, ?2, 'шт', 'FER')",
+                        (format!("ФЕР01-01-{:03}", i), format!("Работа {}", i)),
+                    )
+                    .unwrap();
+                    conn.query_row("SELECT COUNT(*) FROM normatives", [], |row| row.get::<_, i64>(0))
+                        .unwrap()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert!(handle.join().unwrap() >= 1);
+        }
+
+        let count: i64 = pool
+            .get()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM normatives", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 8);
+
+        drop(pool);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path, suffix));
+        }
+    }
+
+    #[test]
+    fn test_save_estimate_as_bincode() {
+        use denidom_core::{EstimateItem, EstimateSection, MeasureUnit};
+        use rust_decimal::Decimal;
+
+        let pool = DbPool::open_in_memory().unwrap();
+        pool.migrate().unwrap();
+
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Раздел".to_string());
+        section.add_item(EstimateItem::new(
+            "ФЕР01-01-001-01".to_string(),
+            "Разработка грунта".to_string(),
+            MeasureUnit::CubicMeter,
+            Decimal::new(125, 1),
+        ));
+        estimate.sections.push(section);
+
+        pool.save_estimate_as(&estimate, StorageFormat::Bincode, "test").unwrap();
+        let loaded = pool.load_estimate(estimate.id).unwrap().unwrap();
+        assert_eq!(loaded.sections[0].items[0].quantity, Decimal::new(125, 1));
+
+        pool.save_estimate(&estimate, "test").unwrap();
+        let data_bin: Option<Vec<u8>> = pool
+            .get()
+            .unwrap()
+            .query_row("SELECT data_bin FROM estimates", [], |row| row.get(0))
+            .unwrap();
+        assert!(data_bin.is_none());
+        assert!(pool.load_estimate(estimate.id).unwrap().is_some());
+    }
+}
@@ -1,6 +1,18 @@
 //! SQLite Database Backend
 
-use rusqlite::{Connection, Result};
+use denidom_core::{Estimate, EstimateStatus, NormativeBase, NormativeItem, ResourceNorm};
+use rusqlite::{Connection, OptionalExtension, Result, Transaction};
+use uuid::Uuid;
+
+use crate::error::DbResult;
+use crate::estimates::{
+    audit_log, delete_estimate, list_estimates, load_estimate, restore_estimate, save_estimate, snapshot_estimate,
+    update_status, AuditEntry, StorageFormat,
+};
+use crate::normatives::{
+    delete_normative, fill_search_keys, get_normative, get_resource_norms, insert_normatives,
+    replace_resource_norms, search_normatives, update_normative,
+};
 
 pub struct Database {
     conn: Connection,
@@ -21,51 +33,657 @@ impl Database {
 
     /// Run database migrations
     pub fn migrate(&self) -> Result<()> {
-        self.conn.execute_batch(
-            "
-            CREATE TABLE IF NOT EXISTS estimates (
-                id TEXT PRIMARY KEY,
-                number TEXT NOT NULL,
-                name TEXT NOT NULL,
-                object TEXT,
-                status TEXT NOT NULL DEFAULT 'draft',
-                data TEXT NOT NULL,
-                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-            );
-
-            CREATE TABLE IF NOT EXISTS normatives (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                code TEXT NOT NULL UNIQUE,
-                name TEXT NOT NULL,
-                unit TEXT NOT NULL,
-                base_type TEXT NOT NULL,
-                direct_cost REAL NOT NULL DEFAULT 0,
-                labor_cost REAL NOT NULL DEFAULT 0,
-                machine_op_cost REAL NOT NULL DEFAULT 0,
-                material_cost REAL NOT NULL DEFAULT 0,
-                machine_cost REAL NOT NULL DEFAULT 0,
-                labor_norm REAL NOT NULL DEFAULT 0,
-                machine_norm REAL NOT NULL DEFAULT 0,
-                section TEXT,
-                notes TEXT
-            );
-
-            CREATE INDEX IF NOT EXISTS idx_normatives_code ON normatives(code);
-            CREATE INDEX IF NOT EXISTS idx_normatives_base_type ON normatives(base_type);
-            "
-        )?;
-        Ok(())
+        migrate_connection(&self.conn)
+    }
+
+    /// Run `f` inside a transaction, committing on `Ok` and rolling back on `Err`
+    pub fn transaction<F, T>(&self, f: F) -> DbResult<T>
+    where
+        F: FnOnce(&Transaction) -> DbResult<T>,
+    {
+        let tx = self.conn.unchecked_transaction()?;
+        let value = f(&tx)?;
+        tx.commit()?;
+        Ok(value)
+    }
+
+    /// Insert or replace normatives atomically
+    pub fn insert_normatives_bulk(&self, items: &[NormativeItem]) -> DbResult<usize> {
+        self.transaction(|tx| insert_normatives(tx, items))
+    }
+
+    /// Load a normative by code, including its resource norms
+    pub fn load_normative(&self, code: &str) -> DbResult<Option<NormativeItem>> {
+        get_normative(&self.conn, code)
+    }
+
+    /// Replace the resource norms stored for a normative atomically
+    pub fn insert_resource_norms(&self, normative_code: &str, norms: &[ResourceNorm]) -> DbResult<()> {
+        self.transaction(|tx| replace_resource_norms(tx, normative_code, norms))
+    }
+
+    /// Resource norms stored for a normative
+    pub fn resource_norms(&self, normative_code: &str) -> DbResult<Vec<ResourceNorm>> {
+        get_resource_norms(&self.conn, normative_code)
+    }
+
+    /// Find normatives by code or name fragment, optionally within one base
+    pub fn search_normatives(
+        &self,
+        query: &str,
+        base: Option<NormativeBase>,
+        limit: usize,
+    ) -> DbResult<Vec<NormativeItem>> {
+        search_normatives(&self.conn, query, base, limit)
+    }
+
+    /// Overwrite the normative stored under `code`; `false` if there is none
+    pub fn update_normative(&self, code: &str, item: &NormativeItem) -> DbResult<bool> {
+        self.transaction(|tx| update_normative(tx, code, item))
+    }
+
+    /// Delete the normative stored under `code`; `false` if there is none
+    pub fn delete_normative(&self, code: &str) -> DbResult<bool> {
+        self.transaction(|tx| delete_normative(tx, code))
+    }
+
+    /// Insert or replace an estimate as JSON on behalf of `actor`
+    pub fn save_estimate(&self, estimate: &Estimate, actor: &str) -> DbResult<()> {
+        self.save_estimate_as(estimate, StorageFormat::Json, actor)
+    }
+
+    /// Insert or replace an estimate in the given storage format on behalf of `actor`
+    pub fn save_estimate_as(
+        &self,
+        estimate: &Estimate,
+        format: StorageFormat,
+        actor: &str,
+    ) -> DbResult<()> {
+        self.transaction(|tx| save_estimate(tx, estimate, format, actor))
+    }
+
+    /// Load an estimate by id
+    pub fn get_estimate(&self, id: Uuid) -> DbResult<Option<Estimate>> {
+        load_estimate(&self.conn, id)
+    }
+
+    /// All stored estimates, oldest first
+    pub fn list_estimates(&self) -> DbResult<Vec<Estimate>> {
+        list_estimates(&self.conn)
+    }
+
+    /// Move a stored estimate to status `to` atomically on behalf of `actor`;
+    /// illegal transitions fail with [`DbError::Validation`](crate::DbError::Validation)
+    pub fn update_status(&self, id: Uuid, to: EstimateStatus, actor: &str) -> DbResult<()> {
+        self.transaction(|tx| update_status(tx, id, to, actor))
+    }
+
+    /// Delete an estimate on behalf of `actor`; `false` if there is none
+    pub fn delete_estimate(&self, id: Uuid, actor: &str) -> DbResult<bool> {
+        self.transaction(|tx| delete_estimate(tx, id, actor))
+    }
+
+    /// Audit entries of an estimate, oldest first
+    pub fn audit_log(&self, estimate_id: Uuid) -> DbResult<Vec<AuditEntry>> {
+        audit_log(&self.conn, estimate_id)
+    }
+
+    /// Store the current state of an estimate as a new version and return its number
+    pub fn snapshot_estimate(&self, id: Uuid) -> DbResult<u32> {
+        self.transaction(|tx| snapshot_estimate(tx, id))
+    }
+
+    /// Load a stored version back into the estimate on behalf of `actor`;
+    /// an unknown version fails with [`DbError::NotFound`](crate::DbError::NotFound)
+    pub fn restore_estimate(&self, id: Uuid, version: u32, actor: &str) -> DbResult<()> {
+        self.transaction(|tx| restore_estimate(tx, id, version, actor))
     }
 }
 
+/// Normative tables; amounts are decimal TEXT so they round-trip exactly
+const NORMATIVE_TABLES: &str = "
+        CREATE TABLE IF NOT EXISTS normatives (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            code TEXT NOT NULL UNIQUE,
+            name TEXT NOT NULL,
+            unit TEXT NOT NULL,
+            base_type TEXT NOT NULL,
+            direct_cost TEXT NOT NULL DEFAULT '0',
+            labor_cost TEXT NOT NULL DEFAULT '0',
+            machine_op_cost TEXT NOT NULL DEFAULT '0',
+            material_cost TEXT NOT NULL DEFAULT '0',
+            machine_cost TEXT NOT NULL DEFAULT '0',
+            labor_norm TEXT NOT NULL DEFAULT '0',
+            machine_norm TEXT NOT NULL DEFAULT '0',
+            section TEXT,
+            notes TEXT,
+            search_key TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS normative_resources (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            normative_code TEXT NOT NULL,
+            resource_code TEXT NOT NULL,
+            name TEXT NOT NULL,
+            type TEXT NOT NULL,
+            unit TEXT NOT NULL,
+            consumption TEXT NOT NULL DEFAULT '0',
+            base_price TEXT
+        );
+";
+
+/// Indexes of the normative tables
+const NORMATIVE_INDEXES: &str = "
+        CREATE INDEX IF NOT EXISTS idx_normatives_code ON normatives(code);
+        CREATE INDEX IF NOT EXISTS idx_normatives_base_type ON normatives(base_type);
+        CREATE INDEX IF NOT EXISTS idx_normative_resources_code
+            ON normative_resources(normative_code);
+";
+
+/// Create tables and indexes on a connection
+pub(crate) fn migrate_connection(conn: &Connection) -> Result<()> {
+    conn.execute_batch(&format!(
+        "
+        CREATE TABLE IF NOT EXISTS estimates (
+            id TEXT PRIMARY KEY,
+            number TEXT NOT NULL,
+            name TEXT NOT NULL,
+            object TEXT,
+            status TEXT NOT NULL DEFAULT 'draft',
+            data TEXT NOT NULL,
+            data_bin BLOB,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        {NORMATIVE_TABLES}
+        CREATE TABLE IF NOT EXISTS estimate_audit (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            estimate_id TEXT NOT NULL,
+            action TEXT NOT NULL,
+            actor TEXT NOT NULL,
+            changed_at TEXT NOT NULL,
+            detail TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS estimate_versions (
+            estimate_id TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            data TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (estimate_id, version)
+        );
+
+        {NORMATIVE_INDEXES}
+        CREATE INDEX IF NOT EXISTS idx_estimate_audit_estimate
+            ON estimate_audit(estimate_id);
+        "
+    ))?;
+    add_column_if_missing(conn, "estimates", "data_bin", "BLOB")?;
+    add_column_if_missing(conn, "normatives", "search_key", "TEXT")?;
+    convert_real_normatives(conn)?;
+    fill_search_keys(conn)?;
+    Ok(())
+}
+
+/// Rebuild normative tables created by an older schema that stored amounts
+/// as REAL; the copied values become their decimal text
+fn convert_real_normatives(conn: &Connection) -> Result<()> {
+    let direct_cost_type: Option<String> = conn
+        .query_row(
+            "SELECT type FROM pragma_table_info('normatives') WHERE name = 'direct_cost'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if direct_cost_type.as_deref() != Some("REAL") {
+        return Ok(());
+    }
+
+    conn.execute_batch(&format!(
+        "
+        SAVEPOINT convert_real_normatives;
+        ALTER TABLE normatives RENAME TO normatives_real;
+        ALTER TABLE normative_resources RENAME TO normative_resources_real;
+        {NORMATIVE_TABLES}
+        INSERT INTO normatives SELECT * FROM normatives_real;
+        INSERT INTO normative_resources SELECT * FROM normative_resources_real;
+        DROP TABLE normatives_real;
+        DROP TABLE normative_resources_real;
+        {NORMATIVE_INDEXES}
+        RELEASE convert_real_normatives;
+        "
+    ))
+}
+
+/// Add a column to a table created by an older schema version
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, ty: &str) -> Result<()> {
+    let exists = conn
+        .prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>>>()?
+        .iter()
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, ty))?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::DbError;
 
     #[test]
     fn test_open_in_memory() {
         let db = Database::open_in_memory().unwrap();
         db.migrate().unwrap();
     }
+
+    fn normatives_count(db: &Database) -> i64 {
+        db.conn
+            .query_row("SELECT COUNT(*) FROM normatives", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    fn sample_normatives() -> Vec<NormativeItem> {
+        ["ФЕР01-01-001-01", "ФЕР01-01-002-01"]
+            .iter()
+            .map(|code| {
+                NormativeItem::new(code.to_string(), "Работа".to_string(), NormativeBase::FER)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_normatives_bulk() {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+
+        assert_eq!(db.insert_normatives_bulk(&sample_normatives()).unwrap(), 2);
+        assert_eq!(db.insert_normatives_bulk(&sample_normatives()).unwrap(), 2);
+        assert_eq!(normatives_count(&db), 2);
+    }
+
+    #[test]
+    fn test_search_normatives() {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        let mut items = sample_normatives();
+        items[1].name = "Засыпка траншей".to_string();
+        items.push(NormativeItem::new(
+            "ГЭСН01-01-001-01".to_string(),
+            "Работа".to_string(),
+            NormativeBase::GESN,
+        ));
+        db.insert_normatives_bulk(&items).unwrap();
+
+        let found = db.search_normatives("Засыпка", None, 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].code, "ФЕР01-01-002-01");
+        assert_eq!(db.search_normatives("ЗАСЫПКА ТРАНШЕЙ", None, 10).unwrap().len(), 1);
+        assert_eq!(db.search_normatives("фер01-01-002", None, 10).unwrap().len(), 1);
+
+        let gesn = db.search_normatives("01-01-001", Some(NormativeBase::GESN), 10).unwrap();
+        assert_eq!(gesn.len(), 1);
+        assert_eq!(gesn[0].base_type, NormativeBase::GESN);
+
+        assert_eq!(db.search_normatives("", None, 2).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_search_escapes_wildcards() {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        let mut items = sample_normatives();
+        items[0].name = "Скидка 10% на материалы".to_string();
+        items[1].name = "Работа_по смете".to_string();
+        db.insert_normatives_bulk(&items).unwrap();
+
+        let percent = db.search_normatives("%", None, 10).unwrap();
+        assert_eq!(percent.len(), 1);
+        assert_eq!(percent[0].code, "ФЕР01-01-001-01");
+
+        let underscore = db.search_normatives("_", None, 10).unwrap();
+        assert_eq!(underscore.len(), 1);
+        assert_eq!(underscore[0].code, "ФЕР01-01-002-01");
+
+        assert!(db.search_normatives("10_", None, 10).unwrap().is_empty());
+        assert!(db.search_normatives("\\", None, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_update_and_delete_normative() {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        db.insert_normatives_bulk(&sample_normatives()).unwrap();
+
+        let mut fixed = sample_normatives().remove(0);
+        fixed.name = "Разработка грунта экскаватором".to_string();
+        assert!(db.update_normative("ФЕР01-01-001-01", &fixed).unwrap());
+        assert!(!db.update_normative("ФЕР99-99-999-99", &fixed).unwrap());
+
+        let found = db.search_normatives("экскаватором", None, 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].code, "ФЕР01-01-001-01");
+
+        assert!(db.delete_normative("ФЕР01-01-002-01").unwrap());
+        assert!(!db.delete_normative("ФЕР01-01-002-01").unwrap());
+        assert_eq!(normatives_count(&db), 1);
+    }
+
+    #[test]
+    fn test_resource_norms_roundtrip() {
+        use denidom_core::{MeasureUnit, ResourceNormType};
+        use rust_decimal::Decimal;
+
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+
+        let mut item = NormativeItem::new(
+            "ГЭСН08-02-001-01".to_string(),
+            "Кладка стен".to_string(),
+            NormativeBase::GESN,
+        );
+        item.resources = vec![
+            ResourceNorm {
+                code: "1-100-30".to_string(),
+                name: "Рабочий-строитель".to_string(),
+                resource_type: ResourceNormType::Labor,
+                unit: MeasureUnit::ManHour,
+                consumption: Decimal::new(54, 1),
+                base_price: None,
+            },
+            ResourceNorm {
+                code: "04.3.01.09-0014".to_string(),
+                name: "Раствор кладочный".to_string(),
+                resource_type: ResourceNormType::Material,
+                unit: MeasureUnit::CubicMeter,
+                consumption: Decimal::new(25, 2),
+                base_price: Some(Decimal::new(519, 0)),
+            },
+        ];
+        db.insert_normatives_bulk(std::slice::from_ref(&item)).unwrap();
+
+        let loaded = db.load_normative("ГЭСН08-02-001-01").unwrap().unwrap();
+        assert_eq!(loaded.resources.len(), 2);
+        for (stored, original) in loaded.resources.iter().zip(&item.resources) {
+            assert_eq!(stored.code, original.code);
+            assert_eq!(stored.name, original.name);
+            assert_eq!(stored.resource_type, original.resource_type);
+            assert_eq!(stored.unit, original.unit);
+            assert_eq!(stored.consumption, original.consumption);
+            assert_eq!(stored.base_price, original.base_price);
+        }
+
+        db.insert_resource_norms("ГЭСН08-02-001-01", &item.resources[..1]).unwrap();
+        assert_eq!(db.resource_norms("ГЭСН08-02-001-01").unwrap().len(), 1);
+
+        assert!(db.delete_normative("ГЭСН08-02-001-01").unwrap());
+        assert!(db.resource_norms("ГЭСН08-02-001-01").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_amounts_stored_exactly() {
+        use denidom_core::{MeasureUnit, ResourceNormType, UnitCosts};
+        use rust_decimal::Decimal;
+
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+
+        let mut item = sample_normatives().remove(0);
+        item.costs = UnitCosts::new(
+            Decimal::new(12_345_678_901_234_567, 4),
+            Decimal::new(1, 1),
+            Decimal::new(2, 1),
+            Decimal::new(30, 2),
+            Decimal::ZERO,
+        );
+        item.labor_norm = Decimal::new(1_000_000_000_000_001, 6);
+        item.resources = vec![ResourceNorm {
+            code: "04.3.01.09-0014".to_string(),
+            name: "Раствор кладочный".to_string(),
+            resource_type: ResourceNormType::Material,
+            unit: MeasureUnit::CubicMeter,
+            consumption: Decimal::new(123_456_789_012_345_678, 18),
+            base_price: Some(Decimal::new(99_999_999_999_999_999, 2)),
+        }];
+        db.insert_normatives_bulk(std::slice::from_ref(&item)).unwrap();
+
+        let loaded = db.load_normative(&item.code).unwrap().unwrap();
+        assert_eq!(loaded.costs.direct, item.costs.direct);
+        assert_eq!(loaded.costs.materials.to_string(), "0.30");
+        assert_eq!(loaded.labor_norm, item.labor_norm);
+        assert_eq!(loaded.resources[0].consumption, item.resources[0].consumption);
+        assert_eq!(loaded.resources[0].base_price, item.resources[0].base_price);
+    }
+
+    #[test]
+    fn test_migrate_converts_real_columns() {
+        use rust_decimal::Decimal;
+
+        let db = Database::open_in_memory().unwrap();
+        db.conn
+            .execute_batch(
+                "CREATE TABLE normatives (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    code TEXT NOT NULL UNIQUE,
+                    name TEXT NOT NULL,
+                    unit TEXT NOT NULL,
+                    base_type TEXT NOT NULL,
+                    direct_cost REAL NOT NULL DEFAULT 0,
+                    labor_cost REAL NOT NULL DEFAULT 0,
+                    machine_op_cost REAL NOT NULL DEFAULT 0,
+                    material_cost REAL NOT NULL DEFAULT 0,
+                    machine_cost REAL NOT NULL DEFAULT 0,
+                    labor_norm REAL NOT NULL DEFAULT 0,
+                    machine_norm REAL NOT NULL DEFAULT 0,
+                    section TEXT,
+                    notes TEXT
+                );
+                CREATE TABLE normative_resources (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    normative_code TEXT NOT NULL,
+                    resource_code TEXT NOT NULL,
+                    name TEXT NOT NULL,
+                    type TEXT NOT NULL,
+                    unit TEXT NOT NULL,
+                    consumption REAL NOT NULL DEFAULT 0,
+                    base_price REAL
+                );
+                INSERT INTO normatives (code, name, unit, base_type, direct_cost)
+                    VALUES ('ФЕР01-01-001-01', 'Работа', 'шт', 'FER', 1234.5);
+                INSERT INTO normative_resources
+                    (normative_code, resource_code, name, type, unit, consumption, base_price)
+                    VALUES ('ФЕР01-01-001-01', '1-100-20', 'Рабочий', 'labor', 'чел.-ч', 0.25, NULL);",
+            )
+            .unwrap();
+
+        db.migrate().unwrap();
+        db.migrate().unwrap();
+
+        let loaded = db.load_normative("ФЕР01-01-001-01").unwrap().unwrap();
+        assert_eq!(loaded.costs.direct, Decimal::new(12345, 1));
+        assert_eq!(loaded.costs.labor, Decimal::ZERO);
+        assert_eq!(loaded.resources[0].consumption, Decimal::new(25, 2));
+        assert_eq!(db.search_normatives("РАБОТА", None, 10).unwrap().len(), 1);
+
+        db.insert_normatives_bulk(&sample_normatives()).unwrap();
+        let stored: String = db
+            .conn
+            .query_row(
+                "SELECT typeof(direct_cost) FROM normatives WHERE code = 'ФЕР01-01-002-01'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(stored, "text");
+    }
+
+    #[test]
+    fn test_estimate_crud_roundtrip() {
+        use denidom_core::{EstimateItem, EstimateSection, MeasureUnit};
+        use rust_decimal::Decimal;
+
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        let mut estimate = Estimate::new("Ремонт кровли".to_string(), "Школа № 5".to_string());
+        estimate.number = "12".to_string();
+        let mut section = EstimateSection::new(1, "Кровля".to_string());
+        section.add_item(EstimateItem::new(
+            "ФЕР12-01-002-01".to_string(),
+            "Устройство кровель".to_string(),
+            MeasureUnit::SquareMeter100,
+            Decimal::new(25, 1),
+        ));
+        estimate.sections.push(section);
+        let other = Estimate::new("Смета".to_string(), "Объект".to_string());
+
+        db.save_estimate(&estimate, "ivanov").unwrap();
+        db.save_estimate(&other, "ivanov").unwrap();
+
+        let stored = db.get_estimate(estimate.id).unwrap().unwrap();
+        assert_eq!(serde_json::to_value(&stored).unwrap(), serde_json::to_value(&estimate).unwrap());
+        assert_eq!(stored.created_at, estimate.created_at);
+        assert_eq!(stored.updated_at, estimate.updated_at);
+        assert_eq!(stored.sections[0].items[0].code, "ФЕР12-01-002-01");
+
+        let (number, object): (String, String) = db
+            .conn
+            .query_row(
+                "SELECT number, object FROM estimates WHERE id = ?1",
+                [estimate.id.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((number.as_str(), object.as_str()), ("12", "Школа № 5"));
+
+        let ids: Vec<Uuid> = db.list_estimates().unwrap().iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![estimate.id, other.id]);
+
+        assert!(db.delete_estimate(estimate.id, "ivanov").unwrap());
+        assert!(db.get_estimate(estimate.id).unwrap().is_none());
+        assert_eq!(db.list_estimates().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_save_estimate_as_bincode() {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        estimate.coefficients.apply_winter(4, "Земляные работы").unwrap();
+
+        db.save_estimate_as(&estimate, StorageFormat::Bincode, "ivanov").unwrap();
+        let (data, data_bin): (String, Option<Vec<u8>>) = db
+            .conn
+            .query_row("SELECT data, data_bin FROM estimates", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert!(data.is_empty() && data_bin.is_some());
+
+        let stored = db.get_estimate(estimate.id).unwrap().unwrap();
+        assert_eq!(serde_json::to_value(&stored).unwrap(), serde_json::to_value(&estimate).unwrap());
+        assert_eq!(db.audit_log(estimate.id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_update_status() {
+        use crate::estimates::load_estimate;
+
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        let estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        db.save_estimate(&estimate, "test").unwrap();
+
+        db.update_status(estimate.id, EstimateStatus::InProgress, "test").unwrap();
+        let stored = load_estimate(&db.conn, estimate.id).unwrap().unwrap();
+        assert_eq!(stored.status, EstimateStatus::InProgress);
+
+        let err = db.update_status(estimate.id, EstimateStatus::Archived, "test").unwrap_err();
+        assert!(matches!(err, DbError::Validation(_)));
+        let stored = load_estimate(&db.conn, estimate.id).unwrap().unwrap();
+        assert_eq!(stored.status, EstimateStatus::InProgress);
+
+        let err = db.update_status(Uuid::new_v4(), EstimateStatus::InProgress, "test").unwrap_err();
+        assert!(matches!(err, DbError::NotFound(_)));
+    }
+
+    #[test]
+    fn test_audit_log_records_changes_in_order() {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        let estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+
+        db.save_estimate(&estimate, "ivanov").unwrap();
+        db.update_status(estimate.id, EstimateStatus::InProgress, "petrov").unwrap();
+
+        let log = db.audit_log(estimate.id).unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!((log[0].action.as_str(), log[0].actor.as_str()), ("save", "ivanov"));
+        assert_eq!((log[1].action.as_str(), log[1].actor.as_str()), ("status", "petrov"));
+        assert_eq!(log[1].detail.as_deref(), Some("draft -> in_progress"));
+        assert!(log[0].changed_at <= log[1].changed_at);
+
+        // A rejected transition leaves no audit row
+        assert!(db.update_status(estimate.id, EstimateStatus::Archived, "petrov").is_err());
+        assert!(db.delete_estimate(estimate.id, "sidorov").unwrap());
+        let log = db.audit_log(estimate.id).unwrap();
+        assert_eq!(log.len(), 3);
+        assert_eq!(log[2].action, "delete");
+    }
+
+    #[test]
+    fn test_snapshot_and_restore_estimate() {
+        use crate::estimates::load_estimate;
+        use denidom_core::{EstimateSection, EstimateItem, MeasureUnit};
+        use rust_decimal::Decimal;
+
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+        let mut estimate = Estimate::new("Смета".to_string(), "Объект".to_string());
+        let mut section = EstimateSection::new(1, "Земляные работы".to_string());
+        section.add_item(EstimateItem::new(
+            "ФЕР01-01-001-01".to_string(),
+            "Разработка грунта".to_string(),
+            MeasureUnit::CubicMeter1000,
+            Decimal::new(25, 1),
+        ));
+        estimate.sections.push(section);
+        db.save_estimate(&estimate, "ivanov").unwrap();
+
+        assert_eq!(db.snapshot_estimate(estimate.id).unwrap(), 1);
+        let snapshot = load_estimate(&db.conn, estimate.id).unwrap().unwrap();
+
+        estimate.name = "Смета (изменена)".to_string();
+        estimate.sections.clear();
+        db.save_estimate(&estimate, "ivanov").unwrap();
+        assert_eq!(db.snapshot_estimate(estimate.id).unwrap(), 2);
+
+        db.restore_estimate(estimate.id, 1, "petrov").unwrap();
+        let restored = load_estimate(&db.conn, estimate.id).unwrap().unwrap();
+        assert_eq!(serde_json::to_value(&restored).unwrap(), serde_json::to_value(&snapshot).unwrap());
+
+        let log = db.audit_log(estimate.id).unwrap();
+        assert_eq!(log.last().unwrap().action, "restore");
+        assert_eq!(log.last().unwrap().detail.as_deref(), Some("version 1"));
+
+        let err = db.restore_estimate(estimate.id, 7, "petrov").unwrap_err();
+        assert!(matches!(err, DbError::NotFound(_)));
+        assert!(matches!(db.snapshot_estimate(Uuid::new_v4()), Err(DbError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_transaction_rolls_back_on_error() {
+        let db = Database::open_in_memory().unwrap();
+        db.migrate().unwrap();
+
+        let result: DbResult<()> = db.transaction(|tx| {
+            insert_normatives(tx, &sample_normatives())?;
+            Err(DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(normatives_count(&db), 0);
+    }
 }
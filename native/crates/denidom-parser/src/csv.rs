@@ -0,0 +1,112 @@
+//! CSV helpers shared by the importers and exporters
+//!
+//! Russian CSV exports use `;` as the delimiter and `,` as the decimal separator.
+
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+use denidom_core::{DeniDomError, ParseContext};
+
+/// Default delimiter for Russian CSV exports
+pub(crate) const DELIMITER: char = ';';
+
+/// Split a CSV line into trimmed fields, honoring double quotes
+pub(crate) fn split_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => {
+                fields.push(field.trim().to_string());
+                field.clear();
+            }
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+
+    fields
+}
+
+/// Join fields into a CSV line, quoting fields that need it
+pub(crate) fn join_line<S: AsRef<str>>(fields: &[S], delimiter: char) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            let field = field.as_ref();
+            if field.contains(delimiter) || field.contains('"') || field.contains('\n') {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(&delimiter.to_string())
+}
+
+/// Parse a decimal that may use a comma separator and space grouping.
+/// Empty fields parse as zero.
+pub(crate) fn parse_decimal(field: &str) -> Option<Decimal> {
+    let cleaned: String = field
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| if c == ',' { '.' } else { c })
+        .collect();
+
+    if cleaned.is_empty() {
+        return Some(Decimal::ZERO);
+    }
+
+    Decimal::from_str(&cleaned).ok()
+}
+
+/// Build a located parse error for a CSV row
+pub(crate) fn row_error(
+    message: impl Into<String>,
+    line_no: usize,
+    column: Option<usize>,
+    line: &str,
+) -> DeniDomError {
+    let mut context = ParseContext::at_line(line_no).with_snippet(line);
+    if let Some(column) = column {
+        context = context.with_column(column);
+    }
+
+    DeniDomError::ParseAt {
+        message: message.into(),
+        context,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_line_quotes() {
+        let fields = split_line(r#"ФЕР01;"Грунт; ""тип"" 1";м3"#, DELIMITER);
+        assert_eq!(fields, vec!["ФЕР01", r#"Грунт; "тип" 1"#, "м3"]);
+    }
+
+    #[test]
+    fn test_join_line_roundtrip() {
+        let fields = vec!["ФЕР01", r#"Грунт; "тип" 1"#, "м3"];
+        let line = join_line(&fields, DELIMITER);
+        assert_eq!(split_line(&line, DELIMITER), fields);
+    }
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!(parse_decimal("1 234,56"), Some(Decimal::new(123456, 2)));
+        assert_eq!(parse_decimal(""), Some(Decimal::ZERO));
+        assert_eq!(parse_decimal("abc"), None);
+    }
+}
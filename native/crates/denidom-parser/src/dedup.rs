@@ -0,0 +1,46 @@
+//! Deduplication of imported normatives by code
+
+use std::collections::HashMap;
+
+use denidom_core::{DeniDomError, NormativeItem, Result};
+
+/// How to resolve rows sharing the same normative code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DedupPolicy {
+    /// Keep the first row for each code
+    KeepFirst,
+    /// Keep the last row for each code (revised rows follow base rows)
+    #[default]
+    KeepLast,
+    /// Reject the import on the first duplicate code
+    Error,
+}
+
+/// Remove duplicate codes according to `policy`
+///
+/// Items keep the position of the first occurrence of their code.
+pub fn dedup_normatives(items: Vec<NormativeItem>, policy: DedupPolicy) -> Result<Vec<NormativeItem>> {
+    let mut result: Vec<NormativeItem> = Vec::with_capacity(items.len());
+    let mut positions: HashMap<String, usize> = HashMap::with_capacity(items.len());
+
+    for item in items {
+        match positions.get(&item.code) {
+            None => {
+                positions.insert(item.code.clone(), result.len());
+                result.push(item);
+            }
+            Some(&pos) => match policy {
+                DedupPolicy::KeepFirst => {}
+                DedupPolicy::KeepLast => result[pos] = item,
+                DedupPolicy::Error => {
+                    return Err(DeniDomError::Validation(format!(
+                        "duplicate normative code '{}'",
+                        item.code
+                    )));
+                }
+            },
+        }
+    }
+
+    Ok(result)
+}
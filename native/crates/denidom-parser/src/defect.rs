@@ -0,0 +1,119 @@
+//! Defect List Parser - Дефектная ведомость
+//!
+//! Reads a defect list CSV (`наименование;объём;ед. изм.`) into unpriced
+//! estimate items. Codes are left empty and costs zeroed so the items can be
+//! matched against normatives later.
+
+use std::io::{BufRead, BufReader, Read};
+
+use denidom_core::{EstimateItem, MeasureUnit, Result};
+use rust_decimal::Decimal;
+
+use crate::csv::{parse_decimal, row_error, split_line, DELIMITER};
+use crate::encoding::{decode_to_utf8, SourceEncoding};
+use crate::units::{map_unit, UnitMap};
+use crate::warning::ImportWarning;
+
+/// Parse a defect list, discarding warnings
+pub fn parse_defect_list(reader: impl Read) -> Result<Vec<EstimateItem>> {
+    parse_defect_list_with_warnings(reader, None).map(|(items, _)| items)
+}
+
+/// Parse a defect list file, transcoding from `force_encoding` or the
+/// detected charset (UTF-8 or CP1251)
+pub fn parse_defect_list_file(
+    path: &str,
+    force_encoding: Option<SourceEncoding>,
+) -> Result<Vec<EstimateItem>> {
+    let bytes = std::fs::read(path)?;
+    parse_defect_list(decode_to_utf8(&bytes, force_encoding).as_bytes())
+}
+
+/// Parse a defect list, returning items and non-fatal warnings
+///
+/// Rows without a quantity are imported with zero quantity and reported.
+/// `unit_map` is consulted before the built-in unit spellings.
+pub fn parse_defect_list_with_warnings(
+    reader: impl Read,
+    unit_map: Option<&UnitMap>,
+) -> Result<(Vec<EstimateItem>, Vec<ImportWarning>)> {
+    let mut items = Vec::new();
+    let mut warnings = Vec::new();
+
+    for (idx, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        let line_no = idx + 1;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = split_line(&line, DELIMITER);
+        if line_no == 1 && fields[0].to_lowercase().starts_with("наименование") {
+            continue;
+        }
+
+        let name = &fields[0];
+        if name.is_empty() {
+            return Err(row_error("empty work name", line_no, Some(1), &line));
+        }
+
+        let quantity_field = fields.get(1).map(String::as_str).unwrap_or("");
+        let quantity = if quantity_field.is_empty() {
+            warnings.push(ImportWarning::new(
+                line_no,
+                format!("no quantity for '{}', defaulting to 0", name),
+            ));
+            Decimal::ZERO
+        } else {
+            parse_decimal(quantity_field).ok_or_else(|| {
+                row_error(
+                    format!("invalid quantity '{}'", quantity_field),
+                    line_no,
+                    Some(2),
+                    &line,
+                )
+            })?
+        };
+
+        let unit = match fields.get(2).map(String::as_str) {
+            Some(unit) if !unit.is_empty() => map_unit(unit, unit_map),
+            _ => MeasureUnit::default(),
+        };
+
+        let mut item = EstimateItem::new(String::new(), name.clone(), unit, quantity);
+        item.position = items.len() as u32 + 1;
+        items.push(item);
+    }
+
+    Ok((items, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_defect_list() {
+        let data = "\
+Наименование;Объём;Ед. изм.
+Демонтаж штукатурки;45,5;м2
+Замена оконных блоков;3;шт
+Ремонт кровли;;100 м2
+";
+        let (items, warnings) = parse_defect_list_with_warnings(data.as_bytes(), None).unwrap();
+
+        assert_eq!(items.len(), 3);
+        assert!(items.iter().all(|i| i.code.is_empty()));
+        assert_eq!(items[0].quantity, Decimal::new(455, 1));
+        assert_eq!(items[0].unit, MeasureUnit::SquareMeter);
+        assert_eq!(items[1].unit, MeasureUnit::Unit);
+        assert_eq!(items[1].unit_costs.direct, Decimal::ZERO);
+        assert_eq!(items[2].quantity, Decimal::ZERO);
+        assert_eq!(items[2].unit, MeasureUnit::SquareMeter100);
+        assert_eq!(items[2].position, 3);
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].line, 4);
+    }
+}
@@ -0,0 +1,67 @@
+//! Source charset detection
+//!
+//! Legacy ФЕР and defect-list exports are Windows-1251 encoded. File-based
+//! entry points detect the charset (UTF-8 BOM, valid UTF-8, then a CP1251
+//! heuristic) and transcode to UTF-8 before parsing.
+
+use encoding_rs::{UTF_8, WINDOWS_1251};
+
+/// Кодировка исходного файла
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceEncoding {
+    /// UTF-8 (с BOM или без)
+    Utf8,
+    /// Windows-1251
+    Cp1251,
+}
+
+/// Share of high bytes that must be CP1251 letters to pick CP1251
+const CP1251_LETTER_SHARE: f64 = 0.9;
+
+/// Detect the charset of raw file contents
+///
+/// A UTF-8 BOM or valid UTF-8 means UTF-8. Otherwise the data is CP1251 if
+/// almost all bytes above 0x7F are Cyrillic letters (0xC0–0xFF, Ё, ё) or №.
+pub fn detect_encoding(bytes: &[u8]) -> SourceEncoding {
+    if bytes.starts_with(b"\xEF\xBB\xBF") || std::str::from_utf8(bytes).is_ok() {
+        return SourceEncoding::Utf8;
+    }
+
+    let high = bytes.iter().filter(|&&b| b >= 0x80).count();
+    let letters = bytes
+        .iter()
+        .filter(|&&b| b >= 0xC0 || matches!(b, 0xA8 | 0xB8 | 0xB9))
+        .count();
+    if high > 0 && letters as f64 / high as f64 >= CP1251_LETTER_SHARE {
+        SourceEncoding::Cp1251
+    } else {
+        SourceEncoding::Utf8
+    }
+}
+
+/// Transcode raw file contents to UTF-8, using `force_encoding` instead of
+/// detection when set. A UTF-8 BOM is stripped; invalid sequences become U+FFFD.
+pub fn decode_to_utf8(bytes: &[u8], force_encoding: Option<SourceEncoding>) -> String {
+    let encoding = match force_encoding.unwrap_or_else(|| detect_encoding(bytes)) {
+        SourceEncoding::Utf8 => UTF_8,
+        SourceEncoding::Cp1251 => WINDOWS_1251,
+    };
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_encoding() {
+        let (cp1251, _, _) = WINDOWS_1251.encode("Разработка грунта; 1000 м3");
+
+        assert_eq!(detect_encoding(&cp1251), SourceEncoding::Cp1251);
+        assert_eq!(detect_encoding("Разработка".as_bytes()), SourceEncoding::Utf8);
+        assert_eq!(detect_encoding(b"\xEF\xBB\xBFcode"), SourceEncoding::Utf8);
+        assert_eq!(decode_to_utf8(b"\xEF\xBB\xBFcode", None), "code");
+        assert_eq!(decode_to_utf8(&cp1251, Some(SourceEncoding::Cp1251)), "Разработка грунта; 1000 м3");
+    }
+}
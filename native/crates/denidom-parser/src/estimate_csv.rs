@@ -0,0 +1,492 @@
+//! Estimate CSV interchange
+//!
+//! Exports an estimate to a semicolon-separated document and reads it back.
+//! The document records the calculation settings and the applied
+//! coefficients (with justifications) so the importing side can reconstruct
+//! and audit them:
+//!
+//! ```text
+//! #СМЕТА;номер;наименование;объект
+//! #РАСЧЁТ
+//! overhead;0.12;FromLaborCost
+//! profit;0.08;FromLaborCost
+//! contingency;0.02
+//! taxation;Usn;1.1
+//! #КОЭФФИЦИЕНТЫ
+//! index;8.50
+//! winter;1.03
+//! winter_zone;4;Земляные работы
+//! custom;наименование;значение;обоснование;1
+//! #ПОЗИЦИИ
+//! раздел;наименование раздела;позиция;шифр;наименование;ед. изм.;кол-во;ПЗ;ОЗП;ЗПМ;материалы;ЭМ;коэф. раздела;коэф. позиции;метки;формула
+//! ```
+//!
+//! Tags are joined with `|`. The last four item columns are optional.
+
+use std::io::{BufRead, BufReader, Read};
+
+use denidom_core::{
+    Coefficients, CustomCoefficient, Estimate, EstimateItem, EstimateSection, MeasureUnit, Result,
+    UnitCosts,
+};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::csv::{join_line, parse_decimal, row_error, split_line, DELIMITER};
+
+const ESTIMATE_MARKER: &str = "#СМЕТА";
+const SETTINGS_MARKER: &str = "#РАСЧЁТ";
+const COEFFICIENTS_MARKER: &str = "#КОЭФФИЦИЕНТЫ";
+const ITEMS_MARKER: &str = "#ПОЗИЦИИ";
+
+/// Minimum number of columns in an item row
+const ITEM_COLUMNS: usize = 12;
+
+/// Separator of item tags within their column
+const TAG_SEPARATOR: char = '|';
+
+/// Export an estimate to the CSV interchange format
+pub fn export_estimate_csv(estimate: &Estimate) -> String {
+    let optional = |value: Option<Decimal>| value.map(|v| v.to_string()).unwrap_or_default();
+    let mut lines = vec![join_line(
+        &[ESTIMATE_MARKER, &estimate.number, &estimate.name, &estimate.object],
+        DELIMITER,
+    )];
+
+    lines.push(SETTINGS_MARKER.to_string());
+    lines.extend([
+        join_line(
+            &[
+                "overhead".to_string(),
+                estimate.overhead.rate.to_string(),
+                variant_name(&estimate.overhead.method),
+            ],
+            DELIMITER,
+        ),
+        join_line(
+            &[
+                "profit".to_string(),
+                estimate.profit.rate.to_string(),
+                variant_name(&estimate.profit.method),
+            ],
+            DELIMITER,
+        ),
+        join_line(
+            &[
+                "contingency".to_string(),
+                estimate.contingency_rate.to_string(),
+            ],
+            DELIMITER,
+        ),
+        join_line(
+            &[
+                "taxation".to_string(),
+                variant_name(&estimate.taxation),
+                optional(estimate.usn_vat_compensation),
+            ],
+            DELIMITER,
+        ),
+    ]);
+
+    lines.push(COEFFICIENTS_MARKER.to_string());
+    lines.extend(export_coefficients(&estimate.coefficients));
+
+    lines.push(ITEMS_MARKER.to_string());
+    for section in &estimate.sections {
+        for item in &section.items {
+            let costs = &item.unit_costs;
+            lines.push(join_line(
+                &[
+                    section.number.to_string(),
+                    section.name.clone(),
+                    item.position.to_string(),
+                    item.code.clone(),
+                    item.name.clone(),
+                    item.unit.abbreviation().to_string(),
+                    item.quantity.to_string(),
+                    costs.direct.to_string(),
+                    costs.labor.to_string(),
+                    costs.machine_operator.to_string(),
+                    costs.materials.to_string(),
+                    costs.machines.to_string(),
+                    optional(section.coefficient),
+                    optional(item.coefficient),
+                    item.tags.join(&TAG_SEPARATOR.to_string()),
+                    item.quantity_expr
+                        .as_ref()
+                        .map(|expr| expr.source.clone())
+                        .unwrap_or_default(),
+                ],
+                DELIMITER,
+            ));
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn export_coefficients(coefficients: &Coefficients) -> Vec<String> {
+    let optional = |value: Option<Decimal>| value.map(|v| v.to_string()).unwrap_or_default();
+
+    let mut lines = vec![
+        join_line(&["index".to_string(), coefficients.index.to_string()], DELIMITER),
+        join_line(&["winter".to_string(), optional(coefficients.winter)], DELIMITER),
+        join_line(&["cramped".to_string(), optional(coefficients.cramped)], DELIMITER),
+        join_line(&["regional".to_string(), optional(coefficients.regional)], DELIMITER),
+        join_line(&["height".to_string(), optional(coefficients.height)], DELIMITER),
+    ];
+
+    if let Some(winter) = &coefficients.winter_components {
+        lines.push(join_line(
+            &[
+                "winter_zone".to_string(),
+                winter.zone.to_string(),
+                winter.work_section.clone(),
+            ],
+            DELIMITER,
+        ));
+    }
+
+    for custom in &coefficients.custom {
+        lines.push(join_line(
+            &[
+                "custom".to_string(),
+                custom.name.clone(),
+                custom.value.to_string(),
+                custom.justification.clone().unwrap_or_default(),
+                if custom.is_active { "1" } else { "0" }.to_string(),
+            ],
+            DELIMITER,
+        ));
+    }
+
+    lines
+}
+
+/// Serialized name of a unit enum variant, e.g. `FromLaborCost`
+fn variant_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+/// Unit enum variant named in `column`
+fn variant<T: DeserializeOwned>(
+    fields: &[String],
+    column: usize,
+    line_no: usize,
+    line: &str,
+) -> Result<T> {
+    let field = fields.get(column).map(String::as_str).unwrap_or("");
+    serde_json::from_value(serde_json::Value::String(field.to_string())).map_err(|_| {
+        row_error(
+            format!("unknown value '{}'", field),
+            line_no,
+            Some(column + 1),
+            line,
+        )
+    })
+}
+
+#[derive(PartialEq)]
+enum Block {
+    Header,
+    Settings,
+    Coefficients,
+    Items,
+}
+
+/// Parse an estimate from the CSV interchange format
+pub fn parse_estimate_csv(reader: impl Read) -> Result<Estimate> {
+    let mut estimate = Estimate::new(String::new(), String::new());
+    let mut block = Block::Header;
+
+    for (idx, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        let line_no = idx + 1;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = split_line(&line, DELIMITER);
+        match fields[0].as_str() {
+            ESTIMATE_MARKER => {
+                let field = |i: usize| fields.get(i).cloned().unwrap_or_default();
+                estimate.number = field(1);
+                estimate.name = field(2);
+                estimate.object = field(3);
+                continue;
+            }
+            SETTINGS_MARKER => {
+                block = Block::Settings;
+                continue;
+            }
+            COEFFICIENTS_MARKER => {
+                block = Block::Coefficients;
+                continue;
+            }
+            ITEMS_MARKER => {
+                block = Block::Items;
+                continue;
+            }
+            _ => {}
+        }
+
+        let number = |column: usize| {
+            let field = fields.get(column).map(String::as_str).unwrap_or("");
+            parse_decimal(field).ok_or_else(|| {
+                row_error(
+                    format!("invalid number '{}'", field),
+                    line_no,
+                    Some(column + 1),
+                    &line,
+                )
+            })
+        };
+        let optional = |column: usize| -> Result<Option<Decimal>> {
+            match fields.get(column).map(String::as_str) {
+                None | Some("") => Ok(None),
+                Some(_) => number(column).map(Some),
+            }
+        };
+        let text = |column: usize| {
+            fields
+                .get(column)
+                .map(String::as_str)
+                .filter(|s| !s.is_empty())
+        };
+
+        match block {
+            Block::Header => {
+                return Err(row_error("data before a block marker", line_no, Some(1), &line));
+            }
+            Block::Settings => match fields[0].as_str() {
+                "overhead" => {
+                    estimate.overhead.rate = number(1)?;
+                    estimate.overhead.method = variant(&fields, 2, line_no, &line)?;
+                }
+                "profit" => {
+                    estimate.profit.rate = number(1)?;
+                    estimate.profit.method = variant(&fields, 2, line_no, &line)?;
+                }
+                "contingency" => estimate.contingency_rate = number(1)?,
+                "taxation" => {
+                    estimate.taxation = variant(&fields, 1, line_no, &line)?;
+                    estimate.usn_vat_compensation = optional(2)?;
+                }
+                other => {
+                    return Err(row_error(
+                        format!("unknown setting '{}'", other),
+                        line_no,
+                        Some(1),
+                        &line,
+                    ));
+                }
+            },
+            Block::Coefficients => {
+                let coefficients = &mut estimate.coefficients;
+                match fields[0].as_str() {
+                    "index" => coefficients.index = number(1)?,
+                    "winter" => coefficients.winter = optional(1)?,
+                    "cramped" => coefficients.cramped = optional(1)?,
+                    "regional" => coefficients.regional = optional(1)?,
+                    "height" => coefficients.height = optional(1)?,
+                    "winter_zone" => {
+                        let zone = fields
+                            .get(1)
+                            .and_then(|zone| zone.parse().ok())
+                            .ok_or_else(|| {
+                                row_error("invalid winter zone", line_no, Some(2), &line)
+                            })?;
+                        coefficients
+                            .apply_winter(zone, text(2).unwrap_or(""))
+                            .map_err(|e| row_error(e.to_string(), line_no, Some(3), &line))?;
+                    }
+                    "custom" => {
+                        let justification = fields.get(3).cloned().unwrap_or_default();
+                        coefficients.custom.push(CustomCoefficient {
+                            name: fields.get(1).cloned().unwrap_or_default(),
+                            value: number(2)?,
+                            justification: (!justification.is_empty()).then_some(justification),
+                            is_active: fields.get(4).map(String::as_str) != Some("0"),
+                        });
+                    }
+                    other => {
+                        return Err(row_error(
+                            format!("unknown coefficient '{}'", other),
+                            line_no,
+                            Some(1),
+                            &line,
+                        ));
+                    }
+                }
+            }
+            Block::Items => {
+                if fields.len() < ITEM_COLUMNS {
+                    return Err(row_error(
+                        format!("expected {} columns, found {}", ITEM_COLUMNS, fields.len()),
+                        line_no,
+                        None,
+                        &line,
+                    ));
+                }
+
+                let section_number: u32 = fields[0].parse().map_err(|_| {
+                    row_error("invalid section number", line_no, Some(1), &line)
+                })?;
+                let is_same_section = estimate
+                    .sections
+                    .last()
+                    .map(|s| s.number == section_number && s.name == fields[1])
+                    .unwrap_or(false);
+                if !is_same_section {
+                    let mut section = EstimateSection::new(section_number, fields[1].clone());
+                    section.coefficient = optional(12)?;
+                    estimate.sections.push(section);
+                }
+
+                let mut item = EstimateItem::new(
+                    fields[3].clone(),
+                    fields[4].clone(),
+                    MeasureUnit::from(fields[5].as_str()),
+                    number(6)?,
+                );
+                item.position = fields[2].parse().unwrap_or(0);
+                item.unit_costs =
+                    UnitCosts::new(number(7)?, number(8)?, number(9)?, number(10)?, number(11)?);
+                item.coefficient = optional(13)?;
+                for tag in text(14)
+                    .unwrap_or("")
+                    .split(TAG_SEPARATOR)
+                    .filter(|t| !t.is_empty())
+                {
+                    item.add_tag(tag);
+                }
+                if let Some(formula) = text(15) {
+                    item.set_quantity_formula(formula)
+                        .map_err(|e| row_error(e.to_string(), line_no, Some(16), &line))?;
+                }
+
+                if let Some(section) = estimate.sections.last_mut() {
+                    section.add_item(item);
+                }
+            }
+        }
+    }
+
+    Ok(estimate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use denidom_core::{calculate_estimate_totals, OverheadMethod, TaxationRegime};
+
+    fn sample_estimate() -> Estimate {
+        let mut estimate = Estimate::new("Ремонт кровли".to_string(), "г. Москва".to_string());
+        estimate.number = "ЛС-01".to_string();
+        estimate.coefficients.index = Decimal::new(850, 2);
+        estimate.coefficients.winter = Some(Decimal::new(103, 2));
+        estimate.coefficients.add_custom(
+            "Работа в ночное время".to_string(),
+            Decimal::new(120, 2),
+            Some("Приказ №123; п. 4.2".to_string()),
+        );
+
+        let mut section = EstimateSection::new(1, "Кровля".to_string());
+        let mut item = EstimateItem::new(
+            "ФЕР12-01-002-09".to_string(),
+            "Устройство кровли".to_string(),
+            MeasureUnit::SquareMeter100,
+            Decimal::new(125, 2),
+        );
+        item.position = 1;
+        item.unit_costs = UnitCosts::new(
+            Decimal::new(1000, 0),
+            Decimal::new(300, 0),
+            Decimal::new(100, 0),
+            Decimal::new(500, 0),
+            Decimal::new(100, 0),
+        );
+        section.add_item(item);
+        estimate.sections.push(section);
+        estimate
+    }
+
+    #[test]
+    fn test_coefficients_roundtrip() {
+        let estimate = sample_estimate();
+        let exported = export_estimate_csv(&estimate);
+
+        assert!(exported.contains("Приказ №123; п. 4.2"));
+
+        let imported = parse_estimate_csv(exported.as_bytes()).unwrap();
+        let custom = &imported.coefficients.custom[0];
+
+        assert_eq!(imported.number, "ЛС-01");
+        assert_eq!(imported.coefficients.index, Decimal::new(850, 2));
+        assert_eq!(imported.coefficients.winter, Some(Decimal::new(103, 2)));
+        assert_eq!(imported.coefficients.cramped, None);
+        assert_eq!(custom.justification.as_deref(), Some("Приказ №123; п. 4.2"));
+        assert!(custom.is_active);
+        assert_eq!(imported.coefficients.combined(), estimate.coefficients.combined());
+
+        assert_eq!(imported.sections.len(), 1);
+        let item = &imported.sections[0].items[0];
+        assert_eq!(item.unit, MeasureUnit::SquareMeter100);
+        assert_eq!(item.quantity, Decimal::new(125, 2));
+        assert_eq!(item.unit_costs.materials, Decimal::new(500, 0));
+    }
+
+    #[test]
+    fn test_roundtrip_keeps_totals() {
+        let mut estimate = sample_estimate();
+        estimate.overhead.method = OverheadMethod::FromDirectCost;
+        estimate.contingency_rate = Decimal::new(2, 2);
+        estimate.taxation = TaxationRegime::Usn;
+        estimate.usn_vat_compensation = Some(Decimal::new(11, 1));
+        estimate
+            .coefficients
+            .apply_winter(4, "Земляные работы")
+            .unwrap();
+        estimate.sections[0].coefficient = Some(Decimal::new(115, 2));
+        let item = &mut estimate.sections[0].items[0];
+        item.coefficient = Some(Decimal::new(12, 1));
+        item.add_tag("под вопросом");
+        item.add_tag("утверждено");
+        item.set_quantity_formula("12,5 * 2 / 20").unwrap();
+
+        let imported = parse_estimate_csv(export_estimate_csv(&estimate).as_bytes()).unwrap();
+
+        assert_eq!(imported.taxation, TaxationRegime::Usn);
+        assert_eq!(imported.usn_vat_compensation, Some(Decimal::new(11, 1)));
+        assert_eq!(imported.contingency_rate, Decimal::new(2, 2));
+        assert_eq!(imported.overhead.method, OverheadMethod::FromDirectCost);
+        let winter = imported.coefficients.winter_components.as_ref().unwrap();
+        assert_eq!(
+            (winter.zone, winter.work_section.as_str()),
+            (4, "Земляные работы")
+        );
+        assert_eq!(imported.sections[0].coefficient, Some(Decimal::new(115, 2)));
+        let (original, item) = (
+            &estimate.sections[0].items[0],
+            &imported.sections[0].items[0],
+        );
+        assert_eq!(item.coefficient, original.coefficient);
+        assert_eq!(item.tags, original.tags);
+        assert_eq!(item.quantity_expr, original.quantity_expr);
+
+        let (expected, actual) = (
+            calculate_estimate_totals(&estimate),
+            calculate_estimate_totals(&imported),
+        );
+        assert_eq!(actual.direct_costs, expected.direct_costs);
+        assert_eq!(actual.overhead, expected.overhead);
+        assert_eq!(actual.contingency, expected.contingency);
+        assert_eq!(actual.vat, expected.vat);
+        assert_eq!(actual.total, expected.total);
+    }
+}
@@ -1,4 +1,161 @@
 //! Excel Parser
+//!
+//! Reads estimate items from the first worksheet of an `.xlsx` file with
+//! columns `шифр | наименование | ед. изм. | количество | ПЗ | ОЗП | ЗПМ |
+//! материалы | ЭМ`. Rows without a code (section headers, blank lines) are
+//! skipped; a first row starting with "шифр" or "code" is a header.
 
-// Placeholder for future implementation
-pub struct ExcelParser;
+use std::path::Path;
+
+use calamine::{open_workbook, Data, Range, Reader, Xlsx};
+use denidom_core::{DeniDomError, EstimateItem, MeasureUnit, ParseContext, Result, UnitCosts};
+use rust_decimal::Decimal;
+
+use crate::csv::parse_decimal;
+
+/// Parse estimate items from the first worksheet of an `.xlsx` file
+///
+/// Numeric cells may hold numbers or text with a decimal comma; empty
+/// numeric cells are zero. A malformed cell fails with
+/// [`DeniDomError::ParseAt`] carrying its one-based row and column, with
+/// the address (e.g. `F3`) in the message and the cell text as snippet.
+pub fn parse_estimate_xlsx(path: &Path) -> Result<Vec<EstimateItem>> {
+    let mut workbook: Xlsx<_> = open_workbook(path).map_err(excel_error)?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| DeniDomError::Parse("workbook has no worksheets".to_string()))?
+        .map_err(excel_error)?;
+
+    parse_range(&range)
+}
+
+fn parse_range(range: &Range<Data>) -> Result<Vec<EstimateItem>> {
+    let (first_row, first_col) = range.start().unwrap_or((0, 0));
+    let mut items = Vec::new();
+
+    for (idx, row) in range.rows().enumerate() {
+        let row_no = first_row as usize + idx + 1;
+        let cell = |column: usize| row.get(column).unwrap_or(&Data::Empty);
+
+        let code = text(cell(0));
+        if code.is_empty() {
+            continue;
+        }
+        if idx == 0 && is_header(&code) {
+            continue;
+        }
+
+        let number = |column: usize| {
+            decimal(cell(column)).ok_or_else(|| {
+                let value = text(cell(column));
+                let sheet_column = first_col as usize + column;
+                DeniDomError::ParseAt {
+                    message: format!(
+                        "invalid number '{}' in cell {}",
+                        value,
+                        cell_address(row_no, sheet_column)
+                    ),
+                    context: ParseContext::at_line(row_no)
+                        .with_column(sheet_column + 1)
+                        .with_snippet(value),
+                }
+            })
+        };
+
+        let unit = text(cell(2));
+        let mut item = EstimateItem::new(
+            code,
+            text(cell(1)),
+            MeasureUnit::from(unit.as_str()),
+            number(3)?,
+        );
+        item.unit_costs = UnitCosts::new(number(4)?, number(5)?, number(6)?, number(7)?, number(8)?);
+        item.position = items.len() as u32 + 1;
+        items.push(item);
+    }
+
+    Ok(items)
+}
+
+fn excel_error(err: impl std::fmt::Display) -> DeniDomError {
+    DeniDomError::Parse(format!("Excel error: {}", err))
+}
+
+fn is_header(code: &str) -> bool {
+    let code = code.to_lowercase();
+    code == "шифр" || code == "code"
+}
+
+/// Trimmed text of a cell; numbers are formatted as-is
+fn text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::String(s) => s.trim().to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn decimal(cell: &Data) -> Option<Decimal> {
+    match cell {
+        Data::Empty => Some(Decimal::ZERO),
+        Data::Int(value) => Some(Decimal::from(*value)),
+        Data::Float(value) => parse_decimal(&value.to_string()),
+        Data::String(s) => parse_decimal(s),
+        _ => None,
+    }
+}
+
+/// A1-style address of a zero-based column and one-based row
+fn cell_address(row_no: usize, column: usize) -> String {
+    let mut letters = Vec::new();
+    let mut n = column + 1;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        letters.push(b'A' + rem as u8);
+        n = (n - 1) / 26;
+    }
+    letters.reverse();
+    format!("{}{}", String::from_utf8_lossy(&letters), row_no)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+    }
+
+    #[test]
+    fn test_parse_estimate_xlsx() {
+        let items = parse_estimate_xlsx(&fixture("estimate.xlsx")).unwrap();
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].code, "ФЕР01-01-001-01");
+        assert_eq!(items[0].unit, MeasureUnit::CubicMeter1000);
+        assert_eq!(items[0].quantity, Decimal::new(25, 1));
+        assert_eq!(items[0].unit_costs.machines, Decimal::new(3500, 0));
+        assert_eq!(items[1].quantity, Decimal::new(125, 1));
+        assert_eq!(items[1].unit_costs.direct, Decimal::new(18005, 1));
+        assert_eq!(items[2].position, 3);
+    }
+
+    #[test]
+    fn test_malformed_cell_names_address() {
+        let err = parse_estimate_xlsx(&fixture("estimate_bad_cell.xlsx")).unwrap_err();
+        match err {
+            DeniDomError::ParseAt { context, .. } => {
+                assert_eq!(context.line, Some(2));
+                assert_eq!(context.column, Some(6));
+                assert_eq!(context.snippet.as_deref(), Some("много"));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cell_address() {
+        assert_eq!(cell_address(1, 0), "A1");
+        assert_eq!(cell_address(12, 27), "AB12");
+    }
+}
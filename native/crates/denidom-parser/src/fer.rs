@@ -1,6 +1,491 @@
 //! FER Parser - Federal Unit Rates
+//!
+//! Reads ФЕР normatives from a semicolon-separated CSV export with columns:
+//! `шифр;наименование;ед. изм.;ПЗ;ОЗП;ЗПМ;материалы;ЭМ[;затраты труда;маш-ч]`
+//!
+//! Some exports combine ОЗП and ЗПМ into one column; a header naming the
+//! fifth column "ФОТ" switches to the layout
+//! `шифр;наименование;ед. изм.;ПЗ;ФОТ;материалы;ЭМ[;затраты труда;маш-ч]`.
 
-// Placeholder for future implementation
-pub fn parse_fer_file(_path: &str) -> Result<Vec<()>, Box<dyn std::error::Error>> {
-    Ok(vec![])
+use std::io::{BufRead, BufReader, Read};
+
+use denidom_core::{DeniDomError, NormativeBase, NormativeItem, Result, UnitCosts};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+
+use crate::csv::{parse_decimal, row_error, split_line, DELIMITER};
+use crate::dedup::{dedup_normatives, DedupPolicy};
+use crate::encoding::{decode_to_utf8, SourceEncoding};
+use crate::filter::{passes, CodeFilter};
+use crate::units::{map_unit, UnitMap};
+
+/// Minimum number of columns in a FER row
+const MIN_COLUMNS: usize = 8;
+
+/// Minimum number of columns in a row with a combined ФОТ column
+const MIN_COLUMNS_COMBINED_FOT: usize = 7;
+
+/// Number of rows between progress callbacks
+pub const PROGRESS_INTERVAL: usize = 1000;
+
+/// Import progress callback: rows processed so far and the total, if known
+pub type ProgressFn<'a> = &'a mut dyn FnMut(usize, Option<usize>);
+
+/// Parse a FER CSV file, detecting UTF-8 or CP1251
+pub fn parse_fer_file(path: &str) -> Result<Vec<NormativeItem>> {
+    parse_fer_file_with_encoding(path, None)
+}
+
+/// Parse a FER CSV file, transcoding from `force_encoding` or the detected charset
+pub fn parse_fer_file_with_encoding(
+    path: &str,
+    force_encoding: Option<SourceEncoding>,
+) -> Result<Vec<NormativeItem>> {
+    let bytes = std::fs::read(path)?;
+    parse_fer_csv(decode_to_utf8(&bytes, force_encoding).as_bytes())
+}
+
+/// What to do with rows whose direct cost differs from the sum of components
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FixDirect {
+    /// Keep the source value and list the code as flagged
+    #[default]
+    Flag,
+    /// Recompute direct cost from ОЗП + ЗПМ + материалы + ЭМ
+    Fix,
+}
+
+/// Result of a FER import with the direct-cost cross-check report
+#[derive(Debug, Clone, Default)]
+pub struct FerImport {
+    /// Imported normatives
+    pub items: Vec<NormativeItem>,
+    /// Codes with inconsistent direct cost left as-is ([`FixDirect::Flag`])
+    pub flagged: Vec<String>,
+    /// Codes whose direct cost was recomputed ([`FixDirect::Fix`])
+    pub corrected: Vec<String>,
+}
+
+/// Настройки импорта ФЕР
+///
+/// Defaults keep inconsistent direct costs as in the source, resolve
+/// duplicates with the default [`DedupPolicy`], import every row, treat a
+/// combined ФОТ as labor and report no progress.
+#[derive(Default)]
+pub struct FerImportOptions<'a> {
+    /// Сверка прямых затрат с суммой составляющих
+    pub fix_direct: FixDirect,
+    /// Обработка повторяющихся шифров
+    pub dedup: DedupPolicy,
+    /// Отбор строк по шифру; остальные строки не разбираются
+    pub filter: Option<CodeFilter>,
+    /// Доли (ОЗП, ЗПМ) для распределения объединённого столбца ФОТ
+    pub fot_split: Option<(f64, f64)>,
+    /// Дополнительные написания единиц измерения, см. [`map_unit`]
+    pub unit_map: Option<&'a UnitMap>,
+    /// Обратный вызов с ходом импорта
+    pub progress: Option<ProgressFn<'a>>,
+}
+
+impl<'a> FerImportOptions<'a> {
+    /// Default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how inconsistent direct costs are handled
+    pub fn fix_direct(mut self, fix_direct: FixDirect) -> Self {
+        self.fix_direct = fix_direct;
+        self
+    }
+
+    /// Set how duplicate codes are resolved
+    pub fn dedup(mut self, dedup: DedupPolicy) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Import only rows whose code passes `filter`
+    pub fn filter(mut self, filter: CodeFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Distribute a combined ФОТ column by the given fractions, which must
+    /// be non-negative and add up to 1
+    pub fn fot_split(mut self, labor: f64, machine_operator: f64) -> Self {
+        self.fot_split = Some((labor, machine_operator));
+        self
+    }
+
+    /// Consult `unit_map` before the built-in unit spellings
+    pub fn unit_map(mut self, unit_map: &'a UnitMap) -> Self {
+        self.unit_map = Some(unit_map);
+        self
+    }
+
+    /// Report progress every [`PROGRESS_INTERVAL`] data rows and once more
+    /// with the final row count; the total is unknown while streaming
+    pub fn progress(mut self, progress: ProgressFn<'a>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+}
+
+/// Parse FER normatives from CSV data with default [`FerImportOptions`]
+///
+/// The first line is skipped when it is a header (starts with "шифр" or "code").
+pub fn parse_fer_csv(reader: impl Read) -> Result<Vec<NormativeItem>> {
+    parse_fer_csv_with_options(reader, FerImportOptions::default()).map(|import| import.items)
+}
+
+/// Parse FER normatives from CSV data, cross-checking direct cost against
+/// the sum of its components and resolving duplicate codes as set in
+/// `options`. Rows not passing the filter are skipped before they are
+/// parsed.
+pub fn parse_fer_csv_with_options(reader: impl Read, options: FerImportOptions) -> Result<FerImport> {
+    let FerImportOptions {
+        fix_direct,
+        dedup,
+        filter,
+        fot_split,
+        unit_map,
+        mut progress,
+    } = options;
+    let split = fot_fractions(fot_split)?;
+    let mut combined_fot = false;
+    let mut import = FerImport::default();
+    let mut rows = 0;
+
+    for (idx, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        let line_no = idx + 1;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = split_line(&line, DELIMITER);
+        if line_no == 1 && is_header(&fields[0]) {
+            combined_fot = fields
+                .get(4)
+                .is_some_and(|column| column.trim().to_lowercase() == "фот");
+            continue;
+        }
+
+        rows += 1;
+        if rows % PROGRESS_INTERVAL == 0 {
+            if let Some(report) = progress.as_mut() {
+                report(rows, None);
+            }
+        }
+
+        if !passes(filter.as_ref(), &fields[0], base_of(&fields[0])) {
+            continue;
+        }
+
+        let mut item = parse_row(&fields, line_no, &line, combined_fot.then_some(split), unit_map)?;
+        if !item.costs.validate() {
+            match fix_direct {
+                FixDirect::Flag => import.flagged.push(item.code.clone()),
+                FixDirect::Fix => {
+                    let costs = &mut item.costs;
+                    costs.direct =
+                        costs.labor + costs.machine_operator + costs.materials + costs.machines;
+                    import.corrected.push(item.code.clone());
+                }
+            }
+        }
+        import.items.push(item);
+    }
+
+    if let Some(report) = progress.as_mut() {
+        report(rows, None);
+    }
+
+    import.items = dedup_normatives(import.items, dedup)?;
+    Ok(import)
+}
+
+/// Validate a ФОТ split, defaulting to all labor
+fn fot_fractions(fot_split: Option<(f64, f64)>) -> Result<(Decimal, Decimal)> {
+    let Some((labor, machine_operator)) = fot_split else {
+        return Ok((Decimal::ONE, Decimal::ZERO));
+    };
+
+    let valid = labor >= 0.0 && machine_operator >= 0.0 && (labor + machine_operator - 1.0).abs() < 1e-9;
+    match (valid, Decimal::from_f64(labor), Decimal::from_f64(machine_operator)) {
+        (true, Some(labor), Some(machine_operator)) => Ok((labor, machine_operator)),
+        _ => Err(DeniDomError::Validation(format!(
+            "ФОТ split must be two non-negative fractions adding up to 1, got {}/{}",
+            labor, machine_operator
+        ))),
+    }
+}
+
+fn base_of(code: &str) -> NormativeBase {
+    NormativeBase::from_code_prefix(code).unwrap_or(NormativeBase::FER)
+}
+
+fn is_header(first_field: &str) -> bool {
+    let first = first_field.to_lowercase();
+    first == "шифр" || first == "code"
+}
+
+/// Parse a data row; `fot_split` is set for the combined ФОТ layout
+fn parse_row(
+    fields: &[String],
+    line_no: usize,
+    line: &str,
+    fot_split: Option<(Decimal, Decimal)>,
+    unit_map: Option<&UnitMap>,
+) -> Result<NormativeItem> {
+    let min_columns = if fot_split.is_some() { MIN_COLUMNS_COMBINED_FOT } else { MIN_COLUMNS };
+    if fields.len() < min_columns {
+        return Err(row_error(
+            format!("expected at least {} columns, found {}", min_columns, fields.len()),
+            line_no,
+            None,
+            line,
+        ));
+    }
+
+    let code = &fields[0];
+    if code.is_empty() {
+        return Err(row_error("empty normative code", line_no, Some(1), line));
+    }
+
+    let number = |column: usize| {
+        let field = fields.get(column).map(String::as_str).unwrap_or("");
+        parse_decimal(field).ok_or_else(|| {
+            row_error(
+                format!("invalid number '{}'", field),
+                line_no,
+                Some(column + 1),
+                line,
+            )
+        })
+    };
+
+    let mut item = NormativeItem::new(code.clone(), fields[1].clone(), base_of(code));
+    item.unit = map_unit(&fields[2], unit_map);
+    match fot_split {
+        Some((labor, machine_operator)) => {
+            let fot = number(4)?;
+            item.costs =
+                UnitCosts::new(number(3)?, fot * labor, fot * machine_operator, number(5)?, number(6)?);
+            item.labor_norm = number(7)?;
+            item.machine_norm = number(8)?;
+        }
+        None => {
+            item.costs = UnitCosts::new(number(3)?, number(4)?, number(5)?, number(6)?, number(7)?);
+            item.labor_norm = number(8)?;
+            item.machine_norm = number(9)?;
+        }
+    }
+
+    Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use denidom_core::{DeniDomError, MeasureUnit};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_parse_fer_csv() {
+        let data = "\
+Шифр;Наименование;Ед. изм.;ПЗ;ОЗП;ЗПМ;Материалы;ЭМ;Труд;Маш-ч
+ФЕР01-01-001-01;Разработка грунта;1000 м3;5000,50;1200;300;0;3800,50;12,5;8
+ФЕР15-01-002-01;Штукатурка улучшенная;100 м2;1000;300;100;500;100
+";
+        let items = parse_fer_csv(data.as_bytes()).unwrap();
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].code, "ФЕР01-01-001-01");
+        assert_eq!(items[0].unit, MeasureUnit::CubicMeter1000);
+        assert_eq!(items[0].costs.direct, Decimal::new(500050, 2));
+        assert_eq!(items[0].labor_norm, Decimal::new(125, 1));
+        assert_eq!(items[1].unit, MeasureUnit::SquareMeter100);
+        assert_eq!(items[1].machine_norm, Decimal::ZERO);
+        assert!(items[1].costs.validate());
+    }
+
+    const INCONSISTENT: &str = "\
+ФЕР01-01-001-01;Разработка грунта;1000 м3;5000;1200;300;0;3500
+ФЕР01-01-002-01;Засыпка;1000 м3;4100;1000;200;0;2800
+";
+
+    #[test]
+    fn test_direct_cross_check_flag() {
+        let options = FerImportOptions::new().dedup(DedupPolicy::Error);
+        let import = parse_fer_csv_with_options(INCONSISTENT.as_bytes(), options).unwrap();
+
+        assert_eq!(import.flagged, vec!["ФЕР01-01-002-01"]);
+        assert!(import.corrected.is_empty());
+        assert_eq!(import.items[1].costs.direct, Decimal::new(4100, 0));
+    }
+
+    #[test]
+    fn test_direct_cross_check_fix() {
+        let options = FerImportOptions::new().fix_direct(FixDirect::Fix).dedup(DedupPolicy::Error);
+        let import = parse_fer_csv_with_options(INCONSISTENT.as_bytes(), options).unwrap();
+
+        assert_eq!(import.corrected, vec!["ФЕР01-01-002-01"]);
+        assert!(import.flagged.is_empty());
+        assert_eq!(import.items[0].costs.direct, Decimal::new(5000, 0));
+        assert_eq!(import.items[1].costs.direct, Decimal::new(4000, 0));
+    }
+
+    #[test]
+    fn test_malformed_row_reports_line() {
+        let data = "\
+ФЕР01-01-001-01;Разработка грунта;1000 м3;5000;1200;300;0;3500
+ФЕР01-01-002-01;Засыпка;1000 м3;4000;1000;200;0;2800
+
+ФЕР01-01-003-01;Планировка;1000 м2;abc;100;20;0;280
+";
+        let err = parse_fer_csv(data.as_bytes()).unwrap_err();
+
+        match err {
+            DeniDomError::ParseAt { context, .. } => {
+                assert_eq!(context.line, Some(4));
+                assert_eq!(context.column, Some(4));
+                assert!(context.snippet.unwrap().starts_with("ФЕР01-01-003-01"));
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+    }
+
+    const DUPLICATES: &str = "\
+ФЕР01-01-001-01;Разработка грунта;1000 м3;5000;1200;300;0;3500
+ФЕР01-01-002-01;Засыпка;1000 м3;4000;1000;200;0;2800
+ФЕР01-01-001-01;Разработка грунта (ред.);1000 м3;5200;1250;300;0;3650
+ФЕР01-01-002-01;Засыпка (ред.);1000 м3;4100;1000;200;0;2900
+";
+
+    fn import_with(dedup_policy: DedupPolicy) -> Result<Vec<NormativeItem>> {
+        parse_fer_csv_with_options(DUPLICATES.as_bytes(), FerImportOptions::new().dedup(dedup_policy))
+            .map(|import| import.items)
+    }
+
+    #[test]
+    fn test_dedup_keep_first() {
+        let items = import_with(DedupPolicy::KeepFirst).unwrap();
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].name, "Разработка грунта");
+        assert_eq!(items[1].costs.direct, Decimal::new(4000, 0));
+    }
+
+    #[test]
+    fn test_dedup_keep_last() {
+        let items = import_with(DedupPolicy::KeepLast).unwrap();
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].code, "ФЕР01-01-001-01");
+        assert_eq!(items[0].name, "Разработка грунта (ред.)");
+        assert_eq!(items[1].costs.direct, Decimal::new(4100, 0));
+    }
+
+    #[test]
+    fn test_dedup_error_names_code() {
+        let err = import_with(DedupPolicy::Error).unwrap_err();
+
+        match err {
+            DeniDomError::Validation(message) => assert!(message.contains("ФЕР01-01-001-01")),
+            other => panic!("unexpected error: {other}"),
+        }
+    }
+
+    #[test]
+    fn test_collection_filter() {
+        let data = "\
+ФЕР01-01-001-01;Разработка грунта;1000 м3;5000;1200;300;0;3500
+ФЕР15-01-002-01;Штукатурка улучшенная;100 м2;1000;300;100;500;100
+ГЭСН15-01-002-01;Штукатурка улучшенная;100 м2;0;0;0;0;0
+ФЕР15-04-005-01;Окраска;100 м2;800;200;0;600;0
+";
+        let filter = CodeFilter::Collection("ФЕР15".to_string());
+        let options = FerImportOptions::new().dedup(DedupPolicy::Error).filter(filter);
+        let import = parse_fer_csv_with_options(data.as_bytes(), options).unwrap();
+
+        let codes: Vec<&str> = import.items.iter().map(|item| item.code.as_str()).collect();
+        assert_eq!(codes, vec!["ФЕР15-01-002-01", "ФЕР15-04-005-01"]);
+    }
+
+    #[test]
+    fn test_progress_reports_final_row_count() {
+        let mut data = String::from("Шифр;Наименование;Ед. изм.;ПЗ;ОЗП;ЗПМ;Материалы;ЭМ\n");
+        for i in 0..2500 {
+            data.push_str(&format!("ФЕР01-01-{:03}-{:02};Работа;м3;100;40;10;30;20\n", i / 100, i % 100));
+        }
+
+        let mut calls = Vec::new();
+        let mut record = |rows: usize, total: Option<usize>| calls.push((rows, total));
+        let options = FerImportOptions::new().dedup(DedupPolicy::Error).progress(&mut record);
+        let import = parse_fer_csv_with_options(data.as_bytes(), options).unwrap();
+
+        assert_eq!(import.items.len(), 2500);
+        assert_eq!(calls, vec![(1000, None), (2000, None), (2500, None)]);
+    }
+
+    const COMBINED_FOT: &str = "\
+Шифр;Наименование;Ед. изм.;ПЗ;ФОТ;Материалы;ЭМ;Труд;Маш-ч
+ФЕР15-01-002-01;Штукатурка улучшенная;100 м2;1000;400;500;100;45;2
+";
+
+    #[test]
+    fn test_custom_unit_map() {
+        let data = "\
+ФЕР15-01-002-01;Штукатурка;100м.кв.;1000;400;0;500;100
+ФЕР15-01-002-02;Окраска;м.кв.;10;4;0;5;1
+";
+        let map = UnitMap::from([("100м.кв.".to_string(), MeasureUnit::SquareMeter100)]);
+        let options = FerImportOptions::new().dedup(DedupPolicy::Error).unit_map(&map);
+        let import = parse_fer_csv_with_options(data.as_bytes(), options).unwrap();
+
+        assert_eq!(import.items[0].unit, MeasureUnit::SquareMeter100);
+        assert_eq!(import.items[1].unit, MeasureUnit::Other("м.кв.".to_string()));
+    }
+
+    #[test]
+    fn test_parse_cp1251_file() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/fer_cp1251.csv");
+
+        let items = parse_fer_file(path).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].name, "Разработка грунта экскаватором");
+        assert_eq!(items[1].code, "ФЕР15-01-002-01");
+        assert_eq!(items[1].unit, MeasureUnit::SquareMeter100);
+
+        let forced = parse_fer_file_with_encoding(path, Some(SourceEncoding::Cp1251)).unwrap();
+        assert_eq!(forced[0].name, items[0].name);
+    }
+
+    #[test]
+    fn test_combined_fot_split() {
+        let parse = |fot_split| {
+            let options = FerImportOptions {
+                fot_split,
+                ..FerImportOptions::new().dedup(DedupPolicy::Error)
+            };
+            parse_fer_csv_with_options(COMBINED_FOT.as_bytes(), options)
+        };
+
+        let import = parse(Some((0.8, 0.2))).unwrap();
+        let item = &import.items[0];
+        assert_eq!(item.costs.labor, Decimal::new(320, 0));
+        assert_eq!(item.costs.machine_operator, Decimal::new(80, 0));
+        assert_eq!(item.costs.materials, Decimal::new(500, 0));
+        assert_eq!(item.labor_norm, Decimal::new(45, 0));
+        assert!(import.flagged.is_empty());
+
+        let import = parse(None).unwrap();
+        assert_eq!(import.items[0].costs.labor, Decimal::new(400, 0));
+        assert_eq!(import.items[0].costs.machine_operator, Decimal::ZERO);
+
+        assert!(parse(Some((0.8, 0.3))).is_err());
+    }
 }
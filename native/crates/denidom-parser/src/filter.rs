@@ -0,0 +1,45 @@
+//! Import filter by collection or normative base
+
+use denidom_core::{NormativeBase, NormativeCode};
+
+/// Which rows of a normative file to import
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeFilter {
+    /// Only one collection, by its key such as "ФЕР15" or "ФЕРм08"
+    Collection(String),
+    /// Only one normative base
+    Base(NormativeBase),
+}
+
+impl CodeFilter {
+    /// Check whether a row with `code` and resolved `base` passes the filter
+    pub fn matches(&self, code: &str, base: NormativeBase) -> bool {
+        match self {
+            Self::Collection(key) => NormativeCode::parse(code)
+                .is_some_and(|parsed| parsed.collection_key() == key.trim()),
+            Self::Base(expected) => base == *expected,
+        }
+    }
+}
+
+/// Check `code` against an optional filter; no filter matches everything
+pub(crate) fn passes(filter: Option<&CodeFilter>, code: &str, base: NormativeBase) -> bool {
+    filter.is_none_or(|filter| filter.matches(code, base))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_filter() {
+        let collection = CodeFilter::Collection("ФЕР15".to_string());
+        assert!(collection.matches("ФЕР15-01-002-01", NormativeBase::FER));
+        assert!(!collection.matches("ФЕР01-01-001-01", NormativeBase::FER));
+        assert!(!collection.matches("ГЭСН15-01-002-01", NormativeBase::GESN));
+
+        let base = CodeFilter::Base(NormativeBase::GESN);
+        assert!(base.matches("ГЭСН01-01-001-01", NormativeBase::GESN));
+        assert!(!base.matches("ФЕР01-01-001-01", NormativeBase::FER));
+    }
+}
@@ -0,0 +1,232 @@
+//! ФСНБ-2022 Parser - resource-model normatives
+//!
+//! Items come with explicit resource rows and no unit costs; costs are
+//! computed later via [`NormativeItem::price_from_resources`]:
+//!
+//! ```xml
+//! <Section Code="01-01" Name="...">
+//!   <Work Code="ГЭСН01-01-001-01" Name="..." MeasureUnit="1000 м3">
+//!     <Resource Code="1-100-20" Name="..." MeasureUnit="чел.-ч" Quantity="13,44"/>
+//!   </Work>
+//! </Section>
+//! ```
+
+use std::collections::HashMap;
+
+use denidom_core::{
+    DeniDomError, NormativeBase, NormativeItem, ResourceNorm, ResourceNormType,
+    Result,
+};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::csv::parse_decimal;
+use crate::filter::{passes, CodeFilter};
+use crate::units::{map_unit, UnitMap};
+
+/// Parse ФСНБ-2022 normatives from XML; works not passing `filter` are
+/// skipped together with their resources. `unit_map` is consulted before
+/// the built-in unit spellings.
+pub fn parse_fsnb2022(
+    xml: &str,
+    filter: Option<CodeFilter>,
+    unit_map: Option<&UnitMap>,
+) -> Result<Vec<NormativeItem>> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+
+    let mut items = Vec::new();
+    let mut section: Option<String> = None;
+    let mut current: Option<NormativeItem> = None;
+    let mut skipping = false;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| xml_error(reader.buffer_position(), e))?;
+
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let is_empty = matches!(event, Event::Empty(_));
+                let attrs = attributes(e, &reader)?;
+                match e.name().as_ref() {
+                    b"Section" if !is_empty => section = attrs.get("Name").cloned(),
+                    b"Work" => {
+                        let item = parse_work(&attrs, section.clone(), unit_map)?;
+                        if !passes(filter.as_ref(), &item.code, item.base_type) {
+                            skipping = !is_empty;
+                        } else if is_empty {
+                            items.push(item);
+                        } else {
+                            current = Some(item);
+                        }
+                    }
+                    b"Resource" if skipping => {}
+                    b"Resource" => {
+                        let item = current.as_mut().ok_or_else(|| {
+                            DeniDomError::Parse("Resource outside of Work".to_string())
+                        })?;
+                        add_resource(item, parse_resource(&attrs, &item.code, unit_map)?);
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(ref e) => match e.name().as_ref() {
+                b"Work" => {
+                    items.extend(current.take());
+                    skipping = false;
+                }
+                b"Section" => section = None,
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(items)
+}
+
+pub(crate) fn xml_error(position: usize, err: impl std::fmt::Display) -> DeniDomError {
+    DeniDomError::Parse(format!("XML error at byte {}: {}", position, err))
+}
+
+/// Attributes of `element` keyed by local name (namespace prefixes dropped)
+pub(crate) fn attributes<R>(element: &BytesStart, reader: &Reader<R>) -> Result<HashMap<String, String>> {
+    let position = reader.buffer_position();
+    element
+        .attributes()
+        .map(|attr| {
+            let attr = attr.map_err(|e| xml_error(position, e))?;
+            let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned();
+            let value = attr
+                .decode_and_unescape_value(reader)
+                .map_err(|e| xml_error(position, e))?
+                .into_owned();
+            Ok((key, value))
+        })
+        .collect()
+}
+
+pub(crate) fn required<'a>(attrs: &'a HashMap<String, String>, name: &str, element: &str) -> Result<&'a str> {
+    attrs
+        .get(name)
+        .map(String::as_str)
+        .ok_or_else(|| DeniDomError::Parse(format!("{} without {} attribute", element, name)))
+}
+
+fn parse_work(
+    attrs: &HashMap<String, String>,
+    section: Option<String>,
+    unit_map: Option<&UnitMap>,
+) -> Result<NormativeItem> {
+    let code = required(attrs, "Code", "Work")?;
+    let base_type = NormativeBase::from_code_prefix(code).unwrap_or(NormativeBase::GESN);
+
+    let mut item = NormativeItem::new(
+        code.to_string(),
+        attrs.get("Name").cloned().unwrap_or_default(),
+        base_type,
+    );
+    item.unit = map_unit(attrs.get("MeasureUnit").map(String::as_str).unwrap_or(""), unit_map);
+    item.section = section;
+    Ok(item)
+}
+
+/// Resource norm from the attributes of a `Resource` element of work `work_code`
+pub(crate) fn parse_resource(
+    attrs: &HashMap<String, String>,
+    work_code: &str,
+    unit_map: Option<&UnitMap>,
+) -> Result<ResourceNorm> {
+    let code = required(attrs, "Code", "Resource")?;
+    let quantity = attrs.get("Quantity").map(String::as_str).unwrap_or("");
+    let consumption = parse_decimal(quantity).ok_or_else(|| {
+        DeniDomError::Parse(format!(
+            "invalid quantity '{}' of resource {} in {}",
+            quantity, code, work_code
+        ))
+    })?;
+
+    Ok(ResourceNorm {
+        code: code.to_string(),
+        name: attrs.get("Name").cloned().unwrap_or_default(),
+        resource_type: resource_type(code),
+        unit: map_unit(attrs.get("MeasureUnit").map(String::as_str).unwrap_or(""), unit_map),
+        consumption,
+        base_price: None,
+    })
+}
+
+/// Resource type by its classifier code: `1-…` labor, `2` machine operators,
+/// `91.…` machines, everything else materials
+pub(crate) fn resource_type(code: &str) -> ResourceNormType {
+    if code.starts_with("1-") {
+        ResourceNormType::Labor
+    } else if code == "2" || code.starts_with("2-") {
+        ResourceNormType::MachineOperator
+    } else if code.starts_with("91.") {
+        ResourceNormType::Machine
+    } else {
+        ResourceNormType::Material
+    }
+}
+
+pub(crate) fn add_resource(item: &mut NormativeItem, resource: ResourceNorm) {
+    match resource.resource_type {
+        ResourceNormType::Labor => item.labor_norm += resource.consumption,
+        ResourceNormType::Machine => item.machine_norm += resource.consumption,
+        _ => {}
+    }
+    item.resources.push(resource);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use denidom_core::MeasureUnit;
+    use rust_decimal::Decimal;
+
+    const SAMPLE: &str = include_str!("../tests/fixtures/fsnb2022_sample.xml");
+
+    #[test]
+    fn test_parse_fsnb2022() {
+        let items = parse_fsnb2022(SAMPLE, None, None).unwrap();
+
+        assert_eq!(items.len(), 2);
+
+        let excavation = &items[0];
+        assert_eq!(excavation.code, "ГЭСН01-01-001-01");
+        assert_eq!(excavation.base_type, NormativeBase::GESN);
+        assert_eq!(excavation.unit, MeasureUnit::CubicMeter1000);
+        assert_eq!(excavation.section.as_deref(), Some("Разработка грунта экскаваторами"));
+        assert_eq!(excavation.resources.len(), 4);
+        assert_eq!(excavation.resources[1].resource_type, ResourceNormType::MachineOperator);
+        assert_eq!(excavation.labor_norm, Decimal::new(1344, 2));
+        assert_eq!(excavation.machine_norm, Decimal::new(2108, 2));
+        assert_eq!(excavation.costs.direct, Decimal::ZERO);
+
+        let concrete = &items[1];
+        assert_eq!(concrete.resources[1].resource_type, ResourceNormType::Material);
+        assert_eq!(concrete.resources[1].consumption, Decimal::new(102, 0));
+        assert_eq!(concrete.machine_norm, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_resource_outside_work() {
+        let xml = r#"<Base><Resource Code="1-100-20" Quantity="1"/></Base>"#;
+        assert!(matches!(parse_fsnb2022(xml, None, None), Err(DeniDomError::Parse(_))));
+    }
+
+    #[test]
+    fn test_base_filter_skips_works() {
+        let items = parse_fsnb2022(SAMPLE, Some(CodeFilter::Base(NormativeBase::FER)), None).unwrap();
+        assert!(items.is_empty());
+
+        let filter = CodeFilter::Collection("ГЭСН01".to_string());
+        let items = parse_fsnb2022(SAMPLE, Some(filter), None).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].code, "ГЭСН01-01-001-01");
+        assert_eq!(items[0].resources.len(), 4);
+    }
+}
@@ -1,6 +1,202 @@
 //! GESN Parser - State Element Estimated Norms
+//!
+//! Reads the official ГЭСН XML export. Tags may carry a namespace prefix;
+//! resources may be grouped under `Resources` / `ResourceGroup` elements:
+//!
+//! ```xml
+//! <gesn:Section Code="01-01" Name="...">
+//!   <gesn:Table Code="01-01-001" Name="...">
+//!     <gesn:Work Code="ГЭСН01-01-001-01" Name="..." MeasureUnit="1000 м3">
+//!       <gesn:Resources>
+//!         <gesn:Resource Code="1-100-20" Name="..." MeasureUnit="чел.-ч" Quantity="13,44"/>
+//!       </gesn:Resources>
+//!     </gesn:Work>
+//!   </gesn:Table>
+//! </gesn:Section>
+//! ```
 
-// Placeholder for future implementation
-pub fn parse_gesn_file(_path: &str) -> Result<Vec<()>, Box<dyn std::error::Error>> {
-    Ok(vec![])
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use denidom_core::{DeniDomError, NormativeBase, NormativeItem, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::filter::{passes, CodeFilter};
+use crate::fsnb::{add_resource, attributes, parse_resource, required, xml_error};
+use crate::units::{map_unit, UnitMap};
+
+/// Настройки импорта ГЭСН
+///
+/// Defaults import every work with the built-in unit spellings.
+#[derive(Debug, Clone, Default)]
+pub struct GesnImportOptions<'a> {
+    /// Отбор расценок по шифру; остальные расценки пропускаются вместе с ресурсами
+    pub filter: Option<CodeFilter>,
+    /// Дополнительные написания единиц измерения, см. [`map_unit`]
+    pub unit_map: Option<&'a UnitMap>,
+}
+
+impl<'a> GesnImportOptions<'a> {
+    /// Default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Import only works whose code passes `filter`
+    pub fn filter(mut self, filter: CodeFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Consult `unit_map` before the built-in unit spellings
+    pub fn unit_map(mut self, unit_map: &'a UnitMap) -> Self {
+        self.unit_map = Some(unit_map);
+        self
+    }
+}
+
+/// Parse ГЭСН normatives from the XML export with default [`GesnImportOptions`]
+///
+/// GESN has consumption norms only, so `costs` stay at default; labor and
+/// machine norms are summed from the resource rows.
+pub fn parse_gesn_xml(reader: impl BufRead) -> Result<Vec<NormativeItem>> {
+    parse_gesn_xml_with_options(reader, GesnImportOptions::default())
+}
+
+/// Parse ГЭСН normatives from the XML export, keeping only works that pass
+/// the filter of `options` and mapping units through its unit map
+pub fn parse_gesn_xml_with_options(
+    reader: impl BufRead,
+    options: GesnImportOptions,
+) -> Result<Vec<NormativeItem>> {
+    let mut reader = Reader::from_reader(reader);
+    reader.trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut items = Vec::new();
+    let mut section: Option<String> = None;
+    let mut table: Option<String> = None;
+    let mut current: Option<NormativeItem> = None;
+    let mut skipping = false;
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| xml_error(reader.buffer_position(), e))?;
+
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let is_empty = matches!(event, Event::Empty(_));
+                let attrs = attributes(e, &reader)?;
+                match e.local_name().as_ref() {
+                    b"Section" if !is_empty => section = attrs.get("Name").cloned(),
+                    b"Table" if !is_empty => table = attrs.get("Name").cloned(),
+                    b"Work" => {
+                        let mut item = parse_work(&attrs, options.unit_map)?;
+                        if !passes(options.filter.as_ref(), &item.code, NormativeBase::GESN) {
+                            skipping = !is_empty;
+                            buf.clear();
+                            continue;
+                        }
+                        item.section = section.clone();
+                        item.table = table.clone();
+                        if is_empty {
+                            items.push(item);
+                        } else {
+                            current = Some(item);
+                        }
+                    }
+                    b"Resource" if skipping => {}
+                    b"Resource" => {
+                        let item = current.as_mut().ok_or_else(|| {
+                            DeniDomError::Parse("Resource outside of Work".to_string())
+                        })?;
+                        add_resource(item, parse_resource(&attrs, &item.code, options.unit_map)?);
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(ref e) => match e.local_name().as_ref() {
+                b"Work" => {
+                    skipping = false;
+                    items.extend(current.take());
+                }
+                b"Table" => table = None,
+                b"Section" => section = None,
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(items)
+}
+
+fn parse_work(attrs: &HashMap<String, String>, unit_map: Option<&UnitMap>) -> Result<NormativeItem> {
+    let code = required(attrs, "Code", "Work")?;
+    let mut item = NormativeItem::new(
+        code.to_string(),
+        attrs.get("Name").cloned().unwrap_or_default(),
+        NormativeBase::GESN,
+    );
+    item.unit = map_unit(attrs.get("MeasureUnit").map(String::as_str).unwrap_or(""), unit_map);
+    Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use denidom_core::{MeasureUnit, ResourceNormType};
+    use rust_decimal::Decimal;
+
+    const SAMPLE: &[u8] = include_bytes!("../tests/fixtures/gesn_sample.xml");
+
+    #[test]
+    fn test_parse_gesn_xml() {
+        let items = parse_gesn_xml(SAMPLE).unwrap();
+
+        assert_eq!(items.len(), 2);
+
+        let excavation = &items[0];
+        assert_eq!(excavation.code, "ГЭСН01-01-001-01");
+        assert_eq!(excavation.base_type, NormativeBase::GESN);
+        assert_eq!(excavation.unit, MeasureUnit::CubicMeter1000);
+        assert_eq!(excavation.section.as_deref(), Some("Механизированная разработка грунта"));
+        assert_eq!(excavation.table.as_deref(), Some("Разработка грунта в отвал экскаваторами"));
+        assert_eq!(excavation.resources.len(), 4);
+        assert_eq!(excavation.resources[3].resource_type, ResourceNormType::Machine);
+        assert_eq!(excavation.labor_norm, Decimal::new(1344, 2));
+        assert_eq!(excavation.machine_norm, Decimal::new(2108, 2));
+        assert_eq!(excavation.costs.direct, Decimal::ZERO);
+
+        let concrete = &items[1];
+        assert_eq!(concrete.unit, MeasureUnit::CubicMeter100);
+        assert_eq!(concrete.resources[1].consumption, Decimal::new(102, 0));
+        assert_eq!(concrete.machine_norm, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_filter_and_unit_map() {
+        let map = UnitMap::from([("чел.-ч".to_string(), MeasureUnit::Other("чел.-час".to_string()))]);
+        let options = GesnImportOptions::new()
+            .filter(CodeFilter::Collection("ГЭСН06".to_string()))
+            .unit_map(&map);
+        let items = parse_gesn_xml_with_options(SAMPLE, options).unwrap();
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].code, "ГЭСН06-01-001-01");
+        assert_eq!(items[0].resources.len(), 2);
+        assert_eq!(items[0].resources[0].unit, MeasureUnit::Other("чел.-час".to_string()));
+        assert_eq!(items[0].labor_norm, Decimal::new(180, 0));
+    }
+
+    #[test]
+    fn test_invalid_quantity() {
+        let xml = r#"<Base><Work Code="01-01-001-01"><Resource Code="1-100-20" Quantity="много"/></Work></Base>"#;
+        let err = parse_gesn_xml(xml.as_bytes()).unwrap_err();
+        assert!(matches!(err, DeniDomError::Parse(ref m) if m.contains("много")), "{:?}", err);
+    }
 }
@@ -0,0 +1,451 @@
+//! Estimate JSON interchange
+//!
+//! A stable wire format for third parties, decoupled from the internal
+//! `Estimate` fields. Amounts are decimal strings; units are Russian
+//! abbreviations:
+//!
+//! ```json
+//! {
+//!   "number": "ЛС-01",
+//!   "name": "Ремонт кровли",
+//!   "object": "г. Москва",
+//!   "index": "8.50",
+//!   "coefficients": {
+//!     "cramped": "1.15",
+//!     "winter_zone": {"zone": 4, "work_section": "Земляные работы"},
+//!     "custom": [{"name": "Реконструкция", "value": "1.2",
+//!                 "justification": "Приказ №123", "is_active": true}]
+//!   },
+//!   "overhead_rate": "1.12",
+//!   "profit_rate": "0.65",
+//!   "contingency_rate": "0.02",
+//!   "taxation": "Usn",
+//!   "sections": [{
+//!     "number": 1,
+//!     "name": "Кровля",
+//!     "coefficient": "1.15",
+//!     "items": [{
+//!       "position": 1,
+//!       "code": "ФЕР12-01-002-09",
+//!       "name": "Устройство кровли",
+//!       "unit": "100 м²",
+//!       "quantity": "1.25",
+//!       "quantity_formula": "0,5 + 0,75",
+//!       "tags": ["утверждено"],
+//!       "costs": {"direct": "1000", "labor": "300", "machine_operator": "100",
+//!                 "materials": "500", "machines": "100"}
+//!     }]
+//!   }]
+//! }
+//! ```
+
+use denidom_core::{
+    Coefficients, CustomCoefficient, Estimate, EstimateItem, EstimateSection, MeasureUnit,
+    OverheadMethod, ProfitMethod, Result, TaxationRegime, UnitCosts,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Смета в формате обмена
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EstimateJson {
+    /// Номер сметы
+    #[serde(default)]
+    pub number: String,
+    /// Наименование
+    pub name: String,
+    /// Объект строительства
+    #[serde(default)]
+    pub object: String,
+    /// Индекс пересчёта (по умолчанию 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<Decimal>,
+    /// Прочие коэффициенты к смете
+    #[serde(default, skip_serializing_if = "CoefficientsJson::is_empty")]
+    pub coefficients: CoefficientsJson,
+    /// Норматив накладных расходов
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overhead_rate: Option<Decimal>,
+    /// Норматив сметной прибыли
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profit_rate: Option<Decimal>,
+    /// Метод расчёта накладных расходов
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overhead_method: Option<OverheadMethod>,
+    /// Метод расчёта сметной прибыли
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profit_method: Option<ProfitMethod>,
+    /// Норматив непредвиденных затрат
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contingency_rate: Option<Decimal>,
+    /// Система налогообложения (по умолчанию ОСН)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taxation: Option<TaxationRegime>,
+    /// Компенсация НДС при УСН
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usn_vat_compensation: Option<Decimal>,
+    /// Разделы
+    #[serde(default)]
+    pub sections: Vec<SectionJson>,
+}
+
+/// Коэффициенты к смете в формате обмена (кроме индекса)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CoefficientsJson {
+    /// Коэффициент зимнего удорожания
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winter: Option<Decimal>,
+    /// Зимний коэффициент по составляющим: зона и раздел работ по ГСН 81-05-02-2007
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winter_zone: Option<WinterZoneJson>,
+    /// Коэффициент стеснённости
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cramped: Option<Decimal>,
+    /// Региональный коэффициент
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regional: Option<Decimal>,
+    /// Высотный коэффициент
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<Decimal>,
+    /// Пользовательские коэффициенты
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<CustomCoefficientJson>,
+}
+
+impl CoefficientsJson {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Зона и раздел работ зимнего коэффициента
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WinterZoneJson {
+    /// Температурная зона (1-8)
+    pub zone: u8,
+    /// Раздел работ
+    pub work_section: String,
+}
+
+/// Пользовательский коэффициент в формате обмена
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomCoefficientJson {
+    /// Наименование
+    pub name: String,
+    /// Значение
+    pub value: Decimal,
+    /// Обоснование применения
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub justification: Option<String>,
+    /// Активен ли коэффициент (по умолчанию да)
+    #[serde(default = "active")]
+    pub is_active: bool,
+}
+
+fn active() -> bool {
+    true
+}
+
+/// Раздел сметы в формате обмена
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectionJson {
+    /// Номер раздела
+    pub number: u32,
+    /// Наименование раздела
+    pub name: String,
+    /// Коэффициент к разделу
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coefficient: Option<Decimal>,
+    /// Позиции
+    #[serde(default)]
+    pub items: Vec<ItemJson>,
+}
+
+/// Позиция сметы в формате обмена
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemJson {
+    /// Порядковый номер
+    #[serde(default)]
+    pub position: u32,
+    /// Шифр расценки
+    pub code: String,
+    /// Наименование работы
+    pub name: String,
+    /// Единица измерения (сокращение, например «100 м²»)
+    pub unit: String,
+    /// Количество
+    pub quantity: Decimal,
+    /// Формула количества; при наличии пересчитывает количество
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity_formula: Option<String>,
+    /// Коэффициент к позиции
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coefficient: Option<Decimal>,
+    /// Метки
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Стоимость единицы
+    #[serde(default)]
+    pub costs: CostsJson,
+}
+
+/// Стоимость единицы в формате обмена
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostsJson {
+    /// Прямые затраты
+    #[serde(default)]
+    pub direct: Decimal,
+    /// ОЗП
+    #[serde(default)]
+    pub labor: Decimal,
+    /// ЗПМ
+    #[serde(default)]
+    pub machine_operator: Decimal,
+    /// Материалы
+    #[serde(default)]
+    pub materials: Decimal,
+    /// Эксплуатация машин
+    #[serde(default)]
+    pub machines: Decimal,
+}
+
+/// Parse an estimate from the JSON interchange format
+pub fn parse_estimate_json(json: &str) -> Result<Estimate> {
+    let dto: EstimateJson = serde_json::from_str(json)?;
+    dto.into_estimate()
+}
+
+/// Convert an estimate to the JSON interchange DTO
+pub fn estimate_to_json_dto(estimate: &Estimate) -> EstimateJson {
+    EstimateJson {
+        number: estimate.number.clone(),
+        name: estimate.name.clone(),
+        object: estimate.object.clone(),
+        index: Some(estimate.coefficients.index),
+        coefficients: coefficients_to_json(&estimate.coefficients),
+        overhead_rate: Some(estimate.overhead.rate),
+        profit_rate: Some(estimate.profit.rate),
+        overhead_method: Some(estimate.overhead.method),
+        profit_method: Some(estimate.profit.method),
+        contingency_rate: Some(estimate.contingency_rate),
+        taxation: Some(estimate.taxation),
+        usn_vat_compensation: estimate.usn_vat_compensation,
+        sections: estimate
+            .sections
+            .iter()
+            .map(|section| SectionJson {
+                number: section.number,
+                name: section.name.clone(),
+                coefficient: section.coefficient,
+                items: section
+                    .items
+                    .iter()
+                    .map(|item| ItemJson {
+                        position: item.position,
+                        code: item.code.clone(),
+                        name: item.name.clone(),
+                        unit: item.unit.abbreviation().to_string(),
+                        quantity: item.quantity,
+                        quantity_formula: item.quantity_expr.as_ref().map(|e| e.source.clone()),
+                        coefficient: item.coefficient,
+                        tags: item.tags.clone(),
+                        costs: CostsJson {
+                            direct: item.unit_costs.direct,
+                            labor: item.unit_costs.labor,
+                            machine_operator: item.unit_costs.machine_operator,
+                            materials: item.unit_costs.materials,
+                            machines: item.unit_costs.machines,
+                        },
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+fn coefficients_to_json(coefficients: &Coefficients) -> CoefficientsJson {
+    CoefficientsJson {
+        winter: coefficients.winter,
+        winter_zone: coefficients.winter_components.as_ref().map(|wc| WinterZoneJson {
+            zone: wc.zone,
+            work_section: wc.work_section.clone(),
+        }),
+        cramped: coefficients.cramped,
+        regional: coefficients.regional,
+        height: coefficients.height,
+        custom: coefficients
+            .custom
+            .iter()
+            .map(|custom| CustomCoefficientJson {
+                name: custom.name.clone(),
+                value: custom.value,
+                justification: custom.justification.clone(),
+                is_active: custom.is_active,
+            })
+            .collect(),
+    }
+}
+
+impl EstimateJson {
+    /// Map the DTO onto a new core estimate
+    ///
+    /// Fails if a quantity formula does not evaluate or the winter zone and
+    /// work section are not in the table.
+    pub fn into_estimate(self) -> Result<Estimate> {
+        let mut estimate = Estimate::new(self.name, self.object);
+        estimate.number = self.number;
+        if let Some(index) = self.index {
+            estimate.coefficients.index = index;
+        }
+        let coefficients = &mut estimate.coefficients;
+        coefficients.winter = self.coefficients.winter;
+        coefficients.cramped = self.coefficients.cramped;
+        coefficients.regional = self.coefficients.regional;
+        coefficients.height = self.coefficients.height;
+        if let Some(winter) = &self.coefficients.winter_zone {
+            coefficients.apply_winter(winter.zone, &winter.work_section)?;
+        }
+        coefficients.custom = self
+            .coefficients
+            .custom
+            .into_iter()
+            .map(|custom| CustomCoefficient {
+                name: custom.name,
+                value: custom.value,
+                justification: custom.justification,
+                is_active: custom.is_active,
+            })
+            .collect();
+        if let Some(rate) = self.overhead_rate {
+            estimate.overhead.rate = rate;
+        }
+        if let Some(rate) = self.profit_rate {
+            estimate.profit.rate = rate;
+        }
+        if let Some(method) = self.overhead_method {
+            estimate.overhead.method = method;
+        }
+        if let Some(method) = self.profit_method {
+            estimate.profit.method = method;
+        }
+        if let Some(rate) = self.contingency_rate {
+            estimate.contingency_rate = rate;
+        }
+        if let Some(taxation) = self.taxation {
+            estimate.taxation = taxation;
+        }
+        estimate.usn_vat_compensation = self.usn_vat_compensation;
+
+        for section_dto in self.sections {
+            let mut section = EstimateSection::new(section_dto.number, section_dto.name);
+            section.coefficient = section_dto.coefficient;
+            for item_dto in section_dto.items {
+                let mut item = EstimateItem::new(
+                    item_dto.code,
+                    item_dto.name,
+                    MeasureUnit::from(item_dto.unit.as_str()),
+                    item_dto.quantity,
+                );
+                item.position = item_dto.position;
+                if let Some(formula) = &item_dto.quantity_formula {
+                    item.set_quantity_formula(formula)?;
+                }
+                item.coefficient = item_dto.coefficient;
+                for tag in &item_dto.tags {
+                    item.add_tag(tag);
+                }
+                let costs = item_dto.costs;
+                item.unit_costs = UnitCosts::new(
+                    costs.direct,
+                    costs.labor,
+                    costs.machine_operator,
+                    costs.materials,
+                    costs.machines,
+                );
+                section.add_item(item);
+            }
+            estimate.sections.push(section);
+        }
+
+        Ok(estimate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use denidom_core::calculate_estimate_totals;
+
+    #[test]
+    fn test_json_roundtrip() {
+        let mut estimate = Estimate::new("Ремонт кровли".to_string(), "г. Москва".to_string());
+        estimate.number = "ЛС-01".to_string();
+        estimate.coefficients.index = Decimal::new(850, 2);
+        estimate.overhead.method = OverheadMethod::FromDirectCost;
+        estimate.contingency_rate = Decimal::new(2, 2);
+        estimate.taxation = TaxationRegime::Usn;
+        estimate.usn_vat_compensation = Some(Decimal::new(11, 1));
+        estimate.coefficients.cramped = Some(Decimal::new(115, 2));
+        estimate.coefficients.apply_winter(4, "Земляные работы").unwrap();
+        estimate.coefficients.custom.push(CustomCoefficient {
+            name: "Реконструкция".to_string(),
+            value: Decimal::new(12, 1),
+            justification: Some("Приказ №123".to_string()),
+            is_active: false,
+        });
+
+        let mut section = EstimateSection::new(1, "Кровля".to_string());
+        section.coefficient = Some(Decimal::new(115, 2));
+        let mut item = EstimateItem::new(
+            "ФЕР12-01-002-09".to_string(),
+            "Устройство кровли".to_string(),
+            MeasureUnit::SquareMeter100,
+            Decimal::new(125, 2),
+        );
+        item.position = 1;
+        item.unit_costs = UnitCosts::new(
+            Decimal::new(1000, 0),
+            Decimal::new(300, 0),
+            Decimal::new(100, 0),
+            Decimal::new(500, 0),
+            Decimal::new(100, 0),
+        );
+        item.coefficient = Some(Decimal::new(12, 1));
+        item.add_tag("утверждено");
+        item.set_quantity_formula("0,5 + 0,75").unwrap();
+        section.add_item(item);
+        estimate.sections.push(section);
+
+        let dto = estimate_to_json_dto(&estimate);
+        let json = serde_json::to_string(&dto).unwrap();
+        assert!(json.contains(r#""quantity":"1.25""#));
+
+        let imported = parse_estimate_json(&json).unwrap();
+        assert_eq!(estimate_to_json_dto(&imported), dto);
+        assert_eq!(imported.sections[0].items[0].unit, MeasureUnit::SquareMeter100);
+        assert_eq!(imported.coefficients.index, Decimal::new(850, 2));
+        assert_eq!(imported.taxation, TaxationRegime::Usn);
+        assert_eq!(imported.coefficients.cramped, Some(Decimal::new(115, 2)));
+        assert_eq!(imported.coefficients.combined(), estimate.coefficients.combined());
+        let winter = imported.coefficients.winter_components.as_ref().unwrap();
+        assert_eq!(winter.labor_coefficient, Decimal::new(1038, 3));
+        let custom = &imported.coefficients.custom[0];
+        assert_eq!(custom.justification.as_deref(), Some("Приказ №123"));
+        assert!(!custom.is_active);
+        let item = &imported.sections[0].items[0];
+        assert_eq!(item.tags, ["утверждено"]);
+        assert_eq!(item.quantity_expr, estimate.sections[0].items[0].quantity_expr);
+
+        let (expected, actual) = (
+            calculate_estimate_totals(&estimate),
+            calculate_estimate_totals(&imported),
+        );
+        assert_eq!(actual.direct_costs, expected.direct_costs);
+        assert_eq!(actual.overhead, expected.overhead);
+        assert_eq!(actual.contingency, expected.contingency);
+        assert_eq!(actual.vat, expected.vat);
+        assert_eq!(actual.total, expected.total);
+
+        assert!(parse_estimate_json(r#"{"sections": []}"#).is_err());
+    }
+}
@@ -3,10 +3,42 @@
 //! Parse construction normative databases:
 //! - ФЕР (Federal unit rates)
 //! - ГЭСН (State element estimated norms)
+//! - ФСНБ-2022 resource-model normatives
 //! - ТЕР (Territorial unit rates)
 //! - Excel/XML import
+//! - Дефектные ведомости (defect lists)
+//! - UTF-8 / CP1251 detection for legacy files
+//! - Estimate CSV interchange (export/import)
+//! - Estimate JSON interchange for third parties
 
+pub mod dedup;
+pub mod defect;
+pub mod encoding;
+pub mod estimate_csv;
 pub mod fer;
+pub mod filter;
+pub mod fsnb;
 pub mod gesn;
+pub mod json;
+pub mod units;
 pub mod excel;
 pub mod xml;
+pub mod warning;
+
+mod csv;
+
+pub use dedup::{dedup_normatives, DedupPolicy};
+pub use defect::{parse_defect_list, parse_defect_list_file, parse_defect_list_with_warnings};
+pub use encoding::{decode_to_utf8, detect_encoding, SourceEncoding};
+pub use estimate_csv::{export_estimate_csv, parse_estimate_csv};
+pub use excel::parse_estimate_xlsx;
+pub use fer::{
+    parse_fer_csv, parse_fer_csv_with_options, parse_fer_file, parse_fer_file_with_encoding, FerImport, FerImportOptions,
+    FixDirect, ProgressFn, PROGRESS_INTERVAL,
+};
+pub use filter::CodeFilter;
+pub use fsnb::parse_fsnb2022;
+pub use gesn::{parse_gesn_xml, parse_gesn_xml_with_options, GesnImportOptions};
+pub use json::{estimate_to_json_dto, parse_estimate_json, EstimateJson};
+pub use units::{map_unit, UnitMap};
+pub use warning::ImportWarning;
@@ -0,0 +1,35 @@
+//! Source-specific unit dictionaries
+//!
+//! Different normative vintages spell units differently ("100м2", "м.кв.",
+//! "10 шт."). Importers can pass a dictionary that is consulted before the
+//! built-in spellings of [`MeasureUnit::from_str_ru`].
+
+use std::collections::HashMap;
+
+use denidom_core::MeasureUnit;
+
+/// Unit dictionary keyed by the spelling used in the source
+pub type UnitMap = HashMap<String, MeasureUnit>;
+
+/// Resolve a source unit string through `unit_map`, then the built-in
+/// spellings; unknown strings become [`MeasureUnit::Other`]
+pub fn map_unit(raw: &str, unit_map: Option<&UnitMap>) -> MeasureUnit {
+    unit_map
+        .and_then(|map| map.get(raw.trim()))
+        .cloned()
+        .unwrap_or_else(|| MeasureUnit::from(raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_unit_prefers_dictionary() {
+        let map = UnitMap::from([("м.кв.".to_string(), MeasureUnit::SquareMeter)]);
+
+        assert_eq!(map_unit("м.кв.", Some(&map)), MeasureUnit::SquareMeter);
+        assert_eq!(map_unit("м³", Some(&map)), MeasureUnit::CubicMeter);
+        assert_eq!(map_unit("м.кв.", None), MeasureUnit::Other("м.кв.".to_string()));
+    }
+}
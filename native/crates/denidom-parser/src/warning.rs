@@ -0,0 +1,22 @@
+//! Non-fatal import diagnostics
+
+use serde::{Deserialize, Serialize};
+
+/// Предупреждение импорта (строка принята, но с допущениями)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportWarning {
+    /// Номер строки (с единицы)
+    pub line: usize,
+    /// Текст предупреждения
+    pub message: String,
+}
+
+impl ImportWarning {
+    /// Create a warning for a source line
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
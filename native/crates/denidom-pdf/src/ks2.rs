@@ -1,6 +1,285 @@
 //! KS-2 Document Generator
-//! 
+//!
 //! Generates "Акт о приёмке выполненных работ" (Act of acceptance of completed works)
 
-// Placeholder for future implementation
-pub struct KS2Generator;
+use std::io::Write;
+
+use denidom_core::{format_rub_grouped, Estimate, EstimateTotals, Locale, ReportLine, Result};
+use rust_decimal::Decimal;
+use printpdf::{IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point};
+
+use crate::{add_font, pdf_error};
+
+/// A4 landscape
+const PAGE_WIDTH: f32 = 297.0;
+const PAGE_HEIGHT: f32 = 210.0;
+
+const MARGIN_LEFT: f32 = 15.0;
+const MARGIN_RIGHT: f32 = 15.0;
+const TOP: f32 = 195.0;
+const BOTTOM: f32 = 20.0;
+
+const ROW_HEIGHT: f32 = 6.0;
+const TEXT_SIZE: f32 = 9.0;
+const TITLE_SIZE: f32 = 13.0;
+
+/// Table columns: header and left edge in mm
+const COLUMNS: [(&str, f32); 6] = [
+    ("№", 15.0),
+    ("Шифр", 27.0),
+    ("Наименование работ", 72.0),
+    ("Ед. изм.", 192.0),
+    ("Количество", 215.0),
+    ("Стоимость, ₽", 245.0),
+];
+
+/// Characters of the work name that fit into its column
+const NAME_WIDTH: usize = 68;
+
+/// Строка таблицы КС-2
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ks2Row {
+    /// Номер по порядку
+    pub number: usize,
+    /// Шифр расценки
+    pub code: String,
+    /// Наименование работ
+    pub name: String,
+    /// Единица измерения
+    pub unit: String,
+    /// Выполненный объём с коэффициентом позиции
+    pub quantity: Decimal,
+    /// Прямые затраты в текущих ценах
+    pub cost: Decimal,
+}
+
+/// Table rows of the КС-2 act
+///
+/// Costs come from the per-line calculation of the estimate, with section
+/// coefficients, the index and winter coefficients applied, so they add up
+/// to the direct costs of [`denidom_core::calculate_estimate_totals`].
+pub fn ks2_rows(estimate: &Estimate) -> Vec<Ks2Row> {
+    estimate
+        .line_totals(&estimate.calculation_settings())
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (item, totals))| Ks2Row {
+            number: idx + 1,
+            code: item.code.clone(),
+            name: item.name.clone(),
+            unit: item.unit.to_string(),
+            quantity: item.effective_quantity(),
+            cost: totals.direct_costs,
+        })
+        .collect()
+}
+
+/// Write a КС-2 act for `estimate` as PDF into `out`
+///
+/// The header names customer, contractor, object and estimate; the table
+/// lists the [`ks2_rows`] of the estimate; the totals
+/// block shows `totals` with the same labels as
+/// [`EstimateTotals::to_report`]. Long tables continue on new pages with
+/// the column header repeated.
+pub fn generate_ks2(estimate: &Estimate, totals: &EstimateTotals, out: &mut impl Write) -> Result<()> {
+    let (doc, page, layer) = PdfDocument::new(
+        format!("КС-2 {}", estimate.name),
+        Mm(PAGE_WIDTH),
+        Mm(PAGE_HEIGHT),
+        "КС-2",
+    );
+    let font = add_font(&doc)?;
+    let mut sheet = Sheet {
+        layer: doc.get_page(page).get_layer(layer),
+        font,
+        y: TOP,
+    };
+
+    sheet.text("Унифицированная форма № КС-2", TEXT_SIZE, PAGE_WIDTH - 75.0);
+    sheet.advance(ROW_HEIGHT * 1.5);
+    sheet.text(
+        &format!("АКТ № {} О ПРИЁМКЕ ВЫПОЛНЕННЫХ РАБОТ", estimate.number),
+        TITLE_SIZE,
+        PAGE_WIDTH / 2.0 - 55.0,
+    );
+    sheet.advance(ROW_HEIGHT * 1.5);
+    for (label, value) in [
+        ("Заказчик", &estimate.customer.name),
+        ("Подрядчик", &estimate.contractor.name),
+        ("Стройка", &estimate.object),
+        ("Смета", &estimate.name),
+    ] {
+        sheet.text(&format!("{}: {}", label, value), TEXT_SIZE, MARGIN_LEFT);
+        sheet.advance(ROW_HEIGHT);
+    }
+    sheet.advance(ROW_HEIGHT / 2.0);
+    sheet.table_header();
+
+    for row in ks2_rows(estimate) {
+        if sheet.y < BOTTOM + ROW_HEIGHT {
+            sheet.new_page(&doc);
+            sheet.table_header();
+        }
+        let cells = [
+            row.number.to_string(),
+            row.code,
+            fit(&row.name, NAME_WIDTH),
+            row.unit,
+            row.quantity.normalize().to_string(),
+            format_rub_grouped(row.cost),
+        ];
+        for ((_, x), cell) in COLUMNS.iter().zip(&cells) {
+            sheet.text(cell, TEXT_SIZE, *x);
+        }
+        sheet.advance(ROW_HEIGHT);
+    }
+    sheet.rule();
+
+    let report = totals.to_report(Locale::Ru);
+    let mut lines: Vec<&ReportLine> = vec![&report.direct_costs, &report.overhead, &report.profit];
+    if !totals.contingency.is_zero() {
+        lines.push(&report.contingency);
+    }
+    if !totals.limited_costs.is_zero() {
+        lines.push(&report.limited_costs);
+    }
+    lines.extend([&report.subtotal, &report.vat, &report.total]);
+
+    sheet.advance(ROW_HEIGHT / 2.0);
+    for line in lines {
+        if sheet.y < BOTTOM {
+            sheet.new_page(&doc);
+        }
+        sheet.text(&line.label, TEXT_SIZE, COLUMNS[2].1);
+        sheet.text(&line.formatted, TEXT_SIZE, COLUMNS[5].1);
+        sheet.advance(ROW_HEIGHT);
+    }
+
+    let bytes = doc.save_to_bytes().map_err(pdf_error)?;
+    out.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Current page layer with the vertical write position
+struct Sheet {
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    y: f32,
+}
+
+impl Sheet {
+    fn text(&self, text: &str, size: f32, x: f32) {
+        self.layer.use_text(text, size, Mm(x), Mm(self.y), &self.font);
+    }
+
+    fn advance(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    /// Horizontal line across the table just above the current row
+    fn rule(&self) {
+        let y = self.y + ROW_HEIGHT - 1.5;
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(MARGIN_LEFT), Mm(y)), false),
+                (Point::new(Mm(PAGE_WIDTH - MARGIN_RIGHT), Mm(y)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    fn table_header(&mut self) {
+        self.rule();
+        for (title, x) in COLUMNS {
+            self.text(title, TEXT_SIZE, x);
+        }
+        self.advance(ROW_HEIGHT);
+        self.rule();
+    }
+
+    fn new_page(&mut self, doc: &PdfDocumentReference) {
+        let (page, layer) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "КС-2");
+        self.layer = doc.get_page(page).get_layer(layer);
+        self.y = TOP;
+    }
+}
+
+/// Truncate `text` to `width` characters, marking the cut with an ellipsis
+fn fit(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width - 1).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use denidom_core::{calculate_estimate_totals, EstimateItem, EstimateSection, MeasureUnit, UnitCosts};
+    use rust_decimal::Decimal;
+
+    fn estimate(items: usize) -> Estimate {
+        let mut estimate = Estimate::new("Капитальный ремонт кровли".to_string(), "Школа № 5".to_string());
+        estimate.number = "12".to_string();
+        estimate.customer.name = "ГБУ «Заказчик»".to_string();
+        estimate.contractor.name = "ООО «Подрядчик»".to_string();
+
+        let mut section = EstimateSection::new(1, "Кровля".to_string());
+        for i in 0..items {
+            let mut item = EstimateItem::new(
+                format!("ФЕР12-01-{:03}-01", i),
+                "Устройство кровель плоских из наплавляемых материалов в два слоя".to_string(),
+                MeasureUnit::SquareMeter100,
+                Decimal::new(25, 1),
+            );
+            item.unit_costs = UnitCosts::new(
+                Decimal::new(1000, 0),
+                Decimal::new(300, 0),
+                Decimal::ZERO,
+                Decimal::new(700, 0),
+                Decimal::ZERO,
+            );
+            section.add_item(item);
+        }
+        estimate.sections.push(section);
+        estimate
+    }
+
+    #[test]
+    fn test_generate_ks2_pdf() {
+        for count in [3, 80] {
+            let estimate = estimate(count);
+            let totals = calculate_estimate_totals(&estimate);
+
+            let mut buffer = Vec::new();
+            generate_ks2(&estimate, &totals, &mut buffer).unwrap();
+            assert!(buffer.starts_with(b"%PDF"));
+        }
+    }
+
+    #[test]
+    fn test_rows_add_up_to_direct_costs() {
+        let mut estimate = estimate(3);
+        estimate.coefficients.index = Decimal::new(85, 1);
+        estimate.coefficients.apply_winter(5, "Бетонные и железобетонные работы").unwrap();
+        estimate.sections[0].coefficient = Some(Decimal::new(115, 2));
+        estimate.sections[0].items[1].coefficient = Some(Decimal::new(12, 1));
+        estimate.sections[0].items[2].unit_costs.machines = Decimal::new(150, 0);
+        estimate.sections[0].items[2].unit_costs.direct = Decimal::new(1150, 0);
+
+        let rows = ks2_rows(&estimate);
+        let totals = calculate_estimate_totals(&estimate);
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1].quantity, Decimal::new(3, 0));
+        assert_eq!(rows.iter().map(|row| row.cost).sum::<Decimal>(), totals.direct_costs);
+    }
+
+    #[test]
+    fn test_fit() {
+        assert_eq!(fit("Кладка", 10), "Кладка");
+        assert_eq!(fit("Разработка грунта", 5), "Разр…");
+    }
+}
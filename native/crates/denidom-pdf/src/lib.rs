@@ -8,3 +8,21 @@
 pub mod ks2;
 pub mod ks3;
 pub mod m29;
+
+pub use ks2::{generate_ks2, ks2_rows, Ks2Row};
+
+use denidom_core::DeniDomError;
+use printpdf::{IndirectFontRef, PdfDocumentReference};
+
+/// DejaVu Sans, embedded so Cyrillic renders without system fonts
+/// (Bitstream Vera license, see `assets/fonts/DejaVuSans-LICENSE.txt`)
+const FONT_REGULAR: &[u8] = include_bytes!("../assets/fonts/DejaVuSans.ttf");
+
+/// Embed the Unicode document font into `doc`
+pub(crate) fn add_font(doc: &PdfDocumentReference) -> Result<IndirectFontRef, DeniDomError> {
+    doc.add_external_font(FONT_REGULAR).map_err(pdf_error)
+}
+
+pub(crate) fn pdf_error(err: printpdf::Error) -> DeniDomError {
+    DeniDomError::Serialization(format!("PDF error: {}", err))
+}
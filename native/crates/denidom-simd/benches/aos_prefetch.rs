@@ -0,0 +1,42 @@
+//! AoS AVX2 totals with and without software prefetch on 1M items
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use denidom_simd::{CalculationSettings, ItemData, UnitCostsData};
+
+const ITEMS: usize = 1_000_000;
+
+fn make_items(count: usize) -> Vec<ItemData> {
+    (0..count)
+        .map(|i| ItemData {
+            quantity: 10.0 + (i as f64) * 0.5,
+            unit_costs: UnitCostsData {
+                direct: 1000.0 + (i as f64) * 5.0,
+                labor: 300.0 + (i as f64),
+                machine_operator: 100.0 + (i as f64) * 0.3,
+                materials: 500.0 + (i as f64) * 2.0,
+                machines: 100.0 + (i as f64) * 0.5,
+            },
+        })
+        .collect()
+}
+
+fn bench_aos_prefetch(c: &mut Criterion) {
+    let items = make_items(ITEMS);
+    let settings = CalculationSettings::default();
+
+    let mut group = c.benchmark_group("aos_1m");
+    group.bench_function("scalar", |b| {
+        b.iter(|| denidom_simd::calculate_totals_scalar(black_box(&items), &settings))
+    });
+    group.bench_function("dispatch", |b| {
+        b.iter(|| denidom_simd::calculate_estimate_totals(black_box(&items), &settings))
+    });
+    #[cfg(target_arch = "x86_64")]
+    group.bench_function("avx2_prefetch", |b| {
+        b.iter(|| denidom_simd::calculate_totals_avx2_aos_prefetch(black_box(&items), &settings))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_aos_prefetch);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+//! Per-call feature detection vs a cached dispatcher over many tiny estimates
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use denidom_simd::{CalculationSettings, ItemData, SimdDispatcher, UnitCostsData};
+
+const ESTIMATES: usize = 10_000;
+const ITEMS_PER_ESTIMATE: usize = 5;
+
+fn make_estimates() -> Vec<Vec<ItemData>> {
+    (0..ESTIMATES)
+        .map(|e| {
+            (0..ITEMS_PER_ESTIMATE)
+                .map(|i| ItemData {
+                    quantity: 1.0 + (e + i) as f64 * 0.1,
+                    unit_costs: UnitCostsData {
+                        direct: 1000.0,
+                        labor: 300.0,
+                        machine_operator: 100.0,
+                        materials: 500.0,
+                        machines: 100.0,
+                    },
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn bench_dispatch(c: &mut Criterion) {
+    let estimates = make_estimates();
+    let settings = CalculationSettings::default();
+    let dispatcher = SimdDispatcher::new();
+
+    let mut group = c.benchmark_group("tiny_estimates");
+    group.bench_function("free_function", |b| {
+        b.iter(|| {
+            for items in &estimates {
+                black_box(denidom_simd::calculate_estimate_totals(black_box(items), &settings));
+            }
+        })
+    });
+    group.bench_function("dispatcher", |b| {
+        b.iter(|| {
+            for items in &estimates {
+                black_box(dispatcher.totals(black_box(items), &settings));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_dispatch);
+criterion_main!(benches);
@@ -0,0 +1,300 @@
+//! Safe backend types behind a common trait
+//!
+//! Each backend can only be constructed once its CPU features have been
+//! detected, so [`SimdCalculator::calculate`] is safe to call and no
+//! `unsafe` leaks into downstream crates. [`best_backend`] is the single
+//! place where a backend is chosen; [`SimdDispatcher`](crate::SimdDispatcher)
+//! and [`calculate_estimate_totals`](crate::calculate_estimate_totals) go
+//! through it.
+
+use crate::scalar::calculate_totals_scalar;
+use crate::types::{CalculationSettings, CalculationTotals, ItemData};
+
+/// Totals calculator backed by a specific instruction set
+pub trait SimdCalculator: Send + Sync {
+    /// Backend name as reported by [`compiled_backends`](crate::compiled_backends)
+    fn name(&self) -> &'static str;
+
+    /// Calculate estimate totals
+    fn calculate(&self, items: &[ItemData], settings: &CalculationSettings) -> CalculationTotals;
+}
+
+/// Portable scalar backend, available everywhere
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScalarBackend;
+
+impl SimdCalculator for ScalarBackend {
+    fn name(&self) -> &'static str {
+        "scalar"
+    }
+
+    fn calculate(&self, items: &[ItemData], settings: &CalculationSettings) -> CalculationTotals {
+        calculate_totals_scalar(items, settings)
+    }
+}
+
+/// AVX2 backend for x86_64
+#[cfg(target_arch = "x86_64")]
+#[derive(Debug, Clone, Copy)]
+pub struct Avx2Backend {
+    _detected: (),
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Avx2Backend {
+    /// Return the backend if the CPU supports AVX2 and FMA
+    pub fn detect() -> Option<Self> {
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            Some(Self { _detected: () })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl SimdCalculator for Avx2Backend {
+    fn name(&self) -> &'static str {
+        "avx2"
+    }
+
+    fn calculate(&self, items: &[ItemData], settings: &CalculationSettings) -> CalculationTotals {
+        crate::simd_calc::calculate_totals_avx2(items, settings)
+    }
+}
+
+/// AVX2 backend that prefetches arrays of at least
+/// [`PREFETCH_MIN_ITEMS`](crate::PREFETCH_MIN_ITEMS) items; built with the
+/// `prefetch` feature
+#[cfg(all(target_arch = "x86_64", feature = "prefetch"))]
+#[derive(Debug, Clone, Copy)]
+pub struct Avx2PrefetchBackend {
+    avx2: Avx2Backend,
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "prefetch"))]
+impl Avx2PrefetchBackend {
+    /// Return the backend if the CPU supports AVX2 and FMA
+    pub fn detect() -> Option<Self> {
+        Avx2Backend::detect().map(|avx2| Self { avx2 })
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "prefetch"))]
+impl SimdCalculator for Avx2PrefetchBackend {
+    fn name(&self) -> &'static str {
+        "avx2-prefetch"
+    }
+
+    fn calculate(&self, items: &[ItemData], settings: &CalculationSettings) -> CalculationTotals {
+        if settings.deterministic || items.len() < crate::simd_calc::PREFETCH_MIN_ITEMS {
+            return self.avx2.calculate(items, settings);
+        }
+        crate::simd_calc::calculate_totals_avx2_aos_prefetch(items, settings)
+    }
+}
+
+/// AVX-512 backend for x86_64; built with the `avx512` feature
+#[cfg(all(target_arch = "x86_64", feature = "avx512"))]
+#[derive(Debug, Clone, Copy)]
+pub struct Avx512Backend {
+    _detected: (),
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "avx512"))]
+impl Avx512Backend {
+    /// Return the backend if the CPU supports AVX-512F
+    pub fn detect() -> Option<Self> {
+        if is_x86_feature_detected!("avx512f") {
+            Some(Self { _detected: () })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "avx512"))]
+impl SimdCalculator for Avx512Backend {
+    fn name(&self) -> &'static str {
+        "avx512"
+    }
+
+    fn calculate(&self, items: &[ItemData], settings: &CalculationSettings) -> CalculationTotals {
+        if settings.deterministic {
+            return calculate_totals_scalar(items, settings);
+        }
+        // SAFETY: the backend only exists after avx512f was detected
+        unsafe { crate::simd_calc::calculate_totals_avx512(items, settings) }
+    }
+}
+
+/// NEON backend for ARM64
+#[cfg(target_arch = "aarch64")]
+#[derive(Debug, Clone, Copy)]
+pub struct NeonBackend {
+    _detected: (),
+}
+
+#[cfg(target_arch = "aarch64")]
+impl NeonBackend {
+    /// Return the backend; NEON is mandatory on aarch64
+    pub fn detect() -> Option<Self> {
+        Some(Self { _detected: () })
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+impl SimdCalculator for NeonBackend {
+    fn name(&self) -> &'static str {
+        "neon"
+    }
+
+    fn calculate(&self, items: &[ItemData], settings: &CalculationSettings) -> CalculationTotals {
+        crate::simd_calc::calculate_totals_neon(items, settings)
+    }
+}
+
+/// Pick the fastest backend supported by the current CPU among those
+/// compiled in: AVX-512, AVX2 with prefetch, AVX2 (with FMA), NEON, scalar
+///
+/// Backends are zero-sized, so the box does not allocate.
+pub fn best_backend() -> Box<dyn SimdCalculator> {
+    #[cfg(all(target_arch = "x86_64", feature = "avx512"))]
+    {
+        if let Some(backend) = Avx512Backend::detect() {
+            return Box::new(backend);
+        }
+    }
+
+    #[cfg(all(target_arch = "x86_64", feature = "prefetch"))]
+    {
+        if let Some(backend) = Avx2PrefetchBackend::detect() {
+            return Box::new(backend);
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if let Some(backend) = Avx2Backend::detect() {
+            return Box::new(backend);
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        if let Some(backend) = NeonBackend::detect() {
+            return Box::new(backend);
+        }
+    }
+
+    Box::new(ScalarBackend)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::UnitCostsData;
+
+    fn available_backends() -> Vec<Box<dyn SimdCalculator>> {
+        let mut backends: Vec<Box<dyn SimdCalculator>> = vec![Box::new(ScalarBackend)];
+        #[cfg(target_arch = "x86_64")]
+        if let Some(backend) = Avx2Backend::detect() {
+            backends.push(Box::new(backend));
+        }
+        #[cfg(all(target_arch = "x86_64", feature = "prefetch"))]
+        if let Some(backend) = Avx2PrefetchBackend::detect() {
+            backends.push(Box::new(backend));
+        }
+        #[cfg(all(target_arch = "x86_64", feature = "avx512"))]
+        if let Some(backend) = Avx512Backend::detect() {
+            backends.push(Box::new(backend));
+        }
+        #[cfg(target_arch = "aarch64")]
+        if let Some(backend) = NeonBackend::detect() {
+            backends.push(Box::new(backend));
+        }
+        backends
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let settings = CalculationSettings::default();
+        let reference = ScalarBackend;
+
+        for count in [0, 1, 3, 4, 5, 8, 17, 100] {
+            let items: Vec<ItemData> = (0..count)
+                .map(|i| ItemData {
+                    quantity: 1.5 + i as f64,
+                    unit_costs: UnitCostsData {
+                        direct: 1000.0 + i as f64 * 10.0,
+                        labor: 300.0,
+                        machine_operator: 100.0,
+                        materials: 500.0 + i as f64,
+                        machines: 100.0,
+                    },
+                })
+                .collect();
+            let expected = reference.calculate(&items, &settings);
+
+            for backend in available_backends() {
+                let actual = backend.calculate(&items, &settings);
+                assert!(
+                    (actual.total - expected.total).abs() < 1e-6,
+                    "{} diverged at {} items",
+                    backend.name(),
+                    count
+                );
+                assert!((actual.direct_costs - expected.direct_costs).abs() < 1e-6);
+                assert!((actual.labor_costs - expected.labor_costs).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_best_backend_is_available() {
+        let best = best_backend();
+        assert!(available_backends().iter().any(|b| b.name() == best.name()));
+    }
+
+    #[test]
+    fn test_dispatch_paths_share_selection() {
+        let settings = CalculationSettings {
+            index: 8.37,
+            ..Default::default()
+        };
+        let dispatcher = crate::SimdDispatcher::new();
+        assert_eq!(dispatcher.backend(), best_backend().name());
+
+        #[cfg(all(target_arch = "x86_64", feature = "avx512"))]
+        if is_x86_feature_detected!("avx512f") {
+            assert_eq!(dispatcher.backend(), "avx512");
+        }
+
+        for count in [1, 7, 9, 1003, crate::simd_calc::PREFETCH_MIN_ITEMS + 5] {
+            let items: Vec<ItemData> = (0..count)
+                .map(|i| ItemData {
+                    quantity: 0.37 + i as f64 * 0.013,
+                    unit_costs: UnitCostsData {
+                        direct: 1234.567 + (i % 17) as f64 * 0.31,
+                        labor: 312.05,
+                        machine_operator: 98.3,
+                        materials: 511.11 + (i % 5) as f64 * 0.7,
+                        machines: 101.29,
+                    },
+                })
+                .collect();
+            let expected = ScalarBackend.calculate(&items, &settings);
+
+            for actual in [
+                best_backend().calculate(&items, &settings),
+                dispatcher.totals(&items, &settings),
+                crate::calculate_estimate_totals(&items, &settings),
+            ] {
+                let tolerance = 1e-9 * expected.total;
+                assert!((actual.total - expected.total).abs() < tolerance, "{} items", count);
+                assert!((actual.labor_costs - expected.labor_costs).abs() < tolerance);
+                assert!((actual.material_costs - expected.material_costs).abs() < tolerance);
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Totals for many estimates laid out in one item buffer
+//!
+//! A batch job stores M estimates back to back in a single `Vec<ItemData>`;
+//! `offsets[i]` is where estimate `i` starts and it runs up to the next
+//! offset (or the end of the buffer for the last one).
+
+use crate::dispatch::SimdDispatcher;
+use crate::types::{CalculationSettings, CalculationTotals, ItemData};
+
+/// Calculate totals of each estimate segment with its own settings
+///
+/// Backend detection runs once for the whole batch.
+///
+/// # Panics
+///
+/// Panics if `offsets` and `settings` differ in length, or if the offsets
+/// are not sorted or point past the end of `items`.
+pub fn calculate_batch_totals(
+    items: &[ItemData],
+    offsets: &[usize],
+    settings: &[CalculationSettings],
+) -> Vec<CalculationTotals> {
+    assert_eq!(
+        offsets.len(),
+        settings.len(),
+        "one settings entry is required per segment"
+    );
+    assert!(
+        offsets.windows(2).all(|pair| pair[0] <= pair[1]),
+        "segment offsets must be sorted"
+    );
+    assert!(
+        offsets.last().is_none_or(|&last| last <= items.len()),
+        "segment offset out of bounds"
+    );
+
+    let dispatcher = SimdDispatcher::new();
+    let ends = offsets.iter().skip(1).copied().chain(std::iter::once(items.len()));
+
+    offsets
+        .iter()
+        .zip(ends)
+        .zip(settings)
+        .map(|((&start, end), settings)| dispatcher.totals(&items[start..end], settings))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simd_calc::calculate_estimate_totals;
+    use crate::types::UnitCostsData;
+
+    fn items(count: usize, price: f64) -> Vec<ItemData> {
+        (0..count)
+            .map(|i| ItemData {
+                quantity: 2.0 + i as f64,
+                unit_costs: UnitCostsData {
+                    direct: price,
+                    labor: price * 0.3,
+                    machine_operator: price * 0.1,
+                    materials: price * 0.5,
+                    machines: price * 0.1,
+                },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_batch_matches_individual_calls() {
+        let segments = [items(5, 1000.0), items(0, 0.0), items(11, 250.0)];
+        let settings = [
+            CalculationSettings::default(),
+            CalculationSettings::default(),
+            CalculationSettings {
+                index: 8.5,
+                ..Default::default()
+            },
+        ];
+
+        let mut buffer = Vec::new();
+        let mut offsets = Vec::new();
+        for segment in &segments {
+            offsets.push(buffer.len());
+            buffer.extend_from_slice(segment);
+        }
+
+        let batch = calculate_batch_totals(&buffer, &offsets, &settings);
+
+        assert_eq!(batch.len(), 3);
+        for ((totals, segment), settings) in batch.iter().zip(&segments).zip(&settings) {
+            let expected = calculate_estimate_totals(segment, settings);
+            assert_eq!(totals.total, expected.total);
+            assert_eq!(totals.direct_costs, expected.direct_costs);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "sorted")]
+    fn test_unsorted_offsets_panic() {
+        let buffer = items(4, 100.0);
+        let settings = [CalculationSettings::default(); 2];
+        calculate_batch_totals(&buffer, &[2, 1], &settings);
+    }
+}
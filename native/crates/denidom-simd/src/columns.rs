@@ -0,0 +1,251 @@
+//! Zero-copy column view over a raw `f64` buffer
+//!
+//! FFI callers and memory-mapped files hand over item data column-major:
+//! `rows` quantities, then `rows` values each of direct, labor, machine
+//! operator, materials and machines costs, as native-endian `f64`.
+
+use denidom_core::DeniDomError;
+
+use crate::scalar::finalize_totals;
+use crate::types::{CalculationSettings, CalculationTotals};
+
+/// Number of columns in the buffer
+pub const COLUMN_COUNT: usize = 6;
+
+/// Borrowed columns of item data
+#[derive(Debug, Clone, Copy)]
+pub struct ColumnView<'a> {
+    quantities: &'a [f64],
+    direct: &'a [f64],
+    labor: &'a [f64],
+    machine_operator: &'a [f64],
+    materials: &'a [f64],
+    machines: &'a [f64],
+}
+
+impl<'a> ColumnView<'a> {
+    /// View `bytes` as six `f64` columns of `rows` values each
+    ///
+    /// Fails if the buffer is not aligned to `f64` or its length is not
+    /// exactly `rows × 6 × 8` bytes.
+    pub fn from_bytes(bytes: &'a [u8], rows: usize) -> Result<Self, DeniDomError> {
+        let expected = rows
+            .checked_mul(COLUMN_COUNT * std::mem::size_of::<f64>())
+            .ok_or_else(|| DeniDomError::Validation(format!("row count {} is too large", rows)))?;
+        if bytes.len() != expected {
+            return Err(DeniDomError::Validation(format!(
+                "column buffer has {} bytes, expected {} for {} rows",
+                bytes.len(),
+                expected,
+                rows
+            )));
+        }
+
+        // SAFETY: every bit pattern is a valid f64; prefix and suffix are
+        // checked to be empty, so the middle covers the whole buffer
+        let (prefix, values, suffix) = unsafe { bytes.align_to::<f64>() };
+        if !prefix.is_empty() || !suffix.is_empty() {
+            return Err(DeniDomError::Validation(
+                "column buffer is not aligned to f64".to_string(),
+            ));
+        }
+
+        Ok(Self::from_slice(values, rows))
+    }
+
+    fn from_slice(values: &'a [f64], rows: usize) -> Self {
+        let column = |index: usize| &values[index * rows..(index + 1) * rows];
+        Self {
+            quantities: column(0),
+            direct: column(1),
+            labor: column(2),
+            machine_operator: column(3),
+            materials: column(4),
+            machines: column(5),
+        }
+    }
+
+    /// Number of rows
+    pub fn rows(&self) -> usize {
+        self.quantities.len()
+    }
+
+    /// Quantities
+    pub fn quantities(&self) -> &'a [f64] {
+        self.quantities
+    }
+
+    /// Direct unit costs
+    pub fn direct(&self) -> &'a [f64] {
+        self.direct
+    }
+
+    /// Labor unit costs
+    pub fn labor(&self) -> &'a [f64] {
+        self.labor
+    }
+
+    /// Machine operator unit costs
+    pub fn machine_operator(&self) -> &'a [f64] {
+        self.machine_operator
+    }
+
+    /// Materials unit costs
+    pub fn materials(&self) -> &'a [f64] {
+        self.materials
+    }
+
+    /// Machines unit costs
+    pub fn machines(&self) -> &'a [f64] {
+        self.machines
+    }
+}
+
+/// Calculate totals straight from a column view
+pub fn calculate_estimate_totals_columns(
+    view: &ColumnView,
+    settings: &CalculationSettings,
+) -> CalculationTotals {
+    #[cfg(target_arch = "x86_64")]
+    let mut totals = if !settings.deterministic
+        && is_x86_feature_detected!("avx2")
+        && is_x86_feature_detected!("fma")
+    {
+        // SAFETY: AVX2 and FMA support was just checked
+        unsafe { accumulate_columns_avx2(view) }
+    } else {
+        accumulate_columns_scalar(view)
+    };
+
+    #[cfg(not(target_arch = "x86_64"))]
+    let mut totals = accumulate_columns_scalar(view);
+
+    finalize_totals(&mut totals, settings);
+    totals
+}
+
+fn accumulate_columns_scalar(view: &ColumnView) -> CalculationTotals {
+    let dot = |costs: &[f64]| -> f64 {
+        view.quantities.iter().zip(costs).map(|(q, c)| q * c).sum()
+    };
+    CalculationTotals {
+        direct_costs: dot(view.direct),
+        labor_costs: dot(view.labor),
+        machine_op_costs: dot(view.machine_operator),
+        material_costs: dot(view.materials),
+        machine_costs: dot(view.machines),
+        ..Default::default()
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+unsafe fn accumulate_columns_avx2(view: &ColumnView) -> CalculationTotals {
+    use std::arch::x86_64::*;
+
+    use crate::simd_calc::hsum_pd;
+
+    let len = view.rows();
+    let columns = [view.direct, view.labor, view.machine_operator, view.materials, view.machines];
+    // Every column has exactly `len` values, so 4-lane loads at idx + 4 <= len stay in bounds
+    assert!(columns.iter().all(|column| column.len() == len));
+
+    let mut sums = [_mm256_setzero_pd(); 5];
+    let chunks = len / 4;
+    for i in 0..chunks {
+        let idx = i * 4;
+        let q = _mm256_loadu_pd(view.quantities.as_ptr().add(idx));
+        for (sum, column) in sums.iter_mut().zip(&columns) {
+            *sum = _mm256_fmadd_pd(q, _mm256_loadu_pd(column.as_ptr().add(idx)), *sum);
+        }
+    }
+
+    let mut totals = [0.0; 5];
+    for ((total, sum), column) in totals.iter_mut().zip(sums).zip(&columns) {
+        *total = hsum_pd(sum);
+        for idx in (chunks * 4)..len {
+            *total += view.quantities[idx] * column[idx];
+        }
+    }
+
+    CalculationTotals {
+        direct_costs: totals[0],
+        labor_costs: totals[1],
+        machine_op_costs: totals[2],
+        material_costs: totals[3],
+        machine_costs: totals[4],
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simd_calc::calculate_estimate_totals;
+    use crate::types::{ItemData, UnitCostsData};
+
+    fn sample_items(count: usize) -> Vec<ItemData> {
+        (0..count)
+            .map(|i| ItemData {
+                quantity: 1.5 + i as f64,
+                unit_costs: UnitCostsData {
+                    direct: 1000.0 + i as f64,
+                    labor: 300.0,
+                    machine_operator: 50.0,
+                    materials: 550.0 + i as f64,
+                    machines: 100.0,
+                },
+            })
+            .collect()
+    }
+
+    fn column_major(items: &[ItemData]) -> Vec<f64> {
+        let mut values = Vec::with_capacity(items.len() * COLUMN_COUNT);
+        values.extend(items.iter().map(|item| item.quantity));
+        values.extend(items.iter().map(|item| item.unit_costs.direct));
+        values.extend(items.iter().map(|item| item.unit_costs.labor));
+        values.extend(items.iter().map(|item| item.unit_costs.machine_operator));
+        values.extend(items.iter().map(|item| item.unit_costs.materials));
+        values.extend(items.iter().map(|item| item.unit_costs.machines));
+        values
+    }
+
+    fn as_bytes(values: &[f64]) -> &[u8] {
+        // SAFETY: f64 has no padding and u8 has alignment 1
+        unsafe {
+            std::slice::from_raw_parts(values.as_ptr().cast::<u8>(), std::mem::size_of_val(values))
+        }
+    }
+
+    #[test]
+    fn test_totals_from_byte_buffer() {
+        let items = sample_items(11);
+        let values = column_major(&items);
+        let view = ColumnView::from_bytes(as_bytes(&values), items.len()).unwrap();
+
+        assert_eq!(view.rows(), 11);
+        assert_eq!(view.quantities()[3], 4.5);
+        assert_eq!(view.materials()[10], 560.0);
+
+        let settings = CalculationSettings::default();
+        let expected = calculate_estimate_totals(&items, &settings);
+        let actual = calculate_estimate_totals_columns(&view, &settings);
+
+        assert!((actual.total - expected.total).abs() < 1e-6);
+        assert!((actual.labor_costs - expected.labor_costs).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_rejects_bad_buffers() {
+        let mut values = column_major(&sample_items(4));
+        let len = std::mem::size_of_val(values.as_slice());
+
+        assert!(ColumnView::from_bytes(as_bytes(&values), 5).is_err());
+        assert!(ColumnView::from_bytes(&as_bytes(&values)[..len - 8], 4).is_err());
+
+        // Right length, but shifted one byte off the f64 boundary
+        values.push(0.0);
+        let shifted = &as_bytes(&values)[1..=len];
+        assert!(ColumnView::from_bytes(shifted, 4).is_err());
+    }
+}
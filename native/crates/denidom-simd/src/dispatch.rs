@@ -0,0 +1,82 @@
+//! Backend dispatch with one-time feature detection
+//!
+//! [`calculate_estimate_totals`](crate::calculate_estimate_totals) runs CPU
+//! feature detection on every call. For many small estimates in a loop,
+//! [`SimdDispatcher`] detects the backend once and keeps it.
+
+use crate::backend::{best_backend, ScalarBackend, SimdCalculator};
+use crate::types::{CalculationSettings, CalculationTotals, ItemData};
+
+/// Totals calculator bound to the best backend detected at construction
+#[derive(Clone, Copy)]
+pub struct SimdDispatcher {
+    backend: &'static dyn SimdCalculator,
+}
+
+impl SimdDispatcher {
+    /// Detect the best available backend with [`best_backend`]
+    pub fn new() -> Self {
+        // Backends are zero-sized, so leaking the box costs no memory
+        Self {
+            backend: Box::leak(best_backend()),
+        }
+    }
+
+    /// Dispatcher forced to the scalar backend
+    pub fn scalar() -> Self {
+        Self {
+            backend: &ScalarBackend,
+        }
+    }
+
+    /// Name of the selected backend
+    pub fn backend(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    /// Calculate totals with the selected backend
+    #[inline]
+    pub fn totals(&self, items: &[ItemData], settings: &CalculationSettings) -> CalculationTotals {
+        self.backend.calculate(items, settings)
+    }
+}
+
+impl Default for SimdDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simd_calc::calculate_estimate_totals;
+    use crate::types::UnitCostsData;
+
+    #[test]
+    fn test_dispatcher_matches_free_function() {
+        let dispatcher = SimdDispatcher::new();
+        let settings = CalculationSettings::default();
+
+        for count in [0, 1, 3, 4, 7, 100] {
+            let items: Vec<ItemData> = (0..count)
+                .map(|i| ItemData {
+                    quantity: 10.0 + i as f64,
+                    unit_costs: UnitCostsData {
+                        direct: 1000.0 + i as f64 * 10.0,
+                        labor: 300.0,
+                        machine_operator: 100.0,
+                        materials: 500.0,
+                        machines: 100.0,
+                    },
+                })
+                .collect();
+
+            let expected = calculate_estimate_totals(&items, &settings);
+            let actual = dispatcher.totals(&items, &settings);
+
+            assert_eq!(actual.total, expected.total);
+            assert_eq!(actual.direct_costs, expected.direct_costs);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Decimal-exact totals for audit-grade sign-off
+//!
+//! The SIMD paths work in f64 and drift from the `Decimal` calculator in
+//! denidom-core by up to a ruble on large estimates. This path keeps every
+//! step in `Decimal` and only parallelizes the summation across items.
+
+use denidom_core::{apply_index_and_markups, CalculationSettings, EstimateItem, EstimateTotals};
+use rayon::prelude::*;
+
+/// Estimate totals in `Decimal`, summing items in parallel with rayon
+///
+/// Precision guarantee: the result is identical, value and scale, to the
+/// serial [`denidom_core::calculate_totals`]. Per-item costs are the same
+/// `Decimal` products, and `Decimal` addition of these values is exact, so
+/// the order in which rayon combines partial sums does not matter (as long
+/// as no sum exceeds 28 significant digits, far beyond any estimate).
+pub fn calculate_totals_exact(items: &[EstimateItem], settings: &CalculationSettings) -> EstimateTotals {
+    let mut totals = items
+        .par_iter()
+        .map(|item| EstimateTotals {
+            direct_costs: item.total_direct_cost(),
+            labor_costs: item.total_labor_cost(),
+            machine_operator_costs: item.total_machine_operator_cost(),
+            material_costs: item.total_material_cost(),
+            machine_costs: item.total_machine_cost(),
+            ..Default::default()
+        })
+        .reduce(EstimateTotals::default, |a, b| a + b);
+
+    apply_index_and_markups(&mut totals, settings);
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use denidom_core::{calculate_totals, MeasureUnit, UnitCosts};
+    use rust_decimal::Decimal;
+
+    fn items(count: i64) -> Vec<EstimateItem> {
+        (0..count)
+            .map(|i| {
+                let mut item = EstimateItem::new(
+                    format!("ФЕР01-01-{:03}-01", i % 1000),
+                    "Работа".to_string(),
+                    MeasureUnit::CubicMeter,
+                    Decimal::new(1_003 + i * 37, 3),
+                );
+                let labor = Decimal::new(12_345 + i, 2);
+                let materials = Decimal::new(67_891 + i * 3, 2);
+                item.unit_costs = UnitCosts::new(
+                    labor + materials + Decimal::new(1_001, 2),
+                    labor,
+                    Decimal::new(1_001, 2),
+                    materials,
+                    Decimal::ZERO,
+                );
+                item
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_exact_matches_serial_bit_for_bit() {
+        let items = items(10_007);
+        let refs: Vec<&EstimateItem> = items.iter().collect();
+        let settings = CalculationSettings {
+            index: Decimal::new(853, 2),
+            ..Default::default()
+        };
+
+        let serial = calculate_totals(&refs, &settings);
+        let exact = calculate_totals_exact(&items, &settings);
+
+        let fields = |t: &EstimateTotals| {
+            [
+                t.direct_costs,
+                t.labor_costs,
+                t.machine_operator_costs,
+                t.material_costs,
+                t.machine_costs,
+                t.overhead,
+                t.profit,
+                t.contingency,
+                t.limited_costs,
+                t.subtotal,
+                t.vat,
+                t.total,
+            ]
+            .map(|value| value.serialize())
+        };
+        assert_eq!(fields(&exact), fields(&serial));
+    }
+}
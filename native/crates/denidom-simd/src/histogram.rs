@@ -0,0 +1,150 @@
+//! Distribution of line totals for cost histograms, percentiles and
+//! cumulative cost charts
+
+use crate::simd_calc::calculate_lines_and_totals;
+use crate::types::{CalculationSettings, ItemData};
+
+/// Line totals bucketed into equal-width bins over `[min, max]`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    pub counts: Vec<usize>,
+}
+
+/// Bucket line totals (quantity × direct) into `bins` equal-width bins
+///
+/// Line totals are computed in one SIMD pass, binning is scalar. The maximum
+/// falls into the last bin. Empty input yields zero counts with `min == max == 0`,
+/// `bins == 0` yields no counts.
+pub fn line_total_histogram(items: &[ItemData], bins: usize) -> Histogram {
+    if items.is_empty() || bins == 0 {
+        return Histogram {
+            counts: vec![0; bins],
+            ..Histogram::default()
+        };
+    }
+
+    let (lines, _) = calculate_lines_and_totals(items, &CalculationSettings::default());
+    let (min, max) = lines
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| (min.min(v), max.max(v)));
+
+    let mut counts = vec![0; bins];
+    let width = (max - min) / bins as f64;
+    for &line in &lines {
+        let bin = if width > 0.0 {
+            (((line - min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        counts[bin] += 1;
+    }
+
+    Histogram { min, max, counts }
+}
+
+/// Percentile `p` in `[0, 1]` of line totals (quantity × direct)
+///
+/// Line totals are computed in one SIMD pass, then the nearest-rank element
+/// (index `round(p × (n − 1))` of the sorted totals) is found by selection
+/// in O(n) without a full sort. Returns NaN for empty input or `p` outside
+/// `[0, 1]`.
+pub fn line_total_percentile(items: &[ItemData], p: f64) -> f64 {
+    if items.is_empty() || !(0.0..=1.0).contains(&p) {
+        return f64::NAN;
+    }
+
+    let (mut lines, _) = calculate_lines_and_totals(items, &CalculationSettings::default());
+    let rank = (p * (lines.len() - 1) as f64).round() as usize;
+    *lines.select_nth_unstable_by(rank, f64::total_cmp).1
+}
+
+/// Running sums of line totals (quantity × direct, before index): element
+/// `i` is the sum of lines `0..=i`
+///
+/// Line totals are computed in one SIMD pass, the prefix scan is scalar.
+pub fn line_totals_prefix_sum(items: &[ItemData]) -> Vec<f64> {
+    let (mut lines, _) = calculate_lines_and_totals(items, &CalculationSettings::default());
+    let mut running = 0.0;
+    for line in &mut lines {
+        running += *line;
+        *line = running;
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::UnitCostsData;
+
+    fn item(quantity: f64, direct: f64) -> ItemData {
+        ItemData {
+            quantity,
+            unit_costs: UnitCostsData {
+                direct,
+                ..UnitCostsData::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_line_total_histogram() {
+        // Line totals: 0, 10, 20, ..., 100
+        let items: Vec<ItemData> = (0..=10).map(|i| item(i as f64, 10.0)).collect();
+
+        let histogram = line_total_histogram(&items, 4);
+
+        assert_eq!(histogram.min, 0.0);
+        assert_eq!(histogram.max, 100.0);
+        // Bins of width 25: [0,25) [25,50) [50,75) [75,100]
+        assert_eq!(histogram.counts, vec![3, 2, 3, 3]);
+    }
+
+    #[test]
+    fn test_histogram_edge_cases() {
+        assert_eq!(line_total_histogram(&[], 3).counts, vec![0, 0, 0]);
+        assert!(line_total_histogram(&[item(1.0, 1.0)], 0).counts.is_empty());
+
+        let same = line_total_histogram(&[item(2.0, 5.0), item(1.0, 10.0)], 3);
+        assert_eq!(same.counts, vec![2, 0, 0]);
+    }
+
+    #[test]
+    fn test_line_totals_prefix_sum() {
+        let items: Vec<ItemData> = (1..=103).map(|i| item(i as f64, 2.5)).collect();
+
+        let prefix = line_totals_prefix_sum(&items);
+        let (_, totals) = calculate_lines_and_totals(&items, &CalculationSettings::default());
+
+        assert_eq!(prefix.len(), items.len());
+        assert_eq!(prefix[0], 2.5);
+        assert!((prefix[102] - totals.direct_costs).abs() < 1e-9);
+        assert!(prefix.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(line_totals_prefix_sum(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_line_total_percentile_matches_sorted() {
+        // Shuffled line totals so selection has work to do
+        let items: Vec<ItemData> = (0..1000)
+            .map(|i| item(((i * 7919) % 1000) as f64, 3.5))
+            .collect();
+
+        let mut sorted: Vec<f64> = items
+            .iter()
+            .map(|item| item.quantity * item.unit_costs.direct)
+            .collect();
+        sorted.sort_by(f64::total_cmp);
+
+        for p in [0.5, 0.95] {
+            let rank = (p * (sorted.len() - 1) as f64).round() as usize;
+            assert_eq!(line_total_percentile(&items, p), sorted[rank]);
+        }
+
+        assert!(line_total_percentile(&[], 0.5).is_nan());
+        assert!(line_total_percentile(&items, 1.5).is_nan());
+        assert!(line_total_percentile(&items, f64::NAN).is_nan());
+    }
+}
@@ -3,6 +3,12 @@
 //! This crate provides SIMD-optimized functions for estimate calculations.
 //! Supports SSE, AVX2, AVX-512 (x86/x64) and NEON (ARM).
 
+pub mod backend;
+pub mod batch;
+pub mod columns;
+pub mod dispatch;
+pub mod exact;
+pub mod histogram;
 pub mod scalar;
 pub mod simd_calc;
 pub mod types;
@@ -10,6 +16,12 @@ pub mod types;
 #[cfg(feature = "native-c")]
 pub mod ffi;
 
+pub use backend::*;
+pub use batch::*;
+pub use columns::*;
+pub use dispatch::*;
+pub use exact::*;
+pub use histogram::*;
 pub use scalar::*;
 pub use simd_calc::*;
 pub use types::*;
@@ -10,9 +10,15 @@ pub fn calculate_totals_scalar(
     items: &[ItemData],
     settings: &CalculationSettings,
 ) -> CalculationTotals {
+    let mut totals = accumulate_items_scalar(items);
+    finalize_totals(&mut totals, settings);
+    totals
+}
+
+/// Sum the five raw cost components (quantity × unit cost)
+pub fn accumulate_items_scalar(items: &[ItemData]) -> CalculationTotals {
     let mut totals = CalculationTotals::default();
 
-    // Sum all costs
     for item in items {
         let q = item.quantity;
         totals.direct_costs += q * item.unit_costs.direct;
@@ -22,6 +28,61 @@ pub fn calculate_totals_scalar(
         totals.machine_costs += q * item.unit_costs.machines;
     }
 
+    totals
+}
+
+/// Sum the five raw cost components of included items only
+///
+/// `included` must have the same length as `items`.
+pub fn accumulate_items_masked_scalar(items: &[ItemData], included: &[bool]) -> CalculationTotals {
+    let mut totals = CalculationTotals::default();
+
+    for (item, &include) in items.iter().zip(included) {
+        let q = if include { item.quantity } else { 0.0 };
+        totals.direct_costs += q * item.unit_costs.direct;
+        totals.labor_costs += q * item.unit_costs.labor;
+        totals.machine_op_costs += q * item.unit_costs.machine_operator;
+        totals.material_costs += q * item.unit_costs.materials;
+        totals.machine_costs += q * item.unit_costs.machines;
+    }
+
+    totals
+}
+
+/// Turn accumulated cost sums into final totals: apply index, then
+/// overhead and profit from ФОТ, subtotal, VAT and total
+pub fn finalize(accumulated: CalculationTotals, settings: &CalculationSettings) -> CalculationTotals {
+    let mut totals = accumulated;
+    finalize_totals(&mut totals, settings);
+    totals
+}
+
+/// Calculate per-line direct totals (quantity * direct, before index)
+/// and the aggregate totals in a single pass
+pub fn calculate_lines_and_totals_scalar(
+    items: &[ItemData],
+    settings: &CalculationSettings,
+) -> (Vec<f64>, CalculationTotals) {
+    let mut lines = Vec::with_capacity(items.len());
+    let mut totals = CalculationTotals::default();
+
+    for item in items {
+        let q = item.quantity;
+        let line = q * item.unit_costs.direct;
+        lines.push(line);
+        totals.direct_costs += line;
+        totals.labor_costs += q * item.unit_costs.labor;
+        totals.machine_op_costs += q * item.unit_costs.machine_operator;
+        totals.material_costs += q * item.unit_costs.materials;
+        totals.machine_costs += q * item.unit_costs.machines;
+    }
+
+    finalize_totals(&mut totals, settings);
+    (lines, totals)
+}
+
+/// Apply index, overhead, profit and VAT to summed component costs
+pub(crate) fn finalize_totals(totals: &mut CalculationTotals, settings: &CalculationSettings) {
     // Apply index
     totals.direct_costs *= settings.index;
     totals.labor_costs *= settings.index;
@@ -29,21 +90,15 @@ pub fn calculate_totals_scalar(
     totals.material_costs *= settings.index;
     totals.machine_costs *= settings.index;
 
-    // Calculate overhead and profit from labor costs (ФОТ = ОЗП + ЗПМ)
+    // Overhead and profit from ФОТ (ОЗП + ЗПМ)
     let labor_total = totals.labor_costs + totals.machine_op_costs;
     totals.overhead = labor_total * settings.overhead_rate;
     totals.profit = labor_total * settings.profit_rate;
 
-    // Subtotal
+    // Subtotal, VAT, Total
     totals.subtotal = totals.direct_costs + totals.overhead + totals.profit;
-
-    // VAT
     totals.vat = totals.subtotal * settings.vat_rate;
-
-    // Total
     totals.total = totals.subtotal + totals.vat;
-
-    totals
 }
 
 /// Calculate item totals (quantity * unit_cost * coefficient)
@@ -4,42 +4,168 @@
 //! - AVX2/AVX-512 on x86_64
 //! - NEON on ARM64
 //! - Fallback to scalar on other platforms
+//!
+//! SIMD paths reorder floating-point additions, so they may differ from the
+//! scalar result in the last bits. [`CalculationSettings::deterministic`]
+//! routes every entry point to the scalar order.
 
+use crate::backend::best_backend;
 use crate::types::{CalculationSettings, CalculationTotals, ItemData};
-use crate::scalar::{calculate_totals_scalar, calculate_items_scalar};
+use crate::scalar::{
+    accumulate_items_masked_scalar, accumulate_items_scalar, calculate_items_scalar,
+    calculate_lines_and_totals_scalar, calculate_totals_scalar, finalize_totals,
+};
 
 /// Calculate estimate totals using best available SIMD
 ///
-/// Automatically selects the optimal implementation:
+/// The backend is chosen by [`best_backend`]:
 /// - AVX-512 if available and enabled
-/// - AVX2 if available
+/// - AVX2 with prefetch for large arrays if enabled
+/// - AVX2 with FMA if available
 /// - NEON on ARM
 /// - Scalar fallback
+///
+/// With `settings.deterministic` the scalar path is always used.
 pub fn calculate_estimate_totals(
     items: &[ItemData],
     settings: &CalculationSettings,
 ) -> CalculationTotals {
-    #[cfg(all(target_arch = "x86_64", feature = "avx512"))]
+    if settings.deterministic {
+        return calculate_totals_scalar(items, settings);
+    }
+
+    best_backend().calculate(items, settings)
+}
+
+/// Calculate estimate totals counting only items with `included[i] == true`
+///
+/// Excluded items are zeroed in the SIMD loop by masking their quantity, so
+/// toggling an item does not require rebuilding the item vector. If the
+/// mask length differs from `items`, every item is included.
+pub fn calculate_estimate_totals_masked(
+    items: &[ItemData],
+    included: &[bool],
+    settings: &CalculationSettings,
+) -> CalculationTotals {
+    if included.len() != items.len() {
+        return calculate_estimate_totals(items, settings);
+    }
+    if settings.deterministic {
+        let mut totals = accumulate_items_masked_scalar(items, included);
+        finalize_totals(&mut totals, settings);
+        return totals;
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    let mut totals = if crate::backend::Avx2Backend::detect().is_some() {
+        accumulate_items_avx2(items, Some(included))
+    } else {
+        accumulate_items_masked_scalar(items, included)
+    };
+
+    #[cfg(not(target_arch = "x86_64"))]
+    let mut totals = accumulate_items_masked_scalar(items, included);
+
+    finalize_totals(&mut totals, settings);
+    totals
+}
+
+/// Calculate per-line direct totals and aggregate totals in one pass
+///
+/// Line totals are `quantity * direct` before the index is applied, so their
+/// sum times `settings.index` equals `direct_costs` of the returned totals.
+pub fn calculate_lines_and_totals(
+    items: &[ItemData],
+    settings: &CalculationSettings,
+) -> (Vec<f64>, CalculationTotals) {
+    #[cfg(target_arch = "x86_64")]
     {
-        if is_x86_feature_detected!("avx512f") {
-            return unsafe { calculate_totals_avx512(items, settings) };
+        if !settings.deterministic
+            && is_x86_feature_detected!("avx2")
+            && is_x86_feature_detected!("fma")
+        {
+            return unsafe { calculate_lines_and_totals_avx2(items, settings) };
         }
     }
 
+    calculate_lines_and_totals_scalar(items, settings)
+}
+
+/// Accumulate the five raw cost sums (quantity × unit cost) without index,
+/// overhead, profit or VAT; the remaining fields are zero
+///
+/// Accumulators of separate parts (e.g. cached sections) can be combined with
+/// [`CalculationTotals::merge`] and turned into final totals once with
+/// [`finalize`](crate::scalar::finalize).
+pub fn accumulate_items(items: &[ItemData]) -> CalculationTotals {
     #[cfg(target_arch = "x86_64")]
     {
-        if is_x86_feature_detected!("avx2") {
-            return calculate_totals_avx2(items, settings);
+        if crate::backend::Avx2Backend::detect().is_some() {
+            return accumulate_items_avx2(items, None);
         }
     }
 
-    #[cfg(target_arch = "aarch64")]
-    {
-        return calculate_totals_neon(items, settings);
+    accumulate_items_scalar(items)
+}
+
+/// Items buffered per chunk by [`calculate_estimate_totals_iter`]
+pub const ITER_CHUNK_ITEMS: usize = 64;
+
+/// Calculate estimate totals from an iterator without collecting it
+///
+/// Items are buffered in chunks of [`ITER_CHUNK_ITEMS`] and each chunk is
+/// accumulated with [`accumulate_items`], so memory use stays constant for
+/// lazily produced items (e.g. a DB cursor). Chunk partials are merged in
+/// order, which is a different summation order than the slice version;
+/// the trailing partial chunk is short and gains little from SIMD, and the
+/// prefetching and AVX-512 paths of [`calculate_estimate_totals`] are not
+/// used. With `settings.deterministic` items are summed one by one in
+/// scalar order and match [`calculate_totals_scalar`] bit for bit.
+pub fn calculate_estimate_totals_iter(
+    items: impl Iterator<Item = ItemData>,
+    settings: &CalculationSettings,
+) -> CalculationTotals {
+    let mut totals = CalculationTotals::default();
+
+    if settings.deterministic {
+        for item in items {
+            totals.merge(&accumulate_items_scalar(std::slice::from_ref(&item)));
+        }
+        finalize_totals(&mut totals, settings);
+        return totals;
     }
 
-    // Fallback to scalar
-    calculate_totals_scalar(items, settings)
+    let mut chunk = Vec::with_capacity(ITER_CHUNK_ITEMS);
+    for item in items {
+        chunk.push(item);
+        if chunk.len() == ITER_CHUNK_ITEMS {
+            totals.merge(&accumulate_items(&chunk));
+            chunk.clear();
+        }
+    }
+    if !chunk.is_empty() {
+        totals.merge(&accumulate_items(&chunk));
+    }
+
+    finalize_totals(&mut totals, settings);
+    totals
+}
+
+/// Totals of two estimates and their element-wise difference `b - a`,
+/// as `(totals_a, totals_b, delta)`
+///
+/// Both sides run through [`calculate_estimate_totals`] concurrently on the
+/// rayon pool, so each matches its standalone result exactly.
+pub fn compare_totals(
+    a: &[ItemData],
+    b: &[ItemData],
+    settings: &CalculationSettings,
+) -> (CalculationTotals, CalculationTotals, CalculationTotals) {
+    let (totals_a, totals_b) = rayon::join(
+        || calculate_estimate_totals(a, settings),
+        || calculate_estimate_totals(b, settings),
+    );
+    (totals_a, totals_b, totals_a.delta_to(&totals_b))
 }
 
 /// Calculate item results using best available SIMD
@@ -62,17 +188,38 @@ pub fn calculate_items(
 
 /// AVX2 implementation for x86_64
 #[cfg(target_arch = "x86_64")]
-fn calculate_totals_avx2(
+pub(crate) fn calculate_totals_avx2(
     items: &[ItemData],
     settings: &CalculationSettings,
 ) -> CalculationTotals {
+    if settings.deterministic {
+        return calculate_totals_scalar(items, settings);
+    }
+
+    let mut totals = accumulate_items_avx2(items, None);
+    finalize_totals(&mut totals, settings);
+    totals
+}
+
+/// AVX2 accumulation of the five cost sums; with a mask (same length as
+/// `items`), quantities of excluded items are multiplied by zero
+#[cfg(target_arch = "x86_64")]
+fn accumulate_items_avx2(items: &[ItemData], mask: Option<&[bool]>) -> CalculationTotals {
     use std::arch::x86_64::*;
 
     let len = items.len();
     if len < 4 {
-        return calculate_totals_scalar(items, settings);
+        return match mask {
+            Some(included) => accumulate_items_masked_scalar(items, included),
+            None => accumulate_items_scalar(items),
+        };
     }
 
+    let weight = |idx: usize| match mask {
+        Some(included) if !included[idx] => 0.0,
+        _ => 1.0,
+    };
+
     unsafe {
         let mut direct_sum = _mm256_setzero_pd();
         let mut labor_sum = _mm256_setzero_pd();
@@ -87,10 +234,10 @@ fn calculate_totals_avx2(
 
             // Load quantities for 4 items
             let q = _mm256_set_pd(
-                items[idx + 3].quantity,
-                items[idx + 2].quantity,
-                items[idx + 1].quantity,
-                items[idx].quantity,
+                items[idx + 3].quantity * weight(idx + 3),
+                items[idx + 2].quantity * weight(idx + 2),
+                items[idx + 1].quantity * weight(idx + 1),
+                items[idx].quantity * weight(idx),
             );
 
             // Load and multiply costs
@@ -136,42 +283,317 @@ fn calculate_totals_avx2(
         }
 
         // Horizontal sum of vectors
-        let mut totals = CalculationTotals::default();
-        totals.direct_costs = hsum_pd(direct_sum);
-        totals.labor_costs = hsum_pd(labor_sum);
-        totals.machine_op_costs = hsum_pd(machine_op_sum);
-        totals.material_costs = hsum_pd(material_sum);
-        totals.machine_costs = hsum_pd(machine_sum);
+        let mut totals = CalculationTotals {
+            direct_costs: hsum_pd(direct_sum),
+            labor_costs: hsum_pd(labor_sum),
+            machine_op_costs: hsum_pd(machine_op_sum),
+            material_costs: hsum_pd(material_sum),
+            machine_costs: hsum_pd(machine_sum),
+            ..Default::default()
+        };
 
         // Process remainder
-        for i in (chunks * 4)..len {
-            let q = items[i].quantity;
-            totals.direct_costs += q * items[i].unit_costs.direct;
-            totals.labor_costs += q * items[i].unit_costs.labor;
-            totals.machine_op_costs += q * items[i].unit_costs.machine_operator;
-            totals.material_costs += q * items[i].unit_costs.materials;
-            totals.machine_costs += q * items[i].unit_costs.machines;
+        for (idx, item) in items.iter().enumerate().skip(chunks * 4) {
+            let q = item.quantity * weight(idx);
+            totals.direct_costs += q * item.unit_costs.direct;
+            totals.labor_costs += q * item.unit_costs.labor;
+            totals.machine_op_costs += q * item.unit_costs.machine_operator;
+            totals.material_costs += q * item.unit_costs.materials;
+            totals.machine_costs += q * item.unit_costs.machines;
         }
 
-        // Apply index
-        totals.direct_costs *= settings.index;
-        totals.labor_costs *= settings.index;
-        totals.machine_op_costs *= settings.index;
-        totals.material_costs *= settings.index;
-        totals.machine_costs *= settings.index;
+        totals
+    }
+}
 
-        // Calculate overhead and profit
-        let labor_total = totals.labor_costs + totals.machine_op_costs;
-        totals.overhead = labor_total * settings.overhead_rate;
-        totals.profit = labor_total * settings.profit_rate;
+/// Minimum item count for which the `prefetch` feature switches dispatch
+/// to [`calculate_totals_avx2_aos_prefetch`]; smaller arrays stay in cache
+pub const PREFETCH_MIN_ITEMS: usize = 64 * 1024;
 
-        // Subtotal, VAT, Total
-        totals.subtotal = totals.direct_costs + totals.overhead + totals.profit;
-        totals.vat = totals.subtotal * settings.vat_rate;
-        totals.total = totals.subtotal + totals.vat;
+/// How many items ahead of the current chunk to prefetch
+#[cfg(target_arch = "x86_64")]
+const PREFETCH_DISTANCE: usize = 16;
 
-        totals
+/// AVX2 totals over array-of-structs data with software prefetch
+///
+/// Issues `_mm_prefetch` for the cache lines holding items
+/// [`PREFETCH_DISTANCE`] ahead while gathering the current chunk, to hide
+/// memory latency of the `set_pd` gathers on arrays larger than the cache.
+/// Falls back to scalar when AVX2/FMA is unavailable.
+#[cfg(target_arch = "x86_64")]
+pub fn calculate_totals_avx2_aos_prefetch(
+    items: &[ItemData],
+    settings: &CalculationSettings,
+) -> CalculationTotals {
+    if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+        return unsafe { calculate_totals_avx2_aos_prefetch_impl(items, settings) };
+    }
+
+    calculate_totals_scalar(items, settings)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+unsafe fn calculate_totals_avx2_aos_prefetch_impl(
+    items: &[ItemData],
+    settings: &CalculationSettings,
+) -> CalculationTotals {
+    use std::arch::x86_64::*;
+
+    const CACHE_LINE: usize = 64;
+    const CHUNK_BYTES: usize = 4 * std::mem::size_of::<ItemData>();
+
+    let len = items.len();
+    let mut direct_sum = _mm256_setzero_pd();
+    let mut labor_sum = _mm256_setzero_pd();
+    let mut machine_op_sum = _mm256_setzero_pd();
+    let mut material_sum = _mm256_setzero_pd();
+    let mut machine_sum = _mm256_setzero_pd();
+
+    let chunks = len / 4;
+
+    for i in 0..chunks {
+        let idx = i * 4;
+
+        // Prefetch the chunk PREFETCH_DISTANCE items ahead (only in bounds)
+        if idx + PREFETCH_DISTANCE + 4 <= len {
+            let ahead = items.as_ptr().add(idx + PREFETCH_DISTANCE) as *const i8;
+            let mut offset = 0;
+            while offset < CHUNK_BYTES {
+                _mm_prefetch(ahead.add(offset), _MM_HINT_T0);
+                offset += CACHE_LINE;
+            }
+        }
+
+        let it = &items[idx..idx + 4];
+        let q = _mm256_set_pd(it[3].quantity, it[2].quantity, it[1].quantity, it[0].quantity);
+
+        let direct = _mm256_set_pd(
+            it[3].unit_costs.direct,
+            it[2].unit_costs.direct,
+            it[1].unit_costs.direct,
+            it[0].unit_costs.direct,
+        );
+        direct_sum = _mm256_fmadd_pd(q, direct, direct_sum);
+
+        let labor = _mm256_set_pd(
+            it[3].unit_costs.labor,
+            it[2].unit_costs.labor,
+            it[1].unit_costs.labor,
+            it[0].unit_costs.labor,
+        );
+        labor_sum = _mm256_fmadd_pd(q, labor, labor_sum);
+
+        let machine_op = _mm256_set_pd(
+            it[3].unit_costs.machine_operator,
+            it[2].unit_costs.machine_operator,
+            it[1].unit_costs.machine_operator,
+            it[0].unit_costs.machine_operator,
+        );
+        machine_op_sum = _mm256_fmadd_pd(q, machine_op, machine_op_sum);
+
+        let materials = _mm256_set_pd(
+            it[3].unit_costs.materials,
+            it[2].unit_costs.materials,
+            it[1].unit_costs.materials,
+            it[0].unit_costs.materials,
+        );
+        material_sum = _mm256_fmadd_pd(q, materials, material_sum);
+
+        let machines = _mm256_set_pd(
+            it[3].unit_costs.machines,
+            it[2].unit_costs.machines,
+            it[1].unit_costs.machines,
+            it[0].unit_costs.machines,
+        );
+        machine_sum = _mm256_fmadd_pd(q, machines, machine_sum);
+    }
+
+    let mut totals = CalculationTotals {
+        direct_costs: hsum_pd(direct_sum),
+        labor_costs: hsum_pd(labor_sum),
+        machine_op_costs: hsum_pd(machine_op_sum),
+        material_costs: hsum_pd(material_sum),
+        machine_costs: hsum_pd(machine_sum),
+        ..Default::default()
+    };
+
+    // Process remainder
+    for item in &items[chunks * 4..] {
+        let q = item.quantity;
+        totals.direct_costs += q * item.unit_costs.direct;
+        totals.labor_costs += q * item.unit_costs.labor;
+        totals.machine_op_costs += q * item.unit_costs.machine_operator;
+        totals.material_costs += q * item.unit_costs.materials;
+        totals.machine_costs += q * item.unit_costs.machines;
     }
+
+    finalize_totals(&mut totals, settings);
+    totals
+}
+
+/// AVX-512 totals: 8 items per iteration with `_mm512_fmadd_pd`, a
+/// horizontal `_mm512_reduce_add_pd` and a scalar remainder
+///
+/// Caller must ensure `avx512f` is available.
+#[cfg(all(target_arch = "x86_64", feature = "avx512"))]
+#[target_feature(enable = "avx512f")]
+pub(crate) unsafe fn calculate_totals_avx512(
+    items: &[ItemData],
+    settings: &CalculationSettings,
+) -> CalculationTotals {
+    use std::arch::x86_64::*;
+
+    if settings.deterministic {
+        return calculate_totals_scalar(items, settings);
+    }
+
+    let gather = |it: &[ItemData], field: fn(&ItemData) -> f64| {
+        _mm512_set_pd(
+            field(&it[7]),
+            field(&it[6]),
+            field(&it[5]),
+            field(&it[4]),
+            field(&it[3]),
+            field(&it[2]),
+            field(&it[1]),
+            field(&it[0]),
+        )
+    };
+
+    let mut direct_sum = _mm512_setzero_pd();
+    let mut labor_sum = _mm512_setzero_pd();
+    let mut machine_op_sum = _mm512_setzero_pd();
+    let mut material_sum = _mm512_setzero_pd();
+    let mut machine_sum = _mm512_setzero_pd();
+
+    let chunks = items.len() / 8;
+
+    for i in 0..chunks {
+        let it = &items[i * 8..i * 8 + 8];
+
+        let q = gather(it, |item| item.quantity);
+        direct_sum = _mm512_fmadd_pd(q, gather(it, |item| item.unit_costs.direct), direct_sum);
+        labor_sum = _mm512_fmadd_pd(q, gather(it, |item| item.unit_costs.labor), labor_sum);
+        machine_op_sum = _mm512_fmadd_pd(
+            q,
+            gather(it, |item| item.unit_costs.machine_operator),
+            machine_op_sum,
+        );
+        material_sum =
+            _mm512_fmadd_pd(q, gather(it, |item| item.unit_costs.materials), material_sum);
+        machine_sum = _mm512_fmadd_pd(q, gather(it, |item| item.unit_costs.machines), machine_sum);
+    }
+
+    let mut totals = CalculationTotals {
+        direct_costs: _mm512_reduce_add_pd(direct_sum),
+        labor_costs: _mm512_reduce_add_pd(labor_sum),
+        machine_op_costs: _mm512_reduce_add_pd(machine_op_sum),
+        material_costs: _mm512_reduce_add_pd(material_sum),
+        machine_costs: _mm512_reduce_add_pd(machine_sum),
+        ..Default::default()
+    };
+
+    // Process remainder
+    totals.merge(&accumulate_items_scalar(&items[chunks * 8..]));
+
+    finalize_totals(&mut totals, settings);
+    totals
+}
+
+/// AVX2 fused line and totals calculation
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+unsafe fn calculate_lines_and_totals_avx2(
+    items: &[ItemData],
+    settings: &CalculationSettings,
+) -> (Vec<f64>, CalculationTotals) {
+    use std::arch::x86_64::*;
+
+    let len = items.len();
+    let mut lines = vec![0.0; len];
+
+    let mut direct_sum = _mm256_setzero_pd();
+    let mut labor_sum = _mm256_setzero_pd();
+    let mut machine_op_sum = _mm256_setzero_pd();
+    let mut material_sum = _mm256_setzero_pd();
+    let mut machine_sum = _mm256_setzero_pd();
+
+    let chunks = len / 4;
+
+    for i in 0..chunks {
+        let idx = i * 4;
+        let it = &items[idx..idx + 4];
+
+        let q = _mm256_set_pd(it[3].quantity, it[2].quantity, it[1].quantity, it[0].quantity);
+
+        // Line totals double as the direct cost contribution
+        let direct = _mm256_set_pd(
+            it[3].unit_costs.direct,
+            it[2].unit_costs.direct,
+            it[1].unit_costs.direct,
+            it[0].unit_costs.direct,
+        );
+        let line = _mm256_mul_pd(q, direct);
+        debug_assert!(idx + 4 <= lines.len());
+        _mm256_storeu_pd(lines.as_mut_ptr().add(idx), line);
+        direct_sum = _mm256_add_pd(direct_sum, line);
+
+        let labor = _mm256_set_pd(
+            it[3].unit_costs.labor,
+            it[2].unit_costs.labor,
+            it[1].unit_costs.labor,
+            it[0].unit_costs.labor,
+        );
+        labor_sum = _mm256_fmadd_pd(q, labor, labor_sum);
+
+        let machine_op = _mm256_set_pd(
+            it[3].unit_costs.machine_operator,
+            it[2].unit_costs.machine_operator,
+            it[1].unit_costs.machine_operator,
+            it[0].unit_costs.machine_operator,
+        );
+        machine_op_sum = _mm256_fmadd_pd(q, machine_op, machine_op_sum);
+
+        let materials = _mm256_set_pd(
+            it[3].unit_costs.materials,
+            it[2].unit_costs.materials,
+            it[1].unit_costs.materials,
+            it[0].unit_costs.materials,
+        );
+        material_sum = _mm256_fmadd_pd(q, materials, material_sum);
+
+        let machines = _mm256_set_pd(
+            it[3].unit_costs.machines,
+            it[2].unit_costs.machines,
+            it[1].unit_costs.machines,
+            it[0].unit_costs.machines,
+        );
+        machine_sum = _mm256_fmadd_pd(q, machines, machine_sum);
+    }
+
+    let mut totals = CalculationTotals {
+        direct_costs: hsum_pd(direct_sum),
+        labor_costs: hsum_pd(labor_sum),
+        machine_op_costs: hsum_pd(machine_op_sum),
+        material_costs: hsum_pd(material_sum),
+        machine_costs: hsum_pd(machine_sum),
+        ..Default::default()
+    };
+
+    // Process remainder
+    for (line, item) in lines[chunks * 4..].iter_mut().zip(&items[chunks * 4..]) {
+        let q = item.quantity;
+        *line = q * item.unit_costs.direct;
+        totals.direct_costs += *line;
+        totals.labor_costs += q * item.unit_costs.labor;
+        totals.machine_op_costs += q * item.unit_costs.machine_operator;
+        totals.material_costs += q * item.unit_costs.materials;
+        totals.machine_costs += q * item.unit_costs.machines;
+    }
+
+    finalize_totals(&mut totals, settings);
+    (lines, totals)
 }
 
 /// AVX2 item calculation
@@ -194,11 +616,16 @@ fn calculate_items_avx2(
         return;
     }
 
+    // Every unaligned load/store below reads 4 lanes starting at idx < len
+    assert!(len <= quantities.len() && len <= unit_costs.len());
+    assert!(len <= coefficients.len() && len <= results.len());
+
     unsafe {
         let chunks = len / 4;
 
         for i in 0..chunks {
             let idx = i * 4;
+            debug_assert!(idx + 4 <= len);
 
             let q = _mm256_loadu_pd(quantities.as_ptr().add(idx));
             let c = _mm256_loadu_pd(unit_costs.as_ptr().add(idx));
@@ -218,7 +645,7 @@ fn calculate_items_avx2(
 /// Horizontal sum of __m256d vector
 #[cfg(target_arch = "x86_64")]
 #[inline]
-unsafe fn hsum_pd(v: std::arch::x86_64::__m256d) -> f64 {
+pub(crate) unsafe fn hsum_pd(v: std::arch::x86_64::__m256d) -> f64 {
     use std::arch::x86_64::*;
     
     // Extract high and low 128-bit lanes
@@ -237,14 +664,14 @@ unsafe fn hsum_pd(v: std::arch::x86_64::__m256d) -> f64 {
 
 /// NEON implementation for ARM64
 #[cfg(target_arch = "aarch64")]
-fn calculate_totals_neon(
+pub(crate) fn calculate_totals_neon(
     items: &[ItemData],
     settings: &CalculationSettings,
 ) -> CalculationTotals {
     use std::arch::aarch64::*;
 
     let len = items.len();
-    if len < 2 {
+    if len < 2 || settings.deterministic {
         return calculate_totals_scalar(items, settings);
     }
 
@@ -300,21 +727,23 @@ fn calculate_totals_neon(
         }
 
         // Horizontal sum
-        let mut totals = CalculationTotals::default();
-        totals.direct_costs = vaddvq_f64(direct_sum);
-        totals.labor_costs = vaddvq_f64(labor_sum);
-        totals.machine_op_costs = vaddvq_f64(machine_op_sum);
-        totals.material_costs = vaddvq_f64(material_sum);
-        totals.machine_costs = vaddvq_f64(machine_sum);
+        let mut totals = CalculationTotals {
+            direct_costs: vaddvq_f64(direct_sum),
+            labor_costs: vaddvq_f64(labor_sum),
+            machine_op_costs: vaddvq_f64(machine_op_sum),
+            material_costs: vaddvq_f64(material_sum),
+            machine_costs: vaddvq_f64(machine_sum),
+            ..Default::default()
+        };
 
         // Process remainder
-        for i in (chunks * 2)..len {
-            let q = items[i].quantity;
-            totals.direct_costs += q * items[i].unit_costs.direct;
-            totals.labor_costs += q * items[i].unit_costs.labor;
-            totals.machine_op_costs += q * items[i].unit_costs.machine_operator;
-            totals.material_costs += q * items[i].unit_costs.materials;
-            totals.machine_costs += q * items[i].unit_costs.machines;
+        for item in &items[chunks * 2..] {
+            let q = item.quantity;
+            totals.direct_costs += q * item.unit_costs.direct;
+            totals.labor_costs += q * item.unit_costs.labor;
+            totals.machine_op_costs += q * item.unit_costs.machine_operator;
+            totals.material_costs += q * item.unit_costs.materials;
+            totals.machine_costs += q * item.unit_costs.machines;
         }
 
         // Apply index and calculate overhead/profit
@@ -335,6 +764,24 @@ fn calculate_totals_neon(
     }
 }
 
+/// Calculation backends compiled into this build for the target architecture
+pub fn compiled_backends() -> Vec<&'static str> {
+    let mut backends = vec!["scalar"];
+    if cfg!(target_arch = "x86_64") {
+        backends.push("avx2");
+        if cfg!(feature = "avx512") {
+            backends.push("avx512");
+        }
+        if cfg!(feature = "prefetch") {
+            backends.push("avx2-prefetch");
+        }
+    }
+    if cfg!(target_arch = "aarch64") {
+        backends.push("neon");
+    }
+    backends
+}
+
 /// Benchmark utility: calculate N items M times and return average time in microseconds
 pub fn benchmark_calculation(item_count: usize, iterations: usize) -> f64 {
     use crate::types::UnitCostsData;
@@ -407,6 +854,39 @@ mod tests {
         assert!((totals.subtotal - expected_subtotal).abs() < 0.01);
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_small_sizes_stay_in_bounds() {
+        let settings = CalculationSettings::default();
+
+        for len in 0..=17 {
+            let items = create_test_items(len);
+            let expected = calculate_totals_scalar(&items, &settings);
+
+            let totals = calculate_estimate_totals(&items, &settings);
+            assert!((totals.direct_costs - expected.direct_costs).abs() < 1e-6, "len {}", len);
+
+            let (lines, fused) = calculate_lines_and_totals(&items, &settings);
+            assert_eq!(lines.len(), len);
+            assert!((fused.direct_costs - expected.direct_costs).abs() < 1e-6, "len {}", len);
+
+            #[cfg(target_arch = "x86_64")]
+            {
+                let prefetched = calculate_totals_avx2_aos_prefetch(&items, &settings);
+                assert!((prefetched.direct_costs - expected.direct_costs).abs() < 1e-6, "len {}", len);
+            }
+
+            let quantities: Vec<f64> = items.iter().map(|item| item.quantity).collect();
+            let unit_costs: Vec<f64> = items.iter().map(|item| item.unit_costs.direct).collect();
+            let coefficients = vec![1.5; len];
+            let mut results = vec![0.0; len];
+            calculate_items(&quantities, &unit_costs, &coefficients, &mut results);
+            for (i, result) in results.iter().enumerate() {
+                assert_eq!(*result, quantities[i] * unit_costs[i] * 1.5, "len {}", len);
+            }
+        }
+    }
+
     #[test]
     fn test_simd_vs_scalar_consistency() {
         let items = create_test_items(100);
@@ -420,6 +900,198 @@ mod tests {
         assert!((scalar_totals.direct_costs - simd_totals.direct_costs).abs() < 1.0);
     }
 
+    #[test]
+    fn test_masked_totals_match_filtered_items() {
+        let items = create_test_items(11);
+        let included: Vec<bool> = (0..items.len()).map(|i| i % 2 == 0).collect();
+        let settings = CalculationSettings::default();
+
+        let filtered: Vec<ItemData> = items
+            .iter()
+            .zip(&included)
+            .filter(|(_, &include)| include)
+            .map(|(item, _)| *item)
+            .collect();
+        let expected = calculate_estimate_totals(&filtered, &settings);
+        let masked = calculate_estimate_totals_masked(&items, &included, &settings);
+
+        assert!((masked.total - expected.total).abs() < 1e-6);
+        assert!((masked.labor_costs - expected.labor_costs).abs() < 1e-6);
+
+        let all = calculate_estimate_totals(&items, &settings);
+        let mismatched = calculate_estimate_totals_masked(&items, &included[..3], &settings);
+        assert!((mismatched.total - all.total).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_calculate_lines_and_totals() {
+        let items = create_test_items(103);
+        let settings = CalculationSettings {
+            index: 8.5,
+            ..Default::default()
+        };
+
+        let (lines, totals) = calculate_lines_and_totals(&items, &settings);
+
+        assert_eq!(lines.len(), items.len());
+        assert_eq!(lines[0], items[0].quantity * items[0].unit_costs.direct);
+        assert_eq!(lines[102], items[102].quantity * items[102].unit_costs.direct);
+
+        let lines_sum: f64 = lines.iter().sum();
+        assert!((lines_sum * settings.index - totals.direct_costs).abs() < 0.01);
+
+        let reference = calculate_totals_scalar(&items, &settings);
+        assert!((reference.total - totals.total).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_deterministic_matches_scalar_bits() {
+        let items = create_test_items(1003);
+        let settings = CalculationSettings {
+            index: 8.5,
+            deterministic: true,
+            ..Default::default()
+        };
+
+        let scalar = calculate_totals_scalar(&items, &settings);
+        let results = [
+            calculate_estimate_totals(&items, &settings),
+            calculate_lines_and_totals(&items, &settings).1,
+            crate::dispatch::SimdDispatcher::new().totals(&items, &settings),
+        ];
+
+        for totals in results {
+            assert_eq!(totals.direct_costs.to_bits(), scalar.direct_costs.to_bits());
+            assert_eq!(totals.labor_costs.to_bits(), scalar.labor_costs.to_bits());
+            assert_eq!(totals.total.to_bits(), scalar.total.to_bits());
+        }
+    }
+
+    #[cfg(all(target_arch = "x86_64", feature = "avx512"))]
+    #[test]
+    fn test_avx512_vs_scalar() {
+        if !is_x86_feature_detected!("avx512f") {
+            return;
+        }
+        let settings = CalculationSettings {
+            index: 8.5,
+            ..Default::default()
+        };
+
+        for count in [0, 7, 8, 1003] {
+            let items = create_test_items(count);
+            let scalar = calculate_totals_scalar(&items, &settings);
+            let avx512 = unsafe { calculate_totals_avx512(&items, &settings) };
+
+            assert!((scalar.total - avx512.total).abs() < 1e-6 * scalar.total.max(1.0));
+            assert!((scalar.labor_costs - avx512.labor_costs).abs() < 1e-6 * scalar.labor_costs.max(1.0));
+            assert!((scalar.machine_costs - avx512.machine_costs).abs() < 1e-6 * scalar.machine_costs.max(1.0));
+        }
+    }
+
+    #[test]
+    fn test_components_sum_to_direct() {
+        let items: Vec<ItemData> = create_test_items(101)
+            .into_iter()
+            .map(|mut item| {
+                item.unit_costs.direct = 1000.0;
+                item
+            })
+            .collect();
+        let settings = CalculationSettings {
+            index: 8.5,
+            ..Default::default()
+        };
+
+        let totals = calculate_estimate_totals(&items, &settings);
+        let components = totals.components();
+        assert!((components.sum() - totals.direct_costs).abs() < 1e-6);
+        assert_eq!(components.labor, totals.labor_costs);
+        assert_eq!(components.machine_operator, totals.machine_op_costs);
+        assert_ne!(components.labor, components.machine_operator);
+    }
+
+    #[test]
+    fn test_iter_matches_slice() {
+        let items = create_test_items(1003);
+        let settings = CalculationSettings {
+            index: 8.5,
+            ..Default::default()
+        };
+
+        let slice = calculate_estimate_totals(&items, &settings);
+        let iter = calculate_estimate_totals_iter(items.iter().copied().filter(|_| true), &settings);
+        assert!((slice.total - iter.total).abs() < 0.01);
+        assert!((slice.labor_costs - iter.labor_costs).abs() < 0.01);
+
+        let deterministic = CalculationSettings {
+            deterministic: true,
+            ..settings
+        };
+        let scalar = calculate_totals_scalar(&items, &deterministic);
+        let iter = calculate_estimate_totals_iter(items.iter().copied(), &deterministic);
+        assert_eq!(iter.total.to_bits(), scalar.total.to_bits());
+
+        let empty = calculate_estimate_totals_iter(std::iter::empty(), &settings);
+        assert_eq!(empty.total, 0.0);
+    }
+
+    #[test]
+    fn test_compare_totals_delta() {
+        let a = create_test_items(1003);
+        let mut b = create_test_items(517);
+        b[0].quantity *= 3.0;
+        let settings = CalculationSettings {
+            index: 8.5,
+            ..Default::default()
+        };
+
+        let (totals_a, totals_b, delta) = compare_totals(&a, &b, &settings);
+        let alone_a = calculate_estimate_totals(&a, &settings);
+        let alone_b = calculate_estimate_totals(&b, &settings);
+
+        assert_eq!(totals_a.total, alone_a.total);
+        assert_eq!(totals_b.total, alone_b.total);
+        assert_eq!(delta.total, alone_b.total - alone_a.total);
+        assert_eq!(delta.labor_costs, alone_b.labor_costs - alone_a.labor_costs);
+        assert_eq!(delta.vat, alone_b.vat - alone_a.vat);
+        assert!(delta.direct_costs < 0.0);
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_aos_prefetch_vs_scalar() {
+        let items = create_test_items(1003);
+        let settings = CalculationSettings::default();
+
+        let scalar_totals = calculate_totals_scalar(&items, &settings);
+        let prefetch_totals = calculate_totals_avx2_aos_prefetch(&items, &settings);
+
+        assert!((scalar_totals.total - prefetch_totals.total).abs() < 1.0);
+        assert!((scalar_totals.labor_costs - prefetch_totals.labor_costs).abs() < 1.0);
+        assert!((scalar_totals.machine_costs - prefetch_totals.machine_costs).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_accumulate_and_finalize() {
+        let items = create_test_items(1001);
+        let settings = CalculationSettings {
+            index: 8.5,
+            ..Default::default()
+        };
+
+        let (first, second) = items.split_at(400);
+        let mut accumulated = accumulate_items(first);
+        accumulated.merge(&accumulate_items(second));
+        assert_eq!(accumulated.total, 0.0);
+
+        let finalized = crate::scalar::finalize(accumulated, &settings);
+        let direct = calculate_estimate_totals(&items, &settings);
+
+        assert!((finalized.total - direct.total).abs() < 0.01);
+        assert!((finalized.overhead - direct.overhead).abs() < 0.01);
+    }
+
     #[test]
     fn test_benchmark() {
         let avg_time = benchmark_calculation(10000, 100);
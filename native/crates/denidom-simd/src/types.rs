@@ -1,14 +1,18 @@
 //! SIMD data types for calculations
 
+use denidom_core::FastTotals;
+use serde::{Deserialize, Serialize};
+
 /// Item data for fast calculations (f64 for SIMD compatibility)
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct ItemData {
     pub quantity: f64,
     pub unit_costs: UnitCostsData,
 }
 
 /// Unit costs as f64 for SIMD calculations
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UnitCostsData {
     pub direct: f64,
     pub labor: f64,
@@ -30,12 +34,20 @@ impl Default for UnitCostsData {
 }
 
 /// Calculation settings as f64
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CalculationSettings {
     pub overhead_rate: f64,
     pub profit_rate: f64,
     pub vat_rate: f64,
     pub index: f64,
+    /// Force the scalar left-to-right accumulation order on every CPU.
+    ///
+    /// Floating-point addition is not associative: SIMD lanes and their
+    /// horizontal reduction add items in a different order than the scalar
+    /// loop, so results differ in the last bits from scalar and between
+    /// backends. Set this for bit-for-bit reproducible output (golden files).
+    pub deterministic: bool,
 }
 
 impl Default for CalculationSettings {
@@ -45,12 +57,13 @@ impl Default for CalculationSettings {
             profit_rate: 0.08,   // 8%
             vat_rate: 0.20,      // 20%
             index: 1.0,
+            deterministic: false,
         }
     }
 }
 
 /// Calculation result totals
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 #[repr(C)]
 pub struct CalculationTotals {
     pub direct_costs: f64,
@@ -65,7 +78,67 @@ pub struct CalculationTotals {
     pub total: f64,
 }
 
+/// Indexed cost components before overhead and profit
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ComponentTotals {
+    /// ОЗП - оплата труда рабочих
+    pub labor: f64,
+    /// ЗПМ - оплата труда машинистов
+    pub machine_operator: f64,
+    /// Материалы
+    pub materials: f64,
+    /// ЭМ - эксплуатация машин
+    pub machines: f64,
+}
+
+impl ComponentTotals {
+    /// ФОТ: labor plus machine operator wages
+    pub fn labor_fund(&self) -> f64 {
+        self.labor + self.machine_operator
+    }
+
+    /// Sum of all components; equals direct costs when unit costs are consistent
+    pub fn sum(&self) -> f64 {
+        self.labor + self.machine_operator + self.materials + self.machines
+    }
+}
+
 impl CalculationTotals {
+    /// Indexed component subtotals (ОЗП, ЗПМ, материалы, ЭМ)
+    pub fn components(&self) -> ComponentTotals {
+        ComponentTotals {
+            labor: self.labor_costs,
+            machine_operator: self.machine_op_costs,
+            materials: self.material_costs,
+            machines: self.machine_costs,
+        }
+    }
+
+    /// Add another accumulator's five cost sums to this one
+    pub fn merge(&mut self, other: &CalculationTotals) {
+        self.direct_costs += other.direct_costs;
+        self.labor_costs += other.labor_costs;
+        self.machine_op_costs += other.machine_op_costs;
+        self.material_costs += other.material_costs;
+        self.machine_costs += other.machine_costs;
+    }
+
+    /// Element-wise `other - self` over all fields
+    pub fn delta_to(&self, other: &CalculationTotals) -> CalculationTotals {
+        CalculationTotals {
+            direct_costs: other.direct_costs - self.direct_costs,
+            labor_costs: other.labor_costs - self.labor_costs,
+            machine_op_costs: other.machine_op_costs - self.machine_op_costs,
+            material_costs: other.material_costs - self.material_costs,
+            machine_costs: other.machine_costs - self.machine_costs,
+            overhead: other.overhead - self.overhead,
+            profit: other.profit - self.profit,
+            subtotal: other.subtotal - self.subtotal,
+            vat: other.vat - self.vat,
+            total: other.total - self.total,
+        }
+    }
+
     /// Format total as Russian rubles string
     pub fn format_total_rub(&self) -> String {
         format!("{:.2} ₽", self.total)
@@ -89,3 +162,20 @@ impl CalculationTotals {
         )
     }
 }
+
+impl From<&CalculationTotals> for FastTotals {
+    fn from(totals: &CalculationTotals) -> Self {
+        Self {
+            direct_costs: totals.direct_costs,
+            labor_costs: totals.labor_costs,
+            machine_op_costs: totals.machine_op_costs,
+            material_costs: totals.material_costs,
+            machine_costs: totals.machine_costs,
+            overhead: totals.overhead,
+            profit: totals.profit,
+            subtotal: totals.subtotal,
+            vat: totals.vat,
+            total: totals.total,
+        }
+    }
+}
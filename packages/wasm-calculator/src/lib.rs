@@ -1,5 +1,6 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // When the `console_error_panic_hook` feature is enabled, we can call the
 // `set_panic_hook` function at least once during initialization, and then
@@ -18,12 +19,31 @@ pub struct CalculatorItem {
     pub price: f64,
     #[serde(default = "default_coefficient")]
     pub coefficient: f64,
+    // Optional split of `price` into cost components (per unit)
+    #[serde(default)]
+    pub labor: f64,
+    #[serde(default)]
+    pub machine_operator: f64,
+    #[serde(default)]
+    pub materials: f64,
+    #[serde(default)]
+    pub machines: f64,
 }
 
 fn default_coefficient() -> f64 {
     1.0
 }
 
+impl CalculatorItem {
+    fn cost(&self) -> f64 {
+        self.quantity * self.price * self.coefficient
+    }
+
+    fn component_cost(&self, per_unit: f64) -> f64 {
+        self.quantity * per_unit * self.coefficient
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct CalculatorOptions {
     #[serde(default = "default_overhead_rate")]
@@ -62,6 +82,24 @@ pub struct CalculationResult {
     pub items_count: usize,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct ComponentShare {
+    pub amount: f64,
+    pub share: f64,
+}
+
+// ОЗП / ЗПМ / материалы / ЭМ split of direct costs; the part of the price
+// not covered by the items' component split is reported as `unallocated`
+#[derive(Serialize, Deserialize)]
+pub struct ComponentShares {
+    pub direct: f64,
+    pub labor: ComponentShare,
+    pub machine_operator: ComponentShare,
+    pub materials: ComponentShare,
+    pub machines: ComponentShare,
+    pub unallocated: ComponentShare,
+}
+
 #[wasm_bindgen]
 pub struct Calculator {
     options: CalculatorOptions,
@@ -100,38 +138,18 @@ impl Calculator {
 
     fn calculate_internal(&self, items: &[CalculatorItem]) -> CalculationResult {
         // Calculate subtotal using SIMD-like optimization
-        let subtotal: f64 = items
-            .iter()
-            .map(|item| item.quantity * item.price * item.coefficient)
-            .sum();
-
-        // Calculate overhead
-        let overhead = subtotal * self.options.overhead_rate;
-
-        // Calculate profit
-        let profit = (subtotal + overhead) * self.options.profit_rate;
-
-        // Calculate base total
-        let base_total = subtotal + overhead + profit;
-
-        // Calculate VAT
-        let vat = if self.options.include_vat {
-            base_total * self.options.vat_rate
-        } else {
-            0.0
-        };
-
-        // Calculate final total
-        let total = base_total + vat;
-
-        CalculationResult {
-            subtotal: round_to_cents(subtotal),
-            overhead: round_to_cents(overhead),
-            profit: round_to_cents(profit),
-            vat: round_to_cents(vat),
-            total: round_to_cents(total),
-            items_count: items.len(),
-        }
+        let subtotal: f64 = items.iter().map(CalculatorItem::cost).sum();
+
+        totals_from_subtotal(subtotal, items.len(), &self.options)
+    }
+
+    #[wasm_bindgen]
+    pub fn component_shares(&self, items_json: &str) -> Result<String, JsValue> {
+        let items: Vec<CalculatorItem> = serde_json::from_str(items_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse items: {}", e)))?;
+
+        serde_json::to_string(&component_shares_internal(&items))
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize shares: {}", e)))
     }
 
     #[wasm_bindgen]
@@ -170,10 +188,159 @@ impl Default for Calculator {
     }
 }
 
+// Overhead, profit and VAT on top of a direct-cost subtotal
+fn totals_from_subtotal(subtotal: f64, items_count: usize, options: &CalculatorOptions) -> CalculationResult {
+    // Calculate overhead
+    let overhead = subtotal * options.overhead_rate;
+
+    // Calculate profit
+    let profit = (subtotal + overhead) * options.profit_rate;
+
+    // Calculate base total
+    let base_total = subtotal + overhead + profit;
+
+    // Calculate VAT
+    let vat = if options.include_vat {
+        base_total * options.vat_rate
+    } else {
+        0.0
+    };
+
+    // Calculate final total
+    let total = base_total + vat;
+
+    CalculationResult {
+        subtotal: round_to_cents(subtotal),
+        overhead: round_to_cents(overhead),
+        profit: round_to_cents(profit),
+        vat: round_to_cents(vat),
+        total: round_to_cents(total),
+        items_count,
+    }
+}
+
+fn component_shares_internal(items: &[CalculatorItem]) -> ComponentShares {
+    let sum = |per_unit: fn(&CalculatorItem) -> f64| -> f64 {
+        items.iter().map(|item| item.component_cost(per_unit(item))).sum()
+    };
+    let direct: f64 = items.iter().map(CalculatorItem::cost).sum();
+    let labor = sum(|item| item.labor);
+    let machine_operator = sum(|item| item.machine_operator);
+    let materials = sum(|item| item.materials);
+    let machines = sum(|item| item.machines);
+    let unallocated = direct - (labor + machine_operator + materials + machines);
+
+    let share = |amount: f64| ComponentShare {
+        amount: round_to_cents(amount),
+        share: if direct == 0.0 { 0.0 } else { amount / direct },
+    };
+
+    ComponentShares {
+        direct: round_to_cents(direct),
+        labor: share(labor),
+        machine_operator: share(machine_operator),
+        materials: share(materials),
+        machines: share(machines),
+        unallocated: share(unallocated),
+    }
+}
+
 fn round_to_cents(value: f64) -> f64 {
     (value * 100.0).round() / 100.0
 }
 
+// Stateful calculator for incrementally edited estimates: each edit adjusts
+// a running subtotal instead of recalculating the whole item list
+#[wasm_bindgen]
+pub struct StreamingCalculator {
+    options: CalculatorOptions,
+    costs: HashMap<String, f64>,
+    subtotal: f64,
+}
+
+#[wasm_bindgen]
+impl StreamingCalculator {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> StreamingCalculator {
+        #[cfg(feature = "console_error_panic_hook")]
+        set_panic_hook();
+
+        StreamingCalculator {
+            options: CalculatorOptions::default(),
+            costs: HashMap::new(),
+            subtotal: 0.0,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn set_options(&mut self, options_json: &str) -> Result<(), JsValue> {
+        self.options = serde_json::from_str(options_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse options: {}", e)))?;
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn add_item(&mut self, item_json: &str) -> Result<(), JsValue> {
+        let item = parse_item(item_json)?;
+        if self.costs.contains_key(&item.id) {
+            return Err(JsValue::from_str(&format!("Item {} already exists", item.id)));
+        }
+        self.insert(item.id.clone(), item.cost());
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn update_item(&mut self, id: &str, item_json: &str) -> Result<(), JsValue> {
+        let item = parse_item(item_json)?;
+        if !self.remove_item(id) {
+            return Err(JsValue::from_str(&format!("Item {} not found", id)));
+        }
+        self.insert(id.to_string(), item.cost());
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn remove_item(&mut self, id: &str) -> bool {
+        match self.costs.remove(id) {
+            Some(cost) => {
+                self.subtotal -= cost;
+                if self.costs.is_empty() {
+                    // Drop accumulated rounding drift
+                    self.subtotal = 0.0;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn totals(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.totals_internal())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+    }
+
+    fn totals_internal(&self) -> CalculationResult {
+        totals_from_subtotal(self.subtotal, self.costs.len(), &self.options)
+    }
+
+    fn insert(&mut self, id: String, cost: f64) {
+        self.subtotal += cost;
+        self.costs.insert(id, cost);
+    }
+}
+
+impl Default for StreamingCalculator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn parse_item(item_json: &str) -> Result<CalculatorItem, JsValue> {
+    serde_json::from_str(item_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse item: {}", e)))
+}
+
 // Batch calculation for performance
 #[wasm_bindgen]
 pub fn batch_calculate(items_json: &str, options_json: &str) -> Result<String, JsValue> {
@@ -182,6 +349,43 @@ pub fn batch_calculate(items_json: &str, options_json: &str) -> Result<String, J
     calculator.calculate(items_json)
 }
 
+// Currency formatting, e.g. "1 234 567,89 ₽" (NBSP grouping, comma decimal,
+// midpoints rounded away from zero like the native format_rub_grouped)
+#[wasm_bindgen]
+pub fn format_rub(value: f64, decimal_places: u8) -> String {
+    const NBSP: char = '\u{a0}';
+
+    let scale = 10f64.powi(decimal_places as i32);
+    let rounded = (value.abs() * scale).round() / scale;
+    let formatted = format!("{:.*}", decimal_places as usize, rounded);
+    let (int_part, frac_part) = match formatted.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (formatted.as_str(), None),
+    };
+
+    let mut result = String::with_capacity(formatted.len() + int_part.len() / 3 + 4);
+    let is_zero = formatted.bytes().all(|b| b == b'0' || b == b'.');
+    if value.is_sign_negative() && !is_zero {
+        result.push('-');
+    }
+
+    for (i, digit) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            result.push(NBSP);
+        }
+        result.push(digit);
+    }
+
+    if let Some(frac_part) = frac_part {
+        result.push(',');
+        result.push_str(frac_part);
+    }
+
+    result.push(NBSP);
+    result.push('₽');
+    result
+}
+
 // Version info
 #[wasm_bindgen]
 pub fn version() -> String {
@@ -222,6 +426,63 @@ mod tests {
         assert_eq!(result.items_count, 1);
     }
 
+    #[test]
+    fn test_format_rub_grouped() {
+        assert_eq!(format_rub(1234567.891, 2), "1\u{a0}234\u{a0}567,89\u{a0}₽");
+        assert_eq!(format_rub(-1000.0, 0), "-1\u{a0}000\u{a0}₽");
+        assert_eq!(format_rub(999.5, 1), "999,5\u{a0}₽");
+        assert_eq!(format_rub(-0.001, 2), "0,00\u{a0}₽");
+        assert_eq!(format_rub(0.125, 2), "0,13\u{a0}₽");
+        assert_eq!(format_rub(2.5, 0), "3\u{a0}₽");
+    }
+
+    #[test]
+    fn test_streaming_matches_batch() {
+        let items = [
+            r#"{"id": "1", "name": "Кладка", "unit": "м3", "quantity": 12.5, "price": 4200, "coefficient": 1}"#,
+            r#"{"id": "2", "name": "Штукатурка", "unit": "м2", "quantity": 80, "price": 350.75}"#,
+            r#"{"id": "3", "name": "Окраска", "unit": "м2", "quantity": 80, "price": 120, "coefficient": 1.15}"#,
+        ];
+
+        let mut streaming = StreamingCalculator::new();
+        for item in items {
+            streaming.add_item(item).unwrap();
+        }
+        assert!(streaming.remove_item("2"));
+        assert!(!streaming.remove_item("2"));
+
+        let batch_json = format!("[{},{}]", items[0], items[2]);
+        let batch: CalculationResult =
+            serde_json::from_str(&Calculator::new().calculate(&batch_json).unwrap()).unwrap();
+        let streamed: CalculationResult = serde_json::from_str(&streaming.totals().unwrap()).unwrap();
+
+        assert_eq!(streamed.subtotal, batch.subtotal);
+        assert_eq!(streamed.total, batch.total);
+        assert_eq!(streamed.items_count, 2);
+    }
+
+    #[test]
+    fn test_component_shares_sum_to_one() {
+        let items_json = r#"[
+            {"id": "1", "name": "Кладка", "unit": "м3", "quantity": 12.5, "price": 4200,
+             "labor": 1200, "machine_operator": 150, "materials": 2500, "machines": 350},
+            {"id": "2", "name": "Окраска", "unit": "м2", "quantity": 80, "price": 120, "coefficient": 1.15,
+             "labor": 60, "materials": 60}
+        ]"#;
+
+        let shares_json = Calculator::new().component_shares(items_json).unwrap();
+        let shares: ComponentShares = serde_json::from_str(&shares_json).unwrap();
+
+        let total = shares.labor.share
+            + shares.machine_operator.share
+            + shares.materials.share
+            + shares.machines.share
+            + shares.unallocated.share;
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(shares.unallocated.share.abs() < 1e-9);
+        assert_eq!(shares.labor.amount, 20520.0); // 12.5 × 1200 + 80 × 60 × 1.15
+    }
+
     #[test]
     fn test_round_to_cents() {
         assert_eq!(round_to_cents(1.234), 1.23);